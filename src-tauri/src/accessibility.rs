@@ -0,0 +1,123 @@
+use std::ffi::c_void;
+
+use cocoa::{
+    base::{id, nil},
+    foundation::{NSPoint, NSSize, NSString},
+};
+use objc::{msg_send, sel, sel_impl};
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXUIElementCreateApplication(pid: i32) -> id;
+    fn AXUIElementCopyAttributeValue(element: id, attribute: id, value: *mut id) -> i32;
+    fn AXUIElementSetAttributeValue(element: id, attribute: id, value: id) -> i32;
+    fn AXValueCreate(value_type: u32, value: *const c_void) -> id;
+    fn AXValueGetValue(value: id, value_type: u32, value: *mut c_void) -> bool;
+    // Not in the public headers, but the only way to match an AX window to its CGWindowID
+    fn _AXUIElementGetWindow(element: id, window_id: *mut u32) -> i32;
+}
+
+/// kAXErrorSuccess
+const AX_ERROR_SUCCESS: i32 = 0;
+
+/// kAXValueCGPointType
+const AX_VALUE_CG_POINT: u32 = 1;
+/// kAXValueCGSizeType
+const AX_VALUE_CG_SIZE: u32 = 2;
+
+/// The accessibility element of an app, autoreleased. Must be called inside an autorelease pool.
+pub(crate) fn application_element(pid: i32) -> id {
+    unsafe {
+        let element = AXUIElementCreateApplication(pid);
+        msg_send![element, autorelease]
+    }
+}
+
+/// Reads an attribute such as `AXWindows` or `AXTitle`, autoreleased. `nil` when the element
+/// doesn't have it or accessibility access isn't granted.
+pub(crate) fn attribute(element: id, name: &str) -> id {
+    unsafe {
+        let mut value: id = nil;
+        if AXUIElementCopyAttributeValue(element, ns_string(name), &mut value) != AX_ERROR_SUCCESS
+            || value == nil
+        {
+            return nil;
+        }
+
+        msg_send![value, autorelease]
+    }
+}
+
+pub(crate) fn set_attribute(element: id, name: &str, value: id) -> Result<(), String> {
+    let result = unsafe { AXUIElementSetAttributeValue(element, ns_string(name), value) };
+
+    if result != AX_ERROR_SUCCESS {
+        return Err(format!("Unable to set {} ({})", name, result));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn position(element: id) -> Option<NSPoint> {
+    let value = attribute(element, "AXPosition");
+    let mut point = NSPoint::new(0.0, 0.0);
+
+    let success = value != nil
+        && unsafe {
+            AXValueGetValue(
+                value,
+                AX_VALUE_CG_POINT,
+                &mut point as *mut NSPoint as *mut c_void,
+            )
+        };
+
+    success.then_some(point)
+}
+
+pub(crate) fn size(element: id) -> Option<NSSize> {
+    let value = attribute(element, "AXSize");
+    let mut size = NSSize::new(0.0, 0.0);
+
+    let success = value != nil
+        && unsafe {
+            AXValueGetValue(
+                value,
+                AX_VALUE_CG_SIZE,
+                &mut size as *mut NSSize as *mut c_void,
+            )
+        };
+
+    success.then_some(size)
+}
+
+/// Moves a window, in global display coordinates with the origin at the top left
+pub(crate) fn set_position(element: id, point: NSPoint) -> Result<(), String> {
+    unsafe {
+        let value = AXValueCreate(AX_VALUE_CG_POINT, &point as *const NSPoint as *const c_void);
+        let value: id = msg_send![value, autorelease];
+        set_attribute(element, "AXPosition", value)
+    }
+}
+
+pub(crate) fn set_size(element: id, size: NSSize) -> Result<(), String> {
+    unsafe {
+        let value = AXValueCreate(AX_VALUE_CG_SIZE, &size as *const NSSize as *const c_void);
+        let value: id = msg_send![value, autorelease];
+        set_attribute(element, "AXSize", value)
+    }
+}
+
+/// The CGWindowID of a window element
+pub(crate) fn window_id(element: id) -> Option<u32> {
+    let mut window_id = 0;
+    let result = unsafe { _AXUIElementGetWindow(element, &mut window_id) };
+
+    (result == AX_ERROR_SUCCESS).then_some(window_id)
+}
+
+fn ns_string(string: &str) -> id {
+    unsafe {
+        let string = NSString::alloc(nil).init_str(string);
+        msg_send![string, autorelease]
+    }
+}
@@ -0,0 +1,125 @@
+use std::path::PathBuf;
+
+use block::ConcreteBlock;
+use cocoa::{
+    base::{id, nil},
+    foundation::NSString,
+};
+use objc::{class, msg_send, sel, sel_impl};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{events, get_state, nsstring_to_string, screen::Color};
+
+const APPEARANCE_CHANGED_EVENT: &str = "spotlight://appearance-changed";
+
+/// Distributed notifications posted when the appearance, accent or highlight color changes
+const APPEARANCE_NOTIFICATIONS: [&str; 2] = [
+    "AppleInterfaceThemeChangedNotification",
+    "AppleColorPreferencesChangedNotification",
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemAppearance {
+    theme: Theme,
+    accent_color: Color,
+    highlight_color: Color,
+    /// Desktop picture of the display the panel is on
+    wallpaper: Option<PathBuf>,
+}
+
+/// Returns the system theme, accent and highlight colors and the wallpaper of the panel's display
+#[tauri::command]
+pub fn get_system_appearance(app_handle: AppHandle<Wry>) -> SystemAppearance {
+    system_appearance(&app_handle)
+}
+
+pub(crate) fn current_theme() -> Theme {
+    // Read from the global domain rather than NSApp's effective appearance, which lags behind
+    // when this is called from the theme change notification
+    let style: id = unsafe {
+        let defaults: id = msg_send![class!(NSUserDefaults), standardUserDefaults];
+        let key = NSString::alloc(nil).init_str("AppleInterfaceStyle");
+        let key: id = msg_send![key, autorelease];
+        msg_send![defaults, stringForKey: key]
+    };
+
+    match nsstring_to_string!(style).as_deref() {
+        Some("Dark") => Theme::Dark,
+        _ => Theme::Light,
+    }
+}
+
+fn system_appearance(app_handle: &AppHandle<Wry>) -> SystemAppearance {
+    let (accent_color, highlight_color) = unsafe {
+        let accent_color: id = msg_send![class!(NSColor), controlAccentColor];
+        let highlight_color: id = msg_send![class!(NSColor), selectedTextBackgroundColor];
+        (accent_color, highlight_color)
+    };
+
+    SystemAppearance {
+        theme: current_theme(),
+        accent_color: Color::from_ns_color(accent_color),
+        highlight_color: Color::from_ns_color(highlight_color),
+        wallpaper: wallpaper(app_handle),
+    }
+}
+
+fn wallpaper(app_handle: &AppHandle<Wry>) -> Option<PathBuf> {
+    let screen: id = match get_state!(app_handle, panel, clone) {
+        Some(panel) => unsafe { msg_send![&*panel, screen] },
+        None => nil,
+    };
+
+    let path: id = unsafe {
+        let screen: id = if screen == nil {
+            msg_send![class!(NSScreen), mainScreen]
+        } else {
+            screen
+        };
+
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let url: id = msg_send![workspace, desktopImageURLForScreen: screen];
+        msg_send![url, path]
+    };
+
+    nsstring_to_string!(path).map(PathBuf::from)
+}
+
+/// Emits `spotlight://appearance-changed` with the new appearance whenever the theme, accent or
+/// highlight color changes. Must be called on the main thread.
+pub fn watch_appearance(app_handle: AppHandle<Wry>) {
+    unsafe {
+        let center: id = msg_send![class!(NSDistributedNotificationCenter), defaultCenter];
+        let queue: id = msg_send![class!(NSOperationQueue), mainQueue];
+
+        for name in APPEARANCE_NOTIFICATIONS {
+            let handle = app_handle.clone();
+            let observer = ConcreteBlock::new(move |_notification: id| {
+                let _ = events::emit(
+                    &handle,
+                    APPEARANCE_CHANGED_EVENT,
+                    system_appearance(&handle),
+                );
+            })
+            .copy();
+
+            let name = NSString::alloc(nil).init_str(name);
+            let _: id = msg_send![
+                center,
+                addObserverForName: name
+                object: nil
+                queue: queue
+                usingBlock: &*observer
+            ];
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use std::{f64::consts::PI, sync::Mutex, thread, time::Duration};
+
+use cocoa::{
+    base::{id, nil},
+    foundation::NSString,
+};
+use objc::{class, msg_send, sel, sel_impl};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{
+    appearance::Theme,
+    events, get_state,
+    settings::{self, Settings},
+};
+
+const SCHEDULED_THEME_CHANGED_EVENT: &str = "spotlight://scheduled-theme-changed";
+
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// NSCalendarUnit
+const CALENDAR_UNIT_YEAR: u64 = 1 << 2;
+const CALENDAR_UNIT_DAY: u64 = 1 << 4;
+const CALENDAR_UNIT_HOUR: u64 = 1 << 5;
+const CALENDAR_UNIT_MINUTE: u64 = 1 << 6;
+
+/// When the panel switches between the light and dark appearance, independently of the system
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum AppearanceSchedule {
+    /// Light from `light_at` until `dark_at`, both as `HH:MM` in local time
+    #[serde(rename_all = "camelCase")]
+    Custom { light_at: String, dark_at: String },
+    /// Light between sunrise and sunset at the given coordinates
+    Solar { latitude: f64, longitude: f64 },
+}
+
+impl AppearanceSchedule {
+    fn theme_now(&self) -> Result<Theme, String> {
+        let now = LocalTime::now();
+
+        let (light_at, dark_at) = match self {
+            AppearanceSchedule::Custom { light_at, dark_at } => {
+                (parse_time(light_at)?, parse_time(dark_at)?)
+            }
+            AppearanceSchedule::Solar {
+                latitude,
+                longitude,
+            } => match sun_times(now.day_of_year, *latitude, *longitude, now.utc_offset) {
+                SunTimes::Daily { sunrise, sunset } => (sunrise, sunset),
+                SunTimes::PolarDay => return Ok(Theme::Light),
+                SunTimes::PolarNight => return Ok(Theme::Dark),
+            },
+        };
+
+        let light = if light_at <= dark_at {
+            now.minutes >= light_at && now.minutes < dark_at
+        } else {
+            // Light period spanning midnight
+            now.minutes >= light_at || now.minutes < dark_at
+        };
+
+        Ok(if light { Theme::Light } else { Theme::Dark })
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct ScheduledThemePayload {
+    /// `null` once the panel follows the system again
+    theme: Option<Theme>,
+}
+
+/// Last theme applied by the scheduler, `None` when the panel follows the system
+#[derive(Default)]
+pub struct State(Mutex<Option<Theme>>);
+
+/// Sets the appearance schedule and applies it right away. `null` makes the panel follow the
+/// system appearance again.
+#[tauri::command]
+pub fn set_appearance_schedule(
+    app_handle: AppHandle<Wry>,
+    schedule: Option<AppearanceSchedule>,
+) -> Result<(), String> {
+    if let Some(schedule) = &schedule {
+        schedule.theme_now()?;
+    }
+
+    let settings = Settings {
+        appearance_schedule: schedule,
+        ..settings::settings(&app_handle)
+    };
+    settings::set_settings(app_handle.clone(), settings)?;
+
+    apply_schedule(&app_handle);
+
+    Ok(())
+}
+
+/// Applies the schedule from the settings once a minute for the lifetime of the app
+pub fn watch_appearance_schedule(app_handle: AppHandle<Wry>) {
+    thread::spawn(move || loop {
+        let handle = app_handle.clone();
+        let _ = app_handle.run_on_main_thread(move || apply_schedule(&handle));
+
+        thread::sleep(SCHEDULE_CHECK_INTERVAL);
+    });
+}
+
+/// Switches the panel's appearance if the scheduled theme changed and emits
+/// `spotlight://scheduled-theme-changed`. Must be called on the main thread.
+fn apply_schedule(app_handle: &AppHandle<Wry>) {
+    let panel = match get_state!(app_handle, panel, clone) {
+        Some(panel) => panel,
+        None => return,
+    };
+
+    let theme = settings::settings(app_handle)
+        .appearance_schedule
+        .and_then(|schedule| schedule.theme_now().ok());
+
+    {
+        let state = app_handle.state::<State>();
+        let mut current = state.0.lock().unwrap();
+
+        if theme == *current {
+            return;
+        }
+
+        *current = theme;
+    }
+
+    let appearance: id = match theme {
+        Some(theme) => unsafe {
+            let name = NSString::alloc(nil).init_str(theme_name(theme));
+            let name: id = msg_send![name, autorelease];
+            msg_send![class!(NSAppearance), appearanceNamed: name]
+        },
+        None => nil,
+    };
+
+    let _: () = unsafe { msg_send![&*panel, setAppearance: appearance] };
+
+    let _ = events::emit(
+        app_handle,
+        SCHEDULED_THEME_CHANGED_EVENT,
+        ScheduledThemePayload { theme },
+    );
+}
+
+fn theme_name(theme: Theme) -> &'static str {
+    match theme {
+        Theme::Light => "NSAppearanceNameAqua",
+        Theme::Dark => "NSAppearanceNameDarkAqua",
+    }
+}
+
+/// Parses `HH:MM` into minutes since midnight
+fn parse_time(time: &str) -> Result<f64, String> {
+    let invalid = || format!("Invalid time \"{}\", expected HH:MM", time);
+
+    let (hours, minutes) = time.split_once(':').ok_or_else(invalid)?;
+    let hours: u32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u32 = minutes.parse().map_err(|_| invalid())?;
+
+    if hours > 23 || minutes > 59 {
+        return Err(invalid());
+    }
+
+    Ok((hours * 60 + minutes) as f64)
+}
+
+struct LocalTime {
+    /// Minutes since midnight
+    minutes: f64,
+    day_of_year: u32,
+    /// Offset from UTC in minutes
+    utc_offset: f64,
+}
+
+impl LocalTime {
+    fn now() -> Self {
+        unsafe {
+            let calendar: id = msg_send![class!(NSCalendar), currentCalendar];
+            let date: id = msg_send![class!(NSDate), date];
+            let time_zone: id = msg_send![class!(NSTimeZone), localTimeZone];
+
+            let hour: i64 = msg_send![calendar, component: CALENDAR_UNIT_HOUR fromDate: date];
+            let minute: i64 = msg_send![calendar, component: CALENDAR_UNIT_MINUTE fromDate: date];
+            let day_of_year: u64 = msg_send![
+                calendar,
+                ordinalityOfUnit: CALENDAR_UNIT_DAY
+                inUnit: CALENDAR_UNIT_YEAR
+                forDate: date
+            ];
+            let utc_offset: i64 = msg_send![time_zone, secondsFromGMTForDate: date];
+
+            Self {
+                minutes: (hour * 60 + minute) as f64,
+                day_of_year: day_of_year as u32,
+                utc_offset: utc_offset as f64 / 60.0,
+            }
+        }
+    }
+}
+
+enum SunTimes {
+    /// Sunrise and sunset in local minutes since midnight
+    Daily {
+        sunrise: f64,
+        sunset: f64,
+    },
+    PolarDay,
+    PolarNight,
+}
+
+/// NOAA's approximation of sunrise and sunset, accurate to a minute or two.
+///
+/// macOS doesn't expose its own solar calculation (used by Night Shift) publicly.
+fn sun_times(day_of_year: u32, latitude: f64, longitude: f64, utc_offset: f64) -> SunTimes {
+    let gamma = 2.0 * PI / 365.0 * (day_of_year as f64 - 1.0);
+
+    let equation_of_time = 229.18
+        * (0.000075 + 0.001868 * gamma.cos()
+            - 0.032077 * gamma.sin()
+            - 0.014615 * (2.0 * gamma).cos()
+            - 0.040849 * (2.0 * gamma).sin());
+
+    let declination = 0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin()
+        - 0.006758 * (2.0 * gamma).cos()
+        + 0.000907 * (2.0 * gamma).sin()
+        - 0.002697 * (3.0 * gamma).cos()
+        + 0.00148 * (3.0 * gamma).sin();
+
+    let latitude = latitude.to_radians();
+
+    // Zenith of 90.833° accounts for refraction and the size of the solar disk
+    let cos_hour_angle = 90.833_f64.to_radians().cos() / (latitude.cos() * declination.cos())
+        - latitude.tan() * declination.tan();
+
+    if cos_hour_angle < -1.0 {
+        return SunTimes::PolarDay;
+    }
+
+    if cos_hour_angle > 1.0 {
+        return SunTimes::PolarNight;
+    }
+
+    let hour_angle = cos_hour_angle.acos().to_degrees();
+    let to_local = |minutes: f64| (minutes + utc_offset).rem_euclid(24.0 * 60.0);
+
+    SunTimes::Daily {
+        sunrise: to_local(720.0 - 4.0 * (longitude + hour_angle) - equation_of_time),
+        sunset: to_local(720.0 - 4.0 * (longitude - hour_angle) - equation_of_time),
+    }
+}
@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Wry};
+
+use crate::{diagnostics, system_info::sysctl};
+
+/// How much longer timings are allowed to take under Rosetta. Translated processes get events
+/// and activations later, so windows tuned on native builds are missed.
+const TRANSLATED_TIMING_FACTOR: f64 = 1.5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Arch {
+    Arm64,
+    X86_64,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Architecture {
+    /// Architecture of the Mac
+    pub host: Arch,
+    /// Architecture of the binary slice that's running
+    pub process: Arch,
+    /// Whether the process is an Intel build translated by Rosetta on Apple Silicon
+    pub translated: bool,
+}
+
+impl Architecture {
+    pub(crate) fn current() -> Self {
+        let process = if cfg!(target_arch = "aarch64") {
+            Arch::Arm64
+        } else {
+            Arch::X86_64
+        };
+
+        // Missing on macOS versions without Rosetta 2 and on Intel Macs
+        let translated = sysctl::<i32>("sysctl.proc_translated").unwrap_or(0) == 1;
+
+        // Under Rosetta the process reports itself as Intel, the CPU flag doesn't lie
+        let host = if process == Arch::Arm64
+            || translated
+            || sysctl::<i32>("hw.optional.arm64").unwrap_or(0) == 1
+        {
+            Arch::Arm64
+        } else {
+            Arch::X86_64
+        };
+
+        Self {
+            host,
+            process,
+            translated,
+        }
+    }
+}
+
+/// Stretches a timing tuned on native builds when running under Rosetta
+pub(crate) fn adjusted_timing(duration: Duration) -> Duration {
+    if Architecture::current().translated {
+        duration.mul_f64(TRANSLATED_TIMING_FACTOR)
+    } else {
+        duration
+    }
+}
+
+/// Returns the architecture of the Mac and of the running binary, and whether it's translated
+/// by Rosetta
+#[tauri::command]
+pub fn get_architecture() -> Architecture {
+    Architecture::current()
+}
+
+/// Warns the frontend when the Intel build runs under Rosetta on Apple Silicon, where focus
+/// restoration is less reliable than natively
+pub(crate) fn warn_if_translated(app_handle: &AppHandle<Wry>) {
+    if !Architecture::current().translated {
+        return;
+    }
+
+    diagnostics::warn(
+        app_handle,
+        "rosettaTranslation",
+        "Running the Intel build under Rosetta, install the Apple Silicon or universal build for \
+         reliable focus restoration.",
+    );
+}
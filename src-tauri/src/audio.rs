@@ -0,0 +1,154 @@
+use std::sync::Mutex;
+
+use block::ConcreteBlock;
+use cocoa::base::{id, nil, BOOL, YES};
+use objc::{class, msg_send, rc::autoreleasepool, runtime::Object, sel, sel_impl};
+use objc_id::Id;
+use serde::Serialize;
+use tauri::{async_runtime, AppHandle, Manager, Wry};
+
+use crate::{
+    events,
+    locale::{t, Text},
+};
+
+#[link(name = "AVFoundation", kind = "framework")]
+extern "C" {
+    static AVMediaTypeAudio: id;
+}
+
+const AUDIO_LEVEL_EVENT: &str = "spotlight://audio-level";
+
+/// AVAuthorizationStatus
+const AUTHORIZATION_NOT_DETERMINED: i64 = 0;
+const AUTHORIZATION_AUTHORIZED: i64 = 3;
+
+/// Frames per tap callback, ~20 level updates per second at 48kHz
+const TAP_BUFFER_SIZE: u32 = 2048;
+
+#[derive(Default)]
+pub struct State(Mutex<Option<Id<Object>>>);
+
+#[derive(Clone, Serialize)]
+struct AudioLevelPayload {
+    /// Root mean square of the buffer's samples, 0.0 to 1.0
+    rms: f32,
+    /// `rms` in decibels full scale
+    db: f32,
+}
+
+/// Asks for microphone access if it hasn't been decided yet, fails if it's not granted
+pub(crate) async fn request_microphone_access() -> Result<(), String> {
+    let status: i64 = unsafe {
+        msg_send![class!(AVCaptureDevice), authorizationStatusForMediaType: AVMediaTypeAudio]
+    };
+
+    match status {
+        AUTHORIZATION_AUTHORIZED => Ok(()),
+        AUTHORIZATION_NOT_DETERMINED => {
+            let (tx, mut rx) = async_runtime::channel(1);
+
+            // Blocks aren't Send, keep this one out of the await below
+            {
+                let completion_handler = ConcreteBlock::new(move |granted: BOOL| {
+                    let _ = tx.try_send(granted == YES);
+                })
+                .copy();
+
+                let _: () = unsafe {
+                    msg_send![
+                        class!(AVCaptureDevice),
+                        requestAccessForMediaType: AVMediaTypeAudio
+                        completionHandler: &*completion_handler
+                    ]
+                };
+            }
+
+            match rx.recv().await {
+                Some(true) => Ok(()),
+                _ => Err(t(Text::MicrophoneNotGranted).into()),
+            }
+        }
+        _ => Err(t(Text::MicrophoneDenied).into()),
+    }
+}
+
+/// Starts streaming the microphone input level as `spotlight://audio-level` events.
+///
+/// Asks for microphone access the first time. Does nothing if the meter is already running.
+#[tauri::command]
+pub async fn start_audio_level_meter(app_handle: AppHandle<Wry>) -> Result<(), String> {
+    request_microphone_access().await?;
+
+    let state = app_handle.state::<State>();
+    let mut engine = state.0.lock().unwrap();
+
+    if engine.is_some() {
+        return Ok(());
+    }
+
+    autoreleasepool(|| unsafe {
+        let audio_engine: id = msg_send![class!(AVAudioEngine), new];
+        let input_node: id = msg_send![audio_engine, inputNode];
+
+        let handle = app_handle.clone();
+        let tap = ConcreteBlock::new(move |buffer: id, _when: id| {
+            let rms = buffer_rms(buffer);
+            let payload = AudioLevelPayload {
+                rms,
+                db: 20.0 * rms.max(f32::EPSILON).log10(),
+            };
+
+            let _ = events::emit(&handle, AUDIO_LEVEL_EVENT, payload);
+        })
+        .copy();
+
+        let _: () = msg_send![
+            input_node,
+            installTapOnBus: 0usize
+            bufferSize: TAP_BUFFER_SIZE
+            format: nil
+            block: &*tap
+        ];
+
+        let mut error: id = nil;
+        let started: BOOL = msg_send![audio_engine, startAndReturnError: &mut error];
+
+        if started != YES {
+            let _: () = msg_send![input_node, removeTapOnBus: 0usize];
+            let _: () = msg_send![audio_engine, release];
+            return Err(t(Text::AudioInputFailed).to_string());
+        }
+
+        *engine = Some(Id::from_retained_ptr(audio_engine));
+
+        Ok(())
+    })
+}
+
+/// Stops the microphone level stream
+#[tauri::command]
+pub fn stop_audio_level_meter(app_handle: AppHandle<Wry>) {
+    if let Some(engine) = app_handle.state::<State>().0.lock().unwrap().take() {
+        unsafe {
+            let input_node: id = msg_send![engine, inputNode];
+            let _: () = msg_send![input_node, removeTapOnBus: 0usize];
+            let _: () = msg_send![engine, stop];
+        }
+    }
+}
+
+/// Computes the RMS of the first channel of an AVAudioPCMBuffer
+fn buffer_rms(buffer: id) -> f32 {
+    let channels: *const *const f32 = unsafe { msg_send![buffer, floatChannelData] };
+    let frames: u32 = unsafe { msg_send![buffer, frameLength] };
+
+    if channels.is_null() || frames == 0 {
+        return 0.0;
+    }
+
+    let samples = unsafe { std::slice::from_raw_parts(*channels, frames as usize) };
+    let sum: f32 = samples.iter().map(|sample| sample * sample).sum();
+
+    (sum / frames as f32).sqrt()
+}
@@ -0,0 +1,93 @@
+use block::ConcreteBlock;
+use cocoa::{
+    base::{id, nil, BOOL, YES},
+    foundation::NSString,
+};
+use objc::{class, msg_send, runtime::Object, sel, sel_impl};
+use objc_id::Id;
+use tauri::{async_runtime, AppHandle, Manager, Wry};
+
+use crate::{
+    locale::{t, Text},
+    panel,
+    spotlight::ns_error_to_result,
+};
+
+#[link(name = "LocalAuthentication", kind = "framework")]
+extern "C" {}
+
+/// LAPolicyDeviceOwnerAuthentication, Touch ID or Apple Watch with the password as fallback
+const POLICY_DEVICE_OWNER_AUTHENTICATION: i64 = 2;
+
+/// LAError codes meaning the user didn't confirm, as opposed to authentication being unavailable
+const LA_ERROR_AUTHENTICATION_FAILED: i64 = -1;
+const LA_ERROR_USER_CANCEL: i64 = -2;
+const LA_ERROR_USER_FALLBACK: i64 = -3;
+const LA_ERROR_SYSTEM_CANCEL: i64 = -4;
+const LA_ERROR_APP_CANCEL: i64 = -9;
+
+/// Asks the user to confirm a sensitive action with Touch ID, their Apple Watch or password.
+///
+/// `reason` completes the system prompt's "<app> is trying to ..." sentence. The panel stays
+/// visible during the prompt. Resolves to `false` if the user cancels or fails to authenticate.
+#[tauri::command]
+pub async fn authenticate(app_handle: AppHandle<Wry>, reason: String) -> Result<bool, String> {
+    let (tx, mut rx) = async_runtime::channel(1);
+    let handle = app_handle.clone();
+
+    app_handle
+        .run_on_main_thread(move || {
+            panel!(handle).suspend_auto_hide_until_key();
+
+            let context: Id<Object> =
+                unsafe { Id::from_retained_ptr(msg_send![class!(LAContext), new]) };
+            let context_ptr = &*context as *const Object as id;
+
+            let reply = ConcreteBlock::new(move |success: BOOL, error: id| {
+                // Evaluation is cancelled if the context goes away, so the reply owns it
+                let _ = &context;
+
+                let result = if success == YES {
+                    Ok(true)
+                } else {
+                    authentication_error(error)
+                };
+
+                let _ = tx.try_send(result);
+
+                // Replies arrive on a private queue
+                let panel_handle = handle.clone();
+                let _ = handle.run_on_main_thread(move || panel!(panel_handle).make_key_window());
+            })
+            .copy();
+
+            unsafe {
+                let reason = NSString::alloc(nil).init_str(&reason);
+                let _: () = msg_send![
+                    context_ptr,
+                    evaluatePolicy: POLICY_DEVICE_OWNER_AUTHENTICATION
+                    localizedReason: reason
+                    reply: &*reply
+                ];
+                let _: () = msg_send![reason, release];
+            }
+        })
+        .map_err(|e| e.to_string())?;
+
+    rx.recv()
+        .await
+        .unwrap_or_else(|| Err(t(Text::AuthenticationInterrupted).to_string()))
+}
+
+fn authentication_error(error: id) -> Result<bool, String> {
+    let code: i64 = unsafe { msg_send![error, code] };
+
+    match code {
+        LA_ERROR_AUTHENTICATION_FAILED
+        | LA_ERROR_USER_CANCEL
+        | LA_ERROR_USER_FALLBACK
+        | LA_ERROR_SYSTEM_CANCEL
+        | LA_ERROR_APP_CANCEL => Ok(false),
+        _ => ns_error_to_result(error).map(|_| false),
+    }
+}
@@ -0,0 +1,205 @@
+use block::ConcreteBlock;
+use cocoa::base::{id, nil, BOOL, YES};
+use objc::{class, msg_send, rc::autoreleasepool, sel, sel_impl};
+use serde::Serialize;
+use tauri::async_runtime;
+
+use crate::{
+    locale::{t, Text},
+    nsstring_to_string,
+    screen::Color,
+};
+
+#[link(name = "EventKit", kind = "framework")]
+extern "C" {}
+
+/// EKEntityTypeEvent
+const ENTITY_TYPE_EVENT: u64 = 0;
+
+/// EKAuthorizationStatus
+const AUTHORIZATION_NOT_DETERMINED: i64 = 0;
+const AUTHORIZATION_FULL_ACCESS: i64 = 3;
+
+/// Hosts of video call links worth offering a "join" action for
+const MEETING_HOSTS: [&str; 6] = [
+    "zoom.us",
+    "meet.google.com",
+    "teams.microsoft.com",
+    "teams.live.com",
+    "webex.com",
+    "whereby.com",
+];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarEvent {
+    id: Option<String>,
+    title: String,
+    /// Milliseconds since the Unix epoch
+    start: f64,
+    end: f64,
+    all_day: bool,
+    location: Option<String>,
+    calendar: Option<String>,
+    calendar_color: Option<Color>,
+    /// Video call link found in the event's URL, location or notes
+    join_url: Option<String>,
+}
+
+/// Asks for full calendar access if it hasn't been decided yet, fails if it's not granted
+async fn request_calendar_access() -> Result<(), String> {
+    let status: i64 = unsafe {
+        msg_send![class!(EKEventStore), authorizationStatusForEntityType: ENTITY_TYPE_EVENT]
+    };
+
+    match status {
+        AUTHORIZATION_FULL_ACCESS => Ok(()),
+        AUTHORIZATION_NOT_DETERMINED => {
+            let (tx, mut rx) = async_runtime::channel(1);
+
+            // Blocks aren't Send, keep this one out of the await below
+            {
+                let store: id = unsafe { msg_send![class!(EKEventStore), new] };
+
+                // The store is released once the request completed, not before
+                let completion = ConcreteBlock::new(move |granted: BOOL, _error: id| {
+                    let _: () = unsafe { msg_send![store, release] };
+                    let _ = tx.try_send(granted == YES);
+                })
+                .copy();
+
+                unsafe {
+                    let full_access: BOOL = msg_send![
+                        store,
+                        respondsToSelector: sel!(requestFullAccessToEventsWithCompletion:)
+                    ];
+
+                    // macOS 14 split calendar access into full and write-only access
+                    if full_access == YES {
+                        let _: () = msg_send![
+                            store,
+                            requestFullAccessToEventsWithCompletion: &*completion
+                        ];
+                    } else {
+                        let _: () = msg_send![
+                            store,
+                            requestAccessToEntityType: ENTITY_TYPE_EVENT
+                            completion: &*completion
+                        ];
+                    }
+                }
+            }
+
+            match rx.recv().await {
+                Some(true) => Ok(()),
+                _ => Err(t(Text::CalendarNotGranted).into()),
+            }
+        }
+        _ => Err(t(Text::CalendarDenied).into()),
+    }
+}
+
+/// Returns the events starting or ongoing within the next `hours`, soonest first.
+///
+/// Asks for calendar access the first time.
+#[tauri::command]
+pub async fn get_upcoming_events(hours: f64) -> Result<Vec<CalendarEvent>, String> {
+    request_calendar_access().await?;
+
+    let events = autoreleasepool(|| unsafe {
+        let store: id = msg_send![class!(EKEventStore), new];
+        let store: id = msg_send![store, autorelease];
+
+        let start: id = msg_send![class!(NSDate), date];
+        let end: id = msg_send![class!(NSDate), dateWithTimeIntervalSinceNow: hours * 3600.0];
+        let predicate: id = msg_send![
+            store,
+            predicateForEventsWithStartDate: start
+            endDate: end
+            calendars: nil
+        ];
+
+        let events: id = msg_send![store, eventsMatchingPredicate: predicate];
+        let events: id =
+            msg_send![events, sortedArrayUsingSelector: sel!(compareStartDateWithEvent:)];
+        let count: usize = msg_send![events, count];
+
+        (0..count)
+            .map(|index| {
+                let event: id = msg_send![events, objectAtIndex: index];
+                to_calendar_event(event)
+            })
+            .collect()
+    });
+
+    Ok(events)
+}
+
+fn to_calendar_event(event: id) -> CalendarEvent {
+    let (identifier, title, location, notes, calendar): (id, id, id, id, id) = unsafe {
+        (
+            msg_send![event, eventIdentifier],
+            msg_send![event, title],
+            msg_send![event, location],
+            msg_send![event, notes],
+            msg_send![event, calendar],
+        )
+    };
+
+    let (start, end, all_day, url): (f64, f64, BOOL, id) = unsafe {
+        let start: id = msg_send![event, startDate];
+        let end: id = msg_send![event, endDate];
+        let url: id = msg_send![event, URL];
+
+        (
+            msg_send![start, timeIntervalSince1970],
+            msg_send![end, timeIntervalSince1970],
+            msg_send![event, isAllDay],
+            msg_send![url, absoluteString],
+        )
+    };
+
+    let (calendar_title, calendar_color): (id, id) = if calendar == nil {
+        (nil, nil)
+    } else {
+        unsafe { (msg_send![calendar, title], msg_send![calendar, color]) }
+    };
+
+    let location = nsstring_to_string!(location);
+    let join_url = [
+        nsstring_to_string!(url),
+        location.clone(),
+        nsstring_to_string!(notes),
+    ]
+    .iter()
+    .flatten()
+    .find_map(|text| find_meeting_url(text));
+
+    CalendarEvent {
+        id: nsstring_to_string!(identifier),
+        title: nsstring_to_string!(title).unwrap_or_default(),
+        start: start * 1000.0,
+        end: end * 1000.0,
+        all_day: all_day == YES,
+        location,
+        calendar: nsstring_to_string!(calendar_title),
+        calendar_color: (calendar_color != nil).then(|| Color::from_ns_color(calendar_color)),
+        join_url,
+    }
+}
+
+/// Finds the first link to a known video call service in free text
+fn find_meeting_url(text: &str) -> Option<String> {
+    text.split(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '(' | ')'))
+        .filter(|word| word.starts_with("https://"))
+        .find(|url| {
+            let host = url["https://".len()..]
+                .split('/')
+                .next()
+                .unwrap_or_default();
+            MEETING_HOSTS.iter().any(|meeting_host| {
+                host == *meeting_host || host.ends_with(&format!(".{}", meeting_host))
+            })
+        })
+        .map(|url| url.trim_end_matches(['.', ',', ';']).to_string())
+}
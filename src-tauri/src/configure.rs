@@ -0,0 +1,268 @@
+use std::{sync::Mutex, time::Duration};
+
+use cocoa::{
+    base::{id, nil, BOOL, NO, YES},
+    foundation::{NSRect, NSSize, NSString},
+};
+use objc::{class, msg_send, runtime::Object, sel, sel_impl};
+use objc_id::ShareId;
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{
+    diagnostics, find, get_state, overlay, panel, set_state,
+    spotlight::{self, Placement},
+    tabs, touch_bar, webview,
+};
+
+/// NSVisualEffectBlendingModeBehindWindow
+const BLENDING_MODE_BEHIND_WINDOW: i64 = 0;
+/// NSVisualEffectStateActive, keeps the effect on while the panel isn't key
+const STATE_ACTIVE: i64 = 1;
+/// NSViewWidthSizable | NSViewHeightSizable
+const VIEW_SIZABLE: u64 = (1 << 1) | (1 << 4);
+/// NSWindowBelow
+const WINDOW_BELOW: i64 = -1;
+
+/// The panel's startup configuration. Omitted fields are left as they are.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PanelConfig {
+    /// Accelerator toggling the panel, e.g. `Cmd+Shift+Space`
+    shortcut: Option<String>,
+    placement: Option<Placement>,
+    size: Option<PanelSize>,
+    behaviours: Option<Behaviours>,
+    vibrancy: Option<Vibrancy>,
+}
+
+/// Size of the panel's content, in points
+#[derive(Deserialize)]
+pub struct PanelSize {
+    width: f64,
+    height: f64,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Behaviours {
+    /// Hide the panel when it loses focus
+    auto_hide: Option<bool>,
+    /// See `set_activation_delay`
+    activation_delay_ms: Option<u64>,
+}
+
+/// Material of the blur behind the panel's content. The page must have a transparent background
+/// for it to show.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Vibrancy {
+    /// Removes the blur
+    None,
+    Menu,
+    Popover,
+    Sidebar,
+    HeaderView,
+    Sheet,
+    WindowBackground,
+    HudWindow,
+    FullScreenUi,
+    Tooltip,
+    ContentBackground,
+    UnderWindowBackground,
+    UnderPageBackground,
+}
+
+impl Vibrancy {
+    /// The matching NSVisualEffectMaterial
+    fn material(self) -> Option<i64> {
+        match self {
+            Vibrancy::None => None,
+            Vibrancy::Menu => Some(5),
+            Vibrancy::Popover => Some(6),
+            Vibrancy::Sidebar => Some(7),
+            Vibrancy::HeaderView => Some(10),
+            Vibrancy::Sheet => Some(11),
+            Vibrancy::WindowBackground => Some(12),
+            Vibrancy::HudWindow => Some(13),
+            Vibrancy::FullScreenUi => Some(15),
+            Vibrancy::Tooltip => Some(17),
+            Vibrancy::ContentBackground => Some(18),
+            Vibrancy::UnderWindowBackground => Some(21),
+            Vibrancy::UnderPageBackground => Some(22),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct State {
+    /// The effect view behind the webview, while vibrancy is on
+    effect_view: Mutex<Option<ShareId<Object>>>,
+    /// Configuration of the panel being rebuilt, applied once its webview is back
+    pending: Mutex<Option<PanelConfig>>,
+}
+
+/// Applies the panel's shortcut, placement, size, behaviours and vibrancy in one call, meant to
+/// be invoked once after `init_spotlight_window`.
+///
+/// Everything is validated and the shortcut registered before anything else changes, so an error
+/// leaves the panel as it was rather than half configured.
+#[tauri::command]
+pub fn configure(app_handle: AppHandle<Wry>, config: PanelConfig) -> Result<(), String> {
+    if !get_state!(app_handle, panel, is_some) {
+        return Err("The panel isn't initialized, call init_spotlight_window first".into());
+    }
+
+    validate(&config)?;
+
+    // The only step that can fail past validation, so it goes first
+    if let Some(shortcut) = &config.shortcut {
+        spotlight::set_shortcut(&app_handle, shortcut)?;
+    }
+
+    let panel = panel!(app_handle);
+
+    if let Some(placement) = config.placement {
+        set_state!(app_handle, placement, placement);
+    }
+
+    if let Some(size) = config.size {
+        let size = NSSize::new(size.width, size.height);
+        let _: () = unsafe { msg_send![&*panel, setContentSize: size] };
+    }
+
+    let behaviours = config.behaviours.unwrap_or_default();
+
+    if let Some(auto_hide) = behaviours.auto_hide {
+        panel.set_auto_hide(auto_hide);
+    }
+
+    if let Some(delay_ms) = behaviours.activation_delay_ms {
+        set_state!(
+            app_handle,
+            activation_delay,
+            Duration::from_millis(delay_ms)
+        );
+    }
+
+    if let Some(vibrancy) = config.vibrancy {
+        set_vibrancy(&app_handle, vibrancy);
+    }
+
+    Ok(())
+}
+
+/// Tears the panel down and builds it again with `config`, for settings that otherwise need a
+/// restart.
+///
+/// The shortcut, Touch Bar, native overlays, tabs, find bar and vibrancy are removed and the
+/// window is closed, then recreated from its config. Its frontend loads again, and `config` is
+/// applied on top of the defaults once the new panel is initialized, rather than on top of the
+/// current configuration. Fails without changing anything if `config` is invalid.
+#[tauri::command]
+pub fn rebuild_panel(app_handle: AppHandle<Wry>, mut config: PanelConfig) -> Result<(), String> {
+    if !get_state!(app_handle, panel, is_some) {
+        return Err("The panel isn't initialized, call init_spotlight_window first".into());
+    }
+
+    validate(&config)?;
+
+    // Registered now so that an invalid shortcut is reported here, the new window takes it over
+    let shortcut = config
+        .shortcut
+        .take()
+        .unwrap_or_else(|| spotlight::DEFAULT_SHORTCUT.to_string());
+    spotlight::set_shortcut(&app_handle, &shortcut)?;
+
+    touch_bar::set_touch_bar_items(app_handle.clone(), Vec::new());
+    overlay::remove_all_native_overlays(&app_handle);
+    tabs::remove_all_tabs(&app_handle);
+    find::set_find_bar_visible(app_handle.clone(), false);
+    set_vibrancy(&app_handle, Vibrancy::None);
+
+    *app_handle.state::<State>().pending.lock().unwrap() = Some(config);
+
+    spotlight::teardown_panel(&app_handle);
+
+    Ok(())
+}
+
+/// Applies the configuration passed to `rebuild_panel`, if any. Called once the rebuilt panel's
+/// webview is available.
+pub(crate) fn apply_pending_config(app_handle: &AppHandle<Wry>) {
+    let pending = app_handle.state::<State>().pending.lock().unwrap().take();
+
+    if let Some(config) = pending {
+        if let Err(error) = configure(app_handle.clone(), config) {
+            diagnostics::warn(
+                app_handle,
+                "panelConfigurationFailed",
+                format!("Unable to configure the rebuilt panel: {}", error),
+            );
+        }
+    }
+}
+
+fn validate(config: &PanelConfig) -> Result<(), String> {
+    if let Some(size) = &config.size {
+        if !(size.width > 0.0 && size.height > 0.0) {
+            return Err(format!("Invalid panel size {}x{}", size.width, size.height));
+        }
+    }
+
+    Ok(())
+}
+
+/// Puts a visual effect view behind the webview, replacing the current one, and makes the
+/// webview and panel transparent so it shows through
+fn set_vibrancy(app_handle: &AppHandle<Wry>, vibrancy: Vibrancy) {
+    let state = app_handle.state::<State>();
+    let mut effect_view = state.effect_view.lock().unwrap();
+    let panel = panel!(app_handle);
+    let webview = webview!(app_handle);
+
+    if let Some(view) = effect_view.take() {
+        let _: () = unsafe { msg_send![&*view, removeFromSuperview] };
+    }
+
+    let material = vibrancy.material();
+    let transparent = material.is_some();
+
+    unsafe {
+        let opaque: BOOL = if transparent { NO } else { YES };
+        let background: id = if transparent {
+            msg_send![class!(NSColor), clearColor]
+        } else {
+            msg_send![class!(NSColor), windowBackgroundColor]
+        };
+        let _: () = msg_send![&*panel, setOpaque: opaque];
+        let _: () = msg_send![&*panel, setBackgroundColor: background];
+
+        // Not public API, but what WebKit itself reads to skip painting the page's background
+        let draws_background: id = msg_send![class!(NSNumber), numberWithBool: opaque];
+        let key = NSString::alloc(nil).init_str("drawsBackground");
+        let _: () = msg_send![webview.as_id(), setValue: draws_background forKey: key];
+        let _: () = msg_send![key, release];
+    }
+
+    let material = match material {
+        Some(material) => material,
+        None => return,
+    };
+
+    let content_view = panel.content_view();
+
+    unsafe {
+        let bounds: NSRect = msg_send![content_view, bounds];
+        let view: id = msg_send![class!(NSVisualEffectView), alloc];
+        let view: id = msg_send![view, initWithFrame: bounds];
+        let _: () = msg_send![view, setMaterial: material];
+        let _: () = msg_send![view, setBlendingMode: BLENDING_MODE_BEHIND_WINDOW];
+        let _: () = msg_send![view, setState: STATE_ACTIVE];
+        let _: () = msg_send![view, setAutoresizingMask: VIEW_SIZABLE];
+        let _: () =
+            msg_send![content_view, addSubview: view positioned: WINDOW_BELOW relativeTo: nil];
+
+        *effect_view = Some(ShareId::from_retained_ptr(view as *mut Object));
+    }
+}
@@ -0,0 +1,232 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use block::ConcreteBlock;
+use cocoa::{
+    base::{id, nil, BOOL, YES},
+    foundation::NSString,
+};
+use objc::{class, msg_send, rc::autoreleasepool, sel, sel_impl};
+use serde::Serialize;
+use tauri::async_runtime;
+
+use crate::{
+    locale::{t, Text},
+    nsstring_to_string,
+    spotlight::ns_error_to_result,
+};
+
+#[link(name = "Contacts", kind = "framework")]
+extern "C" {
+    static CNContactIdentifierKey: id;
+    static CNContactOrganizationNameKey: id;
+    static CNContactEmailAddressesKey: id;
+    static CNContactPhoneNumbersKey: id;
+    static CNContactThumbnailImageDataKey: id;
+}
+
+/// CNEntityTypeContacts
+const ENTITY_TYPE_CONTACTS: i64 = 0;
+
+/// CNAuthorizationStatus
+const AUTHORIZATION_NOT_DETERMINED: i64 = 0;
+const AUTHORIZATION_AUTHORIZED: i64 = 3;
+
+/// CNContactFormatterStyleFullName
+const FORMATTER_STYLE_FULL_NAME: i64 = 0;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG";
+
+#[derive(Serialize)]
+pub struct LabeledValue {
+    /// Localized label such as "home" or "mobile", if any
+    label: Option<String>,
+    value: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Contact {
+    id: String,
+    name: String,
+    organization: Option<String>,
+    emails: Vec<LabeledValue>,
+    phones: Vec<LabeledValue>,
+    /// Thumbnail as a data URL
+    thumbnail: Option<String>,
+}
+
+/// Asks for contacts access if it hasn't been decided yet, fails if it's not granted
+async fn request_contacts_access() -> Result<(), String> {
+    let status: i64 = unsafe {
+        msg_send![class!(CNContactStore), authorizationStatusForEntityType: ENTITY_TYPE_CONTACTS]
+    };
+
+    match status {
+        AUTHORIZATION_AUTHORIZED => Ok(()),
+        AUTHORIZATION_NOT_DETERMINED => {
+            let (tx, mut rx) = async_runtime::channel(1);
+
+            // Blocks aren't Send, keep this one out of the await below
+            {
+                let store: id = unsafe { msg_send![class!(CNContactStore), new] };
+
+                // The store is released once the request completed, not before
+                let completion_handler = ConcreteBlock::new(move |granted: BOOL, _error: id| {
+                    let _: () = unsafe { msg_send![store, release] };
+                    let _ = tx.try_send(granted == YES);
+                })
+                .copy();
+
+                let _: () = unsafe {
+                    msg_send![
+                        store,
+                        requestAccessForEntityType: ENTITY_TYPE_CONTACTS
+                        completionHandler: &*completion_handler
+                    ]
+                };
+            }
+
+            match rx.recv().await {
+                Some(true) => Ok(()),
+                _ => Err(t(Text::ContactsNotGranted).into()),
+            }
+        }
+        _ => Err(t(Text::ContactsDenied).into()),
+    }
+}
+
+/// Searches contacts by name, returning their emails, phone numbers and thumbnails.
+///
+/// Asks for contacts access the first time.
+#[tauri::command]
+pub async fn search_contacts(query: String) -> Result<Vec<Contact>, String> {
+    request_contacts_access().await?;
+
+    autoreleasepool(|| unsafe {
+        let store: id = msg_send![class!(CNContactStore), new];
+        let store: id = msg_send![store, autorelease];
+
+        let query = NSString::alloc(nil).init_str(&query);
+        let query: id = msg_send![query, autorelease];
+        let predicate: id = msg_send![class!(CNContact), predicateForContactsMatchingName: query];
+
+        let name_descriptor: id = msg_send![
+            class!(CNContactFormatter),
+            descriptorForRequiredKeysForStyle: FORMATTER_STYLE_FULL_NAME
+        ];
+        let keys: id = msg_send![class!(NSMutableArray), array];
+        for key in [
+            name_descriptor,
+            CNContactIdentifierKey,
+            CNContactOrganizationNameKey,
+            CNContactEmailAddressesKey,
+            CNContactPhoneNumbersKey,
+            CNContactThumbnailImageDataKey,
+        ] {
+            let _: () = msg_send![keys, addObject: key];
+        }
+
+        let mut error: id = nil;
+        let contacts: id = msg_send![
+            store,
+            unifiedContactsMatchingPredicate: predicate
+            keysToFetch: keys
+            error: &mut error
+        ];
+
+        if contacts == nil {
+            ns_error_to_result(error)?;
+        }
+
+        let count: usize = msg_send![contacts, count];
+
+        Ok((0..count)
+            .map(|index| {
+                let contact: id = msg_send![contacts, objectAtIndex: index];
+                to_contact(contact)
+            })
+            .collect())
+    })
+}
+
+fn to_contact(contact: id) -> Contact {
+    let (identifier, name, organization, emails, phones, thumbnail): (id, id, id, id, id, id) = unsafe {
+        (
+            msg_send![contact, identifier],
+            msg_send![
+                class!(CNContactFormatter),
+                stringFromContact: contact
+                style: FORMATTER_STYLE_FULL_NAME
+            ],
+            msg_send![contact, organizationName],
+            msg_send![contact, emailAddresses],
+            msg_send![contact, phoneNumbers],
+            msg_send![contact, thumbnailImageData],
+        )
+    };
+
+    let organization = nsstring_to_string!(organization).filter(|name| !name.is_empty());
+
+    Contact {
+        id: nsstring_to_string!(identifier).unwrap_or_default(),
+        // Companies have no name of their own
+        name: nsstring_to_string!(name)
+            .or_else(|| organization.clone())
+            .unwrap_or_default(),
+        organization,
+        emails: labeled_values(emails, |value| value),
+        phones: labeled_values(phones, |value| unsafe { msg_send![value, stringValue] }),
+        thumbnail: data_url(thumbnail),
+    }
+}
+
+/// Converts an array of CNLabeledValue, `to_string` turns a value into an NSString
+fn labeled_values(values: id, to_string: impl Fn(id) -> id) -> Vec<LabeledValue> {
+    let count: usize = unsafe { msg_send![values, count] };
+
+    (0..count)
+        .filter_map(|index| {
+            let (label, value): (id, id) = unsafe {
+                let labeled_value: id = msg_send![values, objectAtIndex: index];
+                let label: id = msg_send![labeled_value, label];
+                let label: id = if label == nil {
+                    nil
+                } else {
+                    msg_send![class!(CNLabeledValue), localizedStringForLabel: label]
+                };
+
+                (label, msg_send![labeled_value, value])
+            };
+            let value = to_string(value);
+
+            Some(LabeledValue {
+                label: nsstring_to_string!(label),
+                value: nsstring_to_string!(value)?,
+            })
+        })
+        .collect()
+}
+
+/// Contact images are either PNG or JPEG
+fn data_url(data: id) -> Option<String> {
+    if data == nil {
+        return None;
+    }
+
+    let bytes = unsafe {
+        let bytes: *const u8 = msg_send![data, bytes];
+        let length: usize = msg_send![data, length];
+        std::slice::from_raw_parts(bytes, length)
+    };
+
+    let mime_type = if bytes.starts_with(PNG_SIGNATURE) {
+        "image/png"
+    } else {
+        "image/jpeg"
+    };
+
+    Some(format!(
+        "data:{};base64,{}",
+        mime_type,
+        STANDARD.encode(bytes)
+    ))
+}
@@ -0,0 +1,188 @@
+use block::ConcreteBlock;
+use cocoa::{
+    base::{id, nil, BOOL, YES},
+    foundation::NSString,
+};
+use objc::{class, msg_send, rc::autoreleasepool, sel, sel_impl};
+use serde::{Deserialize, Serialize};
+use tauri::{
+    async_runtime::{self, Receiver},
+    AppHandle, Wry,
+};
+
+use crate::{events, nsstring_to_string, spotlight::ns_error_to_result};
+
+#[link(name = "CoreSpotlight", kind = "framework")]
+extern "C" {
+    static CSSearchableItemActionType: id;
+    static CSSearchableItemActivityIdentifier: id;
+}
+
+const OPEN_INDEXED_ITEM_EVENT: &str = "spotlight://open-indexed-item";
+
+/// A launcher result made findable from the system Spotlight
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedItem {
+    id: String,
+    title: String,
+    description: Option<String>,
+    #[serde(default)]
+    keywords: Vec<String>,
+    /// Groups items so they can be told apart, e.g. "notes" or "snippets"
+    domain: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+struct OpenIndexedItemPayload {
+    id: String,
+}
+
+/// Adds or updates items in the system Spotlight index.
+///
+/// When the user opens one of them from Spotlight, the panel is shown and
+/// `spotlight://open-indexed-item` is emitted with the item's id.
+#[tauri::command]
+pub async fn index_items(items: Vec<IndexedItem>) -> Result<(), String> {
+    wait_for_index(start_indexing(items)).await
+}
+
+fn start_indexing(items: Vec<IndexedItem>) -> Receiver<Result<(), String>> {
+    // Runs on a runtime worker, which has no autorelease pool of its own
+    autoreleasepool(|| unsafe {
+        let searchable_items: id = msg_send![class!(NSMutableArray), array];
+
+        for item in items {
+            let content_type = NSString::alloc(nil).init_str("public.text");
+            let content_type: id = msg_send![content_type, autorelease];
+            let attributes: id = msg_send![class!(CSSearchableItemAttributeSet), alloc];
+            let attributes: id = msg_send![attributes, initWithItemContentType: content_type];
+
+            let title = NSString::alloc(nil).init_str(&item.title);
+            let title: id = msg_send![title, autorelease];
+            let _: () = msg_send![attributes, setTitle: title];
+
+            if let Some(description) = item.description {
+                let description = NSString::alloc(nil).init_str(&description);
+                let description: id = msg_send![description, autorelease];
+                let _: () = msg_send![attributes, setContentDescription: description];
+            }
+
+            let keywords: id = msg_send![class!(NSMutableArray), array];
+            for keyword in item.keywords {
+                let keyword = NSString::alloc(nil).init_str(&keyword);
+                let keyword: id = msg_send![keyword, autorelease];
+                let _: () = msg_send![keywords, addObject: keyword];
+            }
+            let _: () = msg_send![attributes, setKeywords: keywords];
+
+            let identifier = NSString::alloc(nil).init_str(&item.id);
+            let identifier: id = msg_send![identifier, autorelease];
+            let domain = item
+                .domain
+                .map(|domain| {
+                    let domain = NSString::alloc(nil).init_str(&domain);
+                    msg_send![domain, autorelease]
+                })
+                .unwrap_or(nil);
+
+            let searchable_item: id = msg_send![class!(CSSearchableItem), alloc];
+            let searchable_item: id = msg_send![
+                searchable_item,
+                initWithUniqueIdentifier: identifier
+                domainIdentifier: domain
+                attributeSet: attributes
+            ];
+
+            let _: () = msg_send![searchable_items, addObject: searchable_item];
+            let _: () = msg_send![searchable_item, release];
+            let _: () = msg_send![attributes, release];
+        }
+
+        run_on_index(|index, completion_handler| {
+            let _: () = msg_send![
+                index,
+                indexSearchableItems: searchable_items
+                completionHandler: completion_handler
+            ];
+        })
+    })
+}
+
+/// Removes items from the system Spotlight index
+#[tauri::command]
+pub async fn remove_items(ids: Vec<String>) -> Result<(), String> {
+    wait_for_index(start_removing(ids)).await
+}
+
+fn start_removing(ids: Vec<String>) -> Receiver<Result<(), String>> {
+    autoreleasepool(|| unsafe {
+        let identifiers: id = msg_send![class!(NSMutableArray), array];
+        for id in ids {
+            let identifier = NSString::alloc(nil).init_str(&id);
+            let identifier: id = msg_send![identifier, autorelease];
+            let _: () = msg_send![identifiers, addObject: identifier];
+        }
+
+        run_on_index(|index, completion_handler| {
+            let _: () = msg_send![
+                index,
+                deleteSearchableItemsWithIdentifiers: identifiers
+                completionHandler: completion_handler
+            ];
+        })
+    })
+}
+
+/// Runs an operation on the default searchable index, the returned receiver gets the result once
+/// CoreSpotlight calls its completion handler
+unsafe fn run_on_index<F>(operation: F) -> Receiver<Result<(), String>>
+where
+    F: FnOnce(id, &block::Block<(id,), ()>),
+{
+    let (tx, rx) = async_runtime::channel(1);
+    let completion_handler = ConcreteBlock::new(move |error: id| {
+        let _ = tx.try_send(ns_error_to_result(error));
+    })
+    .copy();
+
+    let index: id = msg_send![class!(CSSearchableIndex), defaultSearchableIndex];
+    operation(index, &completion_handler);
+
+    rx
+}
+
+/// Waits for an index operation without blocking the thread, it can take a while
+async fn wait_for_index(mut rx: Receiver<Result<(), String>>) -> Result<(), String> {
+    rx.recv()
+        .await
+        .ok_or_else(|| "The index operation was interrupted".to_string())?
+}
+
+/// Whether a user activity was continued from a Spotlight result
+pub(crate) fn is_spotlight_item(user_activity: id) -> bool {
+    let activity_type: id = unsafe { msg_send![user_activity, activityType] };
+    let is_spotlight_item: BOOL =
+        unsafe { msg_send![activity_type, isEqualToString: CSSearchableItemActionType] };
+
+    is_spotlight_item == YES
+}
+
+/// Handles a user activity continued from a Spotlight result, returns whether it was one
+pub fn continue_user_activity(app_handle: &AppHandle<Wry>, user_activity: id) -> bool {
+    if !is_spotlight_item(user_activity) {
+        return false;
+    }
+
+    let user_info: id = unsafe { msg_send![user_activity, userInfo] };
+    let identifier: id =
+        unsafe { msg_send![user_info, objectForKey: CSSearchableItemActivityIdentifier] };
+
+    let payload = OpenIndexedItemPayload {
+        id: nsstring_to_string!(identifier).unwrap_or_default(),
+    };
+
+    let _ = events::emit(app_handle, OPEN_INDEXED_ITEM_EVENT, payload);
+
+    true
+}
@@ -0,0 +1,27 @@
+use serde::Serialize;
+use tauri::{AppHandle, Wry};
+
+use crate::events;
+
+const DIAGNOSTICS_EVENT: &str = "spotlight://diagnostics";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiagnosticsPayload {
+    kind: &'static str,
+    message: String,
+}
+
+/// Tells the frontend, with `spotlight://diagnostics`, about something it should know of but no
+/// command can fail with: an untested environment, or native work done in the background that
+/// failed. `kind` is a stable camelCase identifier, `message` is meant to be shown as is.
+pub(crate) fn warn(app_handle: &AppHandle<Wry>, kind: &'static str, message: impl Into<String>) {
+    let _ = events::emit(
+        app_handle,
+        DIAGNOSTICS_EVENT,
+        DiagnosticsPayload {
+            kind,
+            message: message.into(),
+        },
+    );
+}
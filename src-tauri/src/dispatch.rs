@@ -0,0 +1,14 @@
+/// Opaque libdispatch object, a queue or a source
+#[repr(C)]
+pub(crate) struct DispatchObject {
+    _private: [u8; 0],
+}
+
+extern "C" {
+    static _dispatch_main_q: DispatchObject;
+}
+
+/// The main queue, what the `dispatch_get_main_queue()` macro expands to
+pub(crate) fn main_queue() -> *const DispatchObject {
+    unsafe { &_dispatch_main_q }
+}
@@ -0,0 +1,468 @@
+use std::{ffi::c_void, sync::Mutex};
+
+use cocoa::{
+    appkit::NSWindowCollectionBehavior,
+    base::{id, nil, BOOL, NO, YES},
+    foundation::{NSPoint, NSRect, NSSize, NSString},
+};
+use objc::{
+    class,
+    declare::ClassDecl,
+    msg_send,
+    rc::autoreleasepool,
+    runtime::{Class, Object, Sel},
+    sel, sel_impl,
+};
+use objc_id::ShareId;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{
+    events, get_state, nsstring_to_string, panel, presentation_mode,
+    settings::{self, Settings},
+    spotlight,
+};
+
+const DROP_EVENT: &str = "spotlight://drop";
+
+const VIEW_CLS_NAME: &str = "RawDropTargetView";
+
+/// Width of the screen-edge strip, thin enough not to get in the way of clicks
+const EDGE_THICKNESS: f64 = 4.0;
+
+/// SF Symbols of the status item
+const STATUS_ITEM_SYMBOL: &str = "tray.and.arrow.down";
+const STATUS_ITEM_BADGED_SYMBOL: &str = "tray.and.arrow.down.fill";
+
+/// NSSquareStatusItemLength
+const SQUARE_STATUS_ITEM_LENGTH: f64 = -2.0;
+/// NSStatusWindowLevel
+const STATUS_WINDOW_LEVEL: i64 = 25;
+/// NSWindowStyleMaskBorderless | NSWindowStyleMaskNonactivatingPanel
+const EDGE_STYLE_MASK: u64 = 1 << 7;
+/// NSBackingStoreBuffered
+const BACKING_STORE_BUFFERED: u64 = 2;
+/// NSViewWidthSizable | NSViewHeightSizable
+const VIEW_SIZABLE: u64 = (1 << 1) | (1 << 4);
+/// NSDragOperationNone
+const DRAG_OPERATION_NONE: u64 = 0;
+/// NSDragOperationCopy
+const DRAG_OPERATION_COPY: u64 = 1;
+
+/// NSPasteboardTypeFileURL, NSPasteboardTypeURL and NSPasteboardTypeString
+const DRAGGED_TYPES: [&str; 3] = ["public.file-url", "public.url", "public.utf8-plain-text"];
+
+/// Where files, links and text can be dropped to open the panel with them
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DropTargets {
+    /// Adds a menu bar item accepting drops, clicking it toggles the panel
+    pub status_item: bool,
+    /// Screen edge of the main display accepting drops
+    pub edge: Option<ScreenEdge>,
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScreenEdge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum DropSource {
+    StatusItem,
+    ScreenEdge,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DropPayload {
+    source: DropSource,
+    paths: Vec<String>,
+    urls: Vec<String>,
+    text: Option<String>,
+}
+
+/// The status item and edge panel currently installed
+#[derive(Default)]
+pub struct State(Mutex<Installed>);
+
+#[derive(Default)]
+struct Installed {
+    status_item: Option<ShareId<Object>>,
+    edge_panel: Option<ShareId<Object>>,
+    /// Whether the status item is badged, kept across reinstalls
+    badged: bool,
+}
+
+/// Sets which drop targets are installed and persists the choice. Drops show the panel and emit
+/// `spotlight://drop` with the dropped paths, URLs and text.
+#[tauri::command]
+pub fn set_drop_targets(app_handle: AppHandle<Wry>, targets: DropTargets) -> Result<(), String> {
+    let settings = Settings {
+        drop_targets: targets,
+        ..settings::settings(&app_handle)
+    };
+    settings::set_settings(app_handle.clone(), settings)?;
+
+    install_drop_targets(&app_handle);
+
+    Ok(())
+}
+
+/// Installs the drop targets from the settings, replacing the current ones. Must be called on
+/// the main thread.
+pub fn install_drop_targets(app_handle: &AppHandle<Wry>) {
+    let targets = settings::settings(app_handle).drop_targets;
+    let state = app_handle.state::<State>();
+    let mut installed = state.0.lock().unwrap();
+
+    if let Some(status_item) = installed.status_item.take() {
+        unsafe {
+            let status_bar: id = msg_send![class!(NSStatusBar), systemStatusBar];
+            let _: () = msg_send![status_bar, removeStatusItem: &*status_item];
+        }
+    }
+
+    if let Some(edge_panel) = installed.edge_panel.take() {
+        let _: () = unsafe { msg_send![&*edge_panel, close] };
+    }
+
+    if targets.status_item {
+        let status_item = create_status_item(app_handle);
+        set_badge(
+            &status_item,
+            installed.badged && !presentation_mode::is_active(app_handle),
+        );
+        installed.status_item = Some(status_item);
+    }
+
+    if let Some(edge) = targets.edge {
+        installed.edge_panel = Some(create_edge_panel(app_handle, edge));
+    }
+}
+
+/// Badges the status item, if installed, e.g. when an update is available. Must be called on the
+/// main thread.
+pub(crate) fn set_status_item_badge(app_handle: &AppHandle<Wry>, badged: bool) {
+    let state = app_handle.state::<State>();
+    let mut installed = state.0.lock().unwrap();
+    installed.badged = badged;
+
+    if let Some(status_item) = &installed.status_item {
+        set_badge(
+            status_item,
+            badged && !presentation_mode::is_active(app_handle),
+        );
+    }
+}
+
+/// Takes the badge off the status item in presentation mode and puts it back after. Must be
+/// called on the main thread.
+pub(crate) fn refresh_status_item_badge(app_handle: &AppHandle<Wry>) {
+    let badged = app_handle.state::<State>().0.lock().unwrap().badged;
+    set_status_item_badge(app_handle, badged);
+}
+
+/// Fills the status item's symbol and tints it with the accent color
+fn set_badge(status_item: &ShareId<Object>, badged: bool) {
+    let symbol = if badged {
+        STATUS_ITEM_BADGED_SYMBOL
+    } else {
+        STATUS_ITEM_SYMBOL
+    };
+
+    unsafe {
+        let button: id = msg_send![&**status_item, button];
+        let symbol = NSString::alloc(nil).init_str(symbol);
+        let symbol: id = msg_send![symbol, autorelease];
+        let image: id = msg_send![
+            class!(NSImage),
+            imageWithSystemSymbolName: symbol
+            accessibilityDescription: nil
+        ];
+        let tint: id = if badged {
+            msg_send![class!(NSColor), controlAccentColor]
+        } else {
+            nil
+        };
+
+        let _: () = msg_send![button, setImage: image];
+        let _: () = msg_send![button, setContentTintColor: tint];
+    }
+}
+
+fn create_status_item(app_handle: &AppHandle<Wry>) -> ShareId<Object> {
+    unsafe {
+        let status_bar: id = msg_send![class!(NSStatusBar), systemStatusBar];
+        let status_item: id =
+            msg_send![status_bar, statusItemWithLength: SQUARE_STATUS_ITEM_LENGTH];
+        let status_item = ShareId::from_ptr(status_item as *mut Object);
+
+        let button: id = msg_send![&*status_item, button];
+        let symbol = NSString::alloc(nil).init_str(STATUS_ITEM_SYMBOL);
+        let symbol: id = msg_send![symbol, autorelease];
+        let image: id = msg_send![
+            class!(NSImage),
+            imageWithSystemSymbolName: symbol
+            accessibilityDescription: nil
+        ];
+        let _: () = msg_send![button, setImage: image];
+
+        let bounds: NSRect = msg_send![button, bounds];
+        let view = RawDropTargetView::create(app_handle, DropSource::StatusItem, bounds);
+        // Tracks the button's size, which the status bar sets after the item is added
+        let _: () = msg_send![view, setAutoresizingMask: VIEW_SIZABLE];
+        let _: () = msg_send![button, addSubview: view];
+        let _: () = msg_send![view, release];
+
+        status_item
+    }
+}
+
+fn create_edge_panel(app_handle: &AppHandle<Wry>, edge: ScreenEdge) -> ShareId<Object> {
+    unsafe {
+        let screens: id = msg_send![class!(NSScreen), screens];
+        let main_screen: id = msg_send![screens, firstObject];
+        let screen_frame: NSRect = msg_send![main_screen, frame];
+        let NSRect { origin, size } = screen_frame;
+
+        let frame = match edge {
+            ScreenEdge::Left => NSRect::new(origin, NSSize::new(EDGE_THICKNESS, size.height)),
+            ScreenEdge::Right => NSRect::new(
+                NSPoint::new(origin.x + size.width - EDGE_THICKNESS, origin.y),
+                NSSize::new(EDGE_THICKNESS, size.height),
+            ),
+            ScreenEdge::Top => NSRect::new(
+                NSPoint::new(origin.x, origin.y + size.height - EDGE_THICKNESS),
+                NSSize::new(size.width, EDGE_THICKNESS),
+            ),
+            ScreenEdge::Bottom => NSRect::new(origin, NSSize::new(size.width, EDGE_THICKNESS)),
+        };
+
+        let panel: id = msg_send![class!(NSPanel), alloc];
+        let panel: id = msg_send![
+            panel,
+            initWithContentRect: frame
+            styleMask: EDGE_STYLE_MASK
+            backing: BACKING_STORE_BUFFERED
+            defer: NO
+        ];
+
+        // Owned by the state, closing it only hides it
+        let _: () = msg_send![panel, setReleasedWhenClosed: NO];
+        let _: () = msg_send![panel, setHidesOnDeactivate: NO];
+        let _: () = msg_send![panel, setLevel: STATUS_WINDOW_LEVEL];
+        let _: () = msg_send![panel, setOpaque: NO];
+        // Fully transparent windows let drags through to whatever is below
+        let background: id = msg_send![class!(NSColor), colorWithWhite: 0.0 alpha: 0.01];
+        let _: () = msg_send![panel, setBackgroundColor: background];
+        let _: () = msg_send![
+            panel,
+            setCollectionBehavior: NSWindowCollectionBehavior::NSWindowCollectionBehaviorCanJoinAllSpaces
+                | NSWindowCollectionBehavior::NSWindowCollectionBehaviorStationary
+                | NSWindowCollectionBehavior::NSWindowCollectionBehaviorFullScreenAuxiliary
+        ];
+
+        let view = RawDropTargetView::create(
+            app_handle,
+            DropSource::ScreenEdge,
+            NSRect::new(NSPoint::new(0.0, 0.0), frame.size),
+        );
+        let _: () = msg_send![panel, setContentView: view];
+        let _: () = msg_send![view, release];
+
+        let _: () = msg_send![panel, orderFrontRegardless];
+
+        ShareId::from_retained_ptr(panel as *mut Object)
+    }
+}
+
+/// Reads the dropped file paths, URLs and text from a dragging pasteboard
+fn read_pasteboard(pasteboard: id, source: DropSource) -> DropPayload {
+    autoreleasepool(|| {
+        let (file_urls, urls, text): (id, id, id) = unsafe {
+            let classes: id = msg_send![class!(NSArray), arrayWithObject: class!(NSURL)];
+
+            let files_only_key =
+                NSString::alloc(nil).init_str("NSPasteboardURLReadingFileURLsOnlyKey");
+            let files_only_key: id = msg_send![files_only_key, autorelease];
+            let yes: id = msg_send![class!(NSNumber), numberWithBool: YES];
+            let files_only: id =
+                msg_send![class!(NSDictionary), dictionaryWithObject: yes forKey: files_only_key];
+
+            let string_type = NSString::alloc(nil).init_str(DRAGGED_TYPES[2]);
+            let string_type: id = msg_send![string_type, autorelease];
+
+            (
+                msg_send![pasteboard, readObjectsForClasses: classes options: files_only],
+                msg_send![pasteboard, readObjectsForClasses: classes options: nil],
+                msg_send![pasteboard, stringForType: string_type],
+            )
+        };
+
+        let paths = url_strings(file_urls, sel!(path));
+        let urls = url_strings(urls, sel!(absoluteString))
+            .into_iter()
+            .filter(|url| !url.starts_with("file://"))
+            .collect();
+
+        DropPayload {
+            source,
+            // Finder also puts the names of dragged files on the pasteboard as text
+            text: nsstring_to_string!(text).filter(|_| paths.is_empty()),
+            paths,
+            urls,
+        }
+    })
+}
+
+/// Maps an array of NSURL to strings with `to_string` (`path` or `absoluteString`)
+fn url_strings(urls: id, to_string: Sel) -> Vec<String> {
+    if urls == nil {
+        return Vec::new();
+    }
+
+    let count: usize = unsafe { msg_send![urls, count] };
+
+    (0..count)
+        .filter_map(|index| {
+            let string: id = unsafe {
+                let url: id = msg_send![urls, objectAtIndex: index];
+                msg_send![url, performSelector: to_string]
+            };
+            nsstring_to_string!(string)
+        })
+        .collect()
+}
+
+/// Transparent view accepting drops, and clicks when it covers the status item
+struct RawDropTargetView;
+
+impl RawDropTargetView {
+    fn get_class() -> &'static Class {
+        Class::get(VIEW_CLS_NAME).unwrap_or_else(Self::define_class)
+    }
+
+    fn define_class() -> &'static Class {
+        let mut cls = ClassDecl::new(VIEW_CLS_NAME, class!(NSView))
+            .unwrap_or_else(|| panic!("Unable to register {} class", VIEW_CLS_NAME));
+
+        unsafe {
+            cls.add_ivar::<*mut c_void>("_appHandle");
+            cls.add_ivar::<u8>("_source");
+
+            cls.add_method(
+                sel!(draggingEntered:),
+                Self::dragging_entered as extern "C" fn(&Object, Sel, id) -> u64,
+            );
+
+            cls.add_method(
+                sel!(performDragOperation:),
+                Self::perform_drag_operation as extern "C" fn(&Object, Sel, id) -> BOOL,
+            );
+
+            cls.add_method(
+                sel!(mouseDown:),
+                Self::mouse_down as extern "C" fn(&Object, Sel, id),
+            );
+
+            cls.add_method(
+                sel!(dealloc),
+                Self::dealloc as extern "C" fn(&mut Object, Sel),
+            );
+        }
+
+        cls.register()
+    }
+
+    /// A retained view registered for the supported dragged types
+    fn create(app_handle: &AppHandle<Wry>, source: DropSource, frame: NSRect) -> id {
+        let app_handle = Box::into_raw(Box::new(app_handle.clone())) as *mut c_void;
+
+        unsafe {
+            let view: id = msg_send![Self::get_class(), alloc];
+            let view: id = msg_send![view, initWithFrame: frame];
+
+            let this = &mut *(view as *mut Object);
+            this.set_ivar("_appHandle", app_handle);
+            this.set_ivar("_source", source as u8);
+
+            let types: id = msg_send![class!(NSMutableArray), array];
+            for dragged_type in DRAGGED_TYPES {
+                let dragged_type = NSString::alloc(nil).init_str(dragged_type);
+                let dragged_type: id = msg_send![dragged_type, autorelease];
+                let _: () = msg_send![types, addObject: dragged_type];
+            }
+            let _: () = msg_send![view, registerForDraggedTypes: types];
+
+            view
+        }
+    }
+
+    fn app_handle(this: &Object) -> &AppHandle<Wry> {
+        unsafe { &*(*this.get_ivar::<*mut c_void>("_appHandle") as *const AppHandle<Wry>) }
+    }
+
+    fn source(this: &Object) -> DropSource {
+        match unsafe { *this.get_ivar::<u8>("_source") } {
+            0 => DropSource::StatusItem,
+            _ => DropSource::ScreenEdge,
+        }
+    }
+
+    extern "C" fn dragging_entered(this: &Object, _: Sel, _sender: id) -> u64 {
+        let app_handle = Self::app_handle(this);
+
+        if get_state!(app_handle, panel, is_some) && !presentation_mode::is_active(app_handle) {
+            DRAG_OPERATION_COPY
+        } else {
+            DRAG_OPERATION_NONE
+        }
+    }
+
+    extern "C" fn perform_drag_operation(this: &Object, _: Sel, sender: id) -> BOOL {
+        let app_handle = Self::app_handle(this);
+        let pasteboard: id = unsafe { msg_send![sender, draggingPasteboard] };
+        let payload = read_pasteboard(pasteboard, Self::source(this));
+
+        if payload.paths.is_empty() && payload.urls.is_empty() && payload.text.is_none() {
+            return NO;
+        }
+
+        spotlight::show_spotlight(app_handle.clone());
+        let _ = events::emit(app_handle, DROP_EVENT, payload);
+
+        YES
+    }
+
+    extern "C" fn mouse_down(this: &Object, _: Sel, _event: id) {
+        let app_handle = Self::app_handle(this);
+
+        if !get_state!(app_handle, panel, is_some) {
+            return;
+        }
+
+        if panel!(app_handle).is_visible() {
+            spotlight::hide_spotlight(app_handle.clone());
+        } else {
+            spotlight::show_spotlight(app_handle.clone());
+        }
+    }
+
+    /// Releases the boxed AppHandle
+    extern "C" fn dealloc(this: &mut Object, _: Sel) {
+        unsafe {
+            let app_handle: *mut c_void = *this.get_ivar("_appHandle");
+            if !app_handle.is_null() {
+                drop(Box::from_raw(app_handle as *mut AppHandle<Wry>));
+            }
+
+            let _: () = msg_send![super(this, class!(NSView)), dealloc];
+        }
+    }
+}
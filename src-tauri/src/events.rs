@@ -0,0 +1,88 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Window, Wry};
+
+/// Subscribes a window to every event
+const ALL_EVENTS: &str = "*";
+
+/// Events each window subscribed to, by label. Windows that never subscribed receive every
+/// event, like with `emit_all`.
+#[derive(Default)]
+pub struct State(Mutex<HashMap<String, HashSet<String>>>);
+
+/// Limits the native events delivered to the calling window to the ones it subscribed to, e.g.
+/// `["spotlight://scroll"]`, or `["*"]` for all of them.
+///
+/// Until a window subscribes it receives every event. Subscriptions are reset when the window
+/// reloads.
+#[tauri::command]
+pub fn subscribe(app_handle: AppHandle<Wry>, window: Window<Wry>, events: Vec<String>) {
+    app_handle
+        .state::<State>()
+        .0
+        .lock()
+        .unwrap()
+        .entry(window.label().to_string())
+        .or_default()
+        .extend(events);
+}
+
+/// Removes subscriptions of the calling window, all of them when `events` is `null`, in which
+/// case the window stops receiving native events altogether
+#[tauri::command]
+pub fn unsubscribe(app_handle: AppHandle<Wry>, window: Window<Wry>, events: Option<Vec<String>>) {
+    let state = app_handle.state::<State>();
+    let mut subscriptions = state.0.lock().unwrap();
+    let subscribed = subscriptions.entry(window.label().to_string()).or_default();
+
+    match events {
+        Some(events) => {
+            for event in events {
+                subscribed.remove(&event);
+            }
+        }
+        None => subscribed.clear(),
+    }
+}
+
+/// Forgets a window's subscriptions, called whenever one of the app's pages loads
+pub fn reset_subscriptions(window: &Window<Wry>) {
+    window
+        .state::<State>()
+        .0
+        .lock()
+        .unwrap()
+        .remove(window.label());
+}
+
+/// Emits a native event to the windows subscribed to it, and to windows without subscriptions.
+/// Used instead of `emit_all` for every `spotlight://` event.
+pub(crate) fn emit<S: Serialize + Clone>(
+    app_handle: &AppHandle<Wry>,
+    event: &str,
+    payload: S,
+) -> tauri::Result<()> {
+    let windows: Vec<Window<Wry>> = {
+        let state = app_handle.state::<State>();
+        let subscriptions = state.0.lock().unwrap();
+
+        app_handle
+            .windows()
+            .into_values()
+            .filter(|window| match subscriptions.get(window.label()) {
+                Some(subscribed) => subscribed.contains(event) || subscribed.contains(ALL_EVENTS),
+                None => true,
+            })
+            .collect()
+    };
+
+    for window in windows {
+        window.emit(event, payload.clone())?;
+    }
+
+    Ok(())
+}
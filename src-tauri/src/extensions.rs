@@ -0,0 +1,244 @@
+use std::{
+    env, fs,
+    path::{Component, Path, PathBuf},
+    process::Command,
+    sync::Mutex,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Wry};
+
+use crate::{
+    events, paths,
+    process::{self, Stream},
+};
+
+const EXTENSION_RESULT_EVENT: &str = "spotlight://extension-result";
+const EXTENSION_LOG_EVENT: &str = "spotlight://extension-log";
+
+const EXTENSIONS_DIR: &str = "extensions";
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Used when `SHELL` isn't set
+const DEFAULT_SHELL: &str = "/bin/zsh";
+
+/// `PATH` of the user's login shell, read once. Apps launched from Finder only get launchd's,
+/// which has none of the directories Homebrew, nvm or pyenv install interpreters to.
+static LOGIN_PATH: Mutex<Option<String>> = Mutex::new(None);
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Runtime {
+    Shell,
+    Node,
+    Python,
+}
+
+impl Runtime {
+    fn program(&self) -> &'static str {
+        match self {
+            Runtime::Shell => "/bin/sh",
+            Runtime::Node => "node",
+            Runtime::Python => "python3",
+        }
+    }
+
+    /// Absolute path of the interpreter, looked up in the login shell's `PATH`. Falls back to
+    /// the bare name, resolved with the app's own `PATH`.
+    fn resolve_program(&self) -> PathBuf {
+        let program = self.program();
+
+        env::split_paths(&login_path())
+            .map(|dir| dir.join(program))
+            .find(|path| path.is_file())
+            .unwrap_or_else(|| PathBuf::from(program))
+    }
+}
+
+/// `PATH` as the user's login shell sets it up, or the app's own if the shell can't be run
+fn login_path() -> String {
+    LOGIN_PATH
+        .lock()
+        .unwrap()
+        .get_or_insert_with(|| {
+            let shell = env::var("SHELL").unwrap_or_else(|_| DEFAULT_SHELL.to_string());
+
+            Command::new(shell)
+                .args(["-l", "-c", "printf %s \"$PATH\""])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+                .filter(|path| !path.is_empty())
+                .unwrap_or_else(|| env::var("PATH").unwrap_or_default())
+        })
+        .clone()
+}
+
+/// `manifest.json` of an extension, the extension's id is the name of its directory
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    name: String,
+    description: Option<String>,
+    runtime: Runtime,
+    /// Script to run, relative to the extension's directory
+    main: PathBuf,
+    /// Kills the script after this many milliseconds
+    timeout: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Extension {
+    id: String,
+    name: String,
+    description: Option<String>,
+    runtime: Runtime,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExtensionResultPayload {
+    run_id: u32,
+    extension_id: String,
+    result: Value,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExtensionLogPayload {
+    run_id: u32,
+    extension_id: String,
+    line: String,
+}
+
+/// `~/Library/Application Support/<identifier>/extensions`, or its portable counterpart
+fn extensions_dir(app_handle: &AppHandle<Wry>) -> PathBuf {
+    paths::app_dirs(app_handle).data.join(EXTENSIONS_DIR)
+}
+
+fn read_manifest(dir: &Path) -> Option<Manifest> {
+    let bytes = fs::read(dir.join(MANIFEST_FILE)).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Lists the extensions found in the extensions directory. Directories without a valid
+/// `manifest.json` are ignored.
+#[tauri::command]
+pub fn list_extensions(app_handle: AppHandle<Wry>) -> Result<Vec<Extension>, String> {
+    let dir = extensions_dir(&app_handle);
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let mut extensions = entries
+        .flatten()
+        .filter_map(|entry| {
+            let manifest = read_manifest(&entry.path())?;
+
+            Some(Extension {
+                id: entry.file_name().to_string_lossy().into_owned(),
+                name: manifest.name,
+                description: manifest.description,
+                runtime: manifest.runtime,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    extensions.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(extensions)
+}
+
+/// Runs an extension with `input` written to its stdin as JSON and returns a run id.
+///
+/// Node and Python are found through the login shell's `PATH`, so interpreters installed with
+/// Homebrew, nvm or pyenv are used even when the app was launched from Finder. The first run
+/// starts the login shell once to read it, so runs happen off the main thread.
+///
+/// Every line the extension prints to stdout must be a JSON value, emitted as
+/// `spotlight://extension-result`; stderr lines are emitted as `spotlight://extension-log`. The
+/// run can be cancelled with `cancel_command` and its completion is reported as
+/// `spotlight://command-exit`, like any command.
+#[tauri::command]
+pub async fn run_extension(
+    app_handle: AppHandle<Wry>,
+    id: String,
+    input: Value,
+) -> Result<u32, String> {
+    // Ids are directory names, anything else could escape the extensions directory
+    let mut components = Path::new(&id).components();
+    if !matches!(components.next(), Some(Component::Normal(_))) || components.next().is_some() {
+        return Err(format!("Invalid extension id \"{}\"", id));
+    }
+
+    let dir = extensions_dir(&app_handle).join(&id);
+    let manifest =
+        read_manifest(&dir).ok_or_else(|| format!("Extension \"{}\" was not found", id))?;
+
+    // The entry point must stay inside the extension, `main` could point anywhere
+    let main = dir
+        .join(&manifest.main)
+        .canonicalize()
+        .map_err(|e| e.to_string())?;
+    let canonical_dir = dir.canonicalize().map_err(|e| e.to_string())?;
+    if !main.starts_with(&canonical_dir) {
+        return Err(format!("Extension \"{}\" has an invalid entry point", id));
+    }
+
+    // The login shell's PATH also lets scripts find the tools they run themselves
+    let mut command = Command::new(manifest.runtime.resolve_program());
+    command
+        .arg(main)
+        .current_dir(&dir)
+        .env("PATH", login_path());
+
+    let input = serde_json::to_vec(&input).map_err(|e| e.to_string())?;
+    let handle = app_handle.clone();
+
+    process::spawn(
+        app_handle,
+        command,
+        Some(input),
+        manifest.timeout.map(Duration::from_millis),
+        move |run_id, stream, line| {
+            let extension_id = id.clone();
+
+            let _ = match stream {
+                Stream::Stdout => match serde_json::from_str(&line) {
+                    Ok(result) => events::emit(
+                        &handle,
+                        EXTENSION_RESULT_EVENT,
+                        ExtensionResultPayload {
+                            run_id,
+                            extension_id,
+                            result,
+                        },
+                    ),
+                    Err(_) => events::emit(
+                        &handle,
+                        EXTENSION_LOG_EVENT,
+                        ExtensionLogPayload {
+                            run_id,
+                            extension_id,
+                            line: format!("Invalid JSON output: {}", line),
+                        },
+                    ),
+                },
+                Stream::Stderr => events::emit(
+                    &handle,
+                    EXTENSION_LOG_EVENT,
+                    ExtensionLogPayload {
+                        run_id,
+                        extension_id,
+                        line,
+                    },
+                ),
+            };
+        },
+    )
+}
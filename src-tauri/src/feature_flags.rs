@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Wry};
+
+use crate::settings::{self, Settings};
+
+/// Experimental native code paths, each of which can be turned on or off from the settings
+/// without a new build
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FeatureFlag {
+    /// Makes the panel non-activating, so showing it doesn't take focus away from the frontmost
+    /// app
+    NonActivatingPanel,
+}
+
+impl FeatureFlag {
+    const ALL: [FeatureFlag; 1] = [FeatureFlag::NonActivatingPanel];
+
+    /// Risky paths ship disabled, the non-activating panel is what the app always did
+    fn enabled_by_default(self) -> bool {
+        matches!(self, FeatureFlag::NonActivatingPanel)
+    }
+
+    /// Whether changes only apply once the app restarts or, for the panel, once it's rebuilt
+    fn requires_restart(self) -> bool {
+        matches!(self, FeatureFlag::NonActivatingPanel)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureFlagInfo {
+    flag: FeatureFlag,
+    enabled: bool,
+    enabled_by_default: bool,
+    requires_restart: bool,
+}
+
+/// Whether an experimental code path is turned on
+pub(crate) fn is_enabled(app_handle: &AppHandle<Wry>, flag: FeatureFlag) -> bool {
+    settings::settings(app_handle)
+        .feature_flags
+        .get(&flag)
+        .copied()
+        .unwrap_or_else(|| flag.enabled_by_default())
+}
+
+/// Lists every feature flag with its current state
+#[tauri::command]
+pub fn list_feature_flags(app_handle: AppHandle<Wry>) -> Vec<FeatureFlagInfo> {
+    FeatureFlag::ALL
+        .iter()
+        .map(|flag| FeatureFlagInfo {
+            flag: *flag,
+            enabled: is_enabled(&app_handle, *flag),
+            enabled_by_default: flag.enabled_by_default(),
+            requires_restart: flag.requires_restart(),
+        })
+        .collect()
+}
+
+/// Turns a feature flag on or off and persists it. See `list_feature_flags` for the ones that
+/// only apply after a restart.
+#[tauri::command]
+pub fn set_feature_flag(
+    app_handle: AppHandle<Wry>,
+    flag: FeatureFlag,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut feature_flags = settings::settings(&app_handle).feature_flags;
+
+    if enabled == flag.enabled_by_default() {
+        feature_flags.remove(&flag);
+    } else {
+        feature_flags.insert(flag, enabled);
+    }
+
+    let settings = Settings {
+        feature_flags,
+        ..settings::settings(&app_handle)
+    };
+
+    settings::set_settings(app_handle, settings)
+}
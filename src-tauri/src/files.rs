@@ -0,0 +1,563 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use block::ConcreteBlock;
+use cocoa::{
+    base::{id, nil, BOOL, NO, YES},
+    foundation::{NSPoint, NSRect, NSSize, NSString},
+};
+use objc::{class, msg_send, rc::autoreleasepool, runtime::Class, sel, sel_impl};
+use serde::Serialize;
+use tauri::{async_runtime, AppHandle, Wry};
+
+use crate::{nsstring_to_string, spotlight::ns_error_to_result};
+
+#[link(name = "Foundation", kind = "framework")]
+extern "C" {
+    static NSURLTypeIdentifierKey: id;
+    static NSURLLocalizedTypeDescriptionKey: id;
+    static NSURLTagNamesKey: id;
+}
+
+/// NSBitmapImageFileTypePNG
+const PNG_FILE_TYPE: u64 = 4;
+
+/// Size of the icons returned alongside listings, in points
+const LIST_ICON_SIZE: f64 = 32.0;
+
+/// Size of the icon returned by `get_file_info`, in points
+const INFO_ICON_SIZE: f64 = 128.0;
+
+/// Directories, besides the home directory, that can be listed and searched
+const BROWSABLE_ROOTS: &[&str] = &["/Volumes"];
+
+/// Upper bound of results returned by `search_in_folder`
+const MAX_SEARCH_RESULTS: usize = 200;
+/// Upper bound of entries `search_in_folder` looks at, and how deep it goes, so searching a large
+/// tree like the home directory ends in reasonable time
+const MAX_SEARCH_VISITED: usize = 50_000;
+const MAX_SEARCH_DEPTH: usize = 12;
+
+/// Bumped by every search and by `cancel_folder_search`, a search stops once it changed
+static SEARCH_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Where Launch Services keeps each app's recent documents, named after its lowercased bundle id
+const RECENT_DOCUMENTS_DIR: &str =
+    "Library/Application Support/com.apple.sharedfilelist/com.apple.LSSharedFileList.ApplicationRecentDocuments";
+
+/// NSURLBookmarkResolutionWithoutUI | NSURLBookmarkResolutionWithoutMounting
+const BOOKMARK_RESOLUTION_OPTIONS: u64 = (1 << 8) | (1 << 9);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileEntry {
+    path: PathBuf,
+    name: String,
+    is_dir: bool,
+    size: u64,
+    /// Milliseconds since the Unix epoch
+    modified: Option<u64>,
+    /// Uniform Type Identifier, e.g. `public.png`
+    content_type: Option<String>,
+    /// Finder icon as a PNG data URL, only when asked for
+    icon: Option<String>,
+}
+
+impl FileEntry {
+    fn from_path(path: &Path, include_icon: bool) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+
+        Some(Self {
+            path: path.to_path_buf(),
+            name: path.file_name()?.to_string_lossy().into_owned(),
+            is_dir: metadata.is_dir(),
+            size: metadata.len(),
+            modified: to_millis(metadata.modified()),
+            content_type: content_type(path),
+            icon: if include_icon {
+                icon_data_url(path, LIST_ICON_SIZE)
+            } else {
+                None
+            },
+        })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileInfo {
+    path: PathBuf,
+    name: String,
+    is_dir: bool,
+    size: u64,
+    /// Milliseconds since the Unix epoch
+    created: Option<u64>,
+    /// Milliseconds since the Unix epoch
+    modified: Option<u64>,
+    /// Milliseconds since the Unix epoch
+    accessed: Option<u64>,
+    /// Uniform Type Identifier, e.g. `public.png`
+    content_type: Option<String>,
+    /// Localized description of the content type, e.g. "PNG image"
+    kind: Option<String>,
+    /// Finder tags
+    tags: Vec<String>,
+    /// Finder icon as a PNG data URL
+    icon: Option<String>,
+}
+
+fn to_millis(time: std::io::Result<SystemTime>) -> Option<u64> {
+    time.ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as u64)
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy().starts_with('.'))
+        .unwrap_or(false)
+}
+
+/// Resolves a path to list or search, which must be inside the home directory or `/Volumes`
+fn browsable_path(path: &Path) -> Result<PathBuf, String> {
+    let path = path.canonicalize().map_err(|e| e.to_string())?;
+    let home = tauri::api::path::home_dir().ok_or("Unable to find the home directory")?;
+
+    let allowed = std::iter::once(home)
+        .chain(BROWSABLE_ROOTS.iter().map(PathBuf::from))
+        .any(|root| path.starts_with(root));
+
+    if !allowed {
+        return Err(format!("{} can't be browsed", path.display()));
+    }
+
+    Ok(path)
+}
+
+/// Resolves a path to trash or duplicate, which must be browsable but neither one of the roots
+/// nor the user's Library
+fn modifiable_path(path: &Path) -> Result<PathBuf, String> {
+    let path = browsable_path(path)?;
+    let home = tauri::api::path::home_dir().ok_or("Unable to find the home directory")?;
+
+    let protected = [home.join("Library"), home]
+        .into_iter()
+        .chain(BROWSABLE_ROOTS.iter().map(PathBuf::from))
+        .any(|root| path == root);
+
+    if protected {
+        return Err(format!("{} can't be modified", path.display()));
+    }
+
+    Ok(path)
+}
+
+/// Lists the entries of a directory with their metadata, directories first. Rendering icons is
+/// slow for large folders, they're only included with `include_icons`; otherwise
+/// `get_file_info` returns the icon of a single entry.
+#[tauri::command]
+pub async fn list_directory(
+    path: PathBuf,
+    show_hidden: bool,
+    include_icons: bool,
+) -> Result<Vec<FileEntry>, String> {
+    let path = browsable_path(&path)?;
+
+    let mut entries = fs::read_dir(&path)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| show_hidden || !is_hidden(path))
+        .filter_map(|path| FileEntry::from_path(&path, include_icons))
+        .collect::<Vec<_>>();
+
+    entries.sort_by(|a, b| {
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+
+    Ok(entries)
+}
+
+/// Recursively searches a folder for files and directories whose name contains `query`
+/// (case insensitive), skipping hidden entries. Returns at most 200 results, with their icon
+/// only when `include_icons` is set.
+///
+/// Looks at most 50,000 entries and 12 levels deep. Starting another search, or
+/// `cancel_folder_search`, cancels it.
+#[tauri::command]
+pub async fn search_in_folder(
+    path: PathBuf,
+    query: String,
+    include_icons: bool,
+) -> Result<Vec<FileEntry>, String> {
+    let path = browsable_path(&path)?;
+    let generation = SEARCH_GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+
+    async_runtime::spawn_blocking(move || search(path, query, include_icons, generation))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Cancels the running `search_in_folder`, if any
+#[tauri::command]
+pub fn cancel_folder_search() {
+    SEARCH_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+fn search(
+    path: PathBuf,
+    query: String,
+    include_icons: bool,
+    generation: u64,
+) -> Result<Vec<FileEntry>, String> {
+    let query = query.to_lowercase();
+    let mut results = Vec::new();
+    let mut visited = 0;
+    let mut pending = vec![(path, 0)];
+
+    while let Some((dir, depth)) = pending.pop() {
+        if SEARCH_GENERATION.load(Ordering::Relaxed) != generation {
+            return Err("Search was cancelled".into());
+        }
+
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            // Unreadable directories are skipped rather than failing the whole search
+            Err(_) => continue,
+        };
+
+        for entry in entries.flatten() {
+            visited += 1;
+            if visited > MAX_SEARCH_VISITED {
+                return Ok(results);
+            }
+
+            let path = entry.path();
+            if is_hidden(&path) {
+                continue;
+            }
+
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            if name.contains(&query) {
+                if let Some(entry) = FileEntry::from_path(&path, include_icons) {
+                    results.push(entry);
+                }
+
+                if results.len() >= MAX_SEARCH_RESULTS {
+                    return Ok(results);
+                }
+            }
+
+            if depth < MAX_SEARCH_DEPTH && entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                pending.push((path, depth + 1));
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// Returns a file's icon, content type, Finder tags, size and dates. Only files inside the home
+/// directory or `/Volumes` are accepted.
+#[tauri::command]
+pub async fn get_file_info(path: PathBuf) -> Result<FileInfo, String> {
+    let path = browsable_path(&path)?;
+    let metadata = fs::metadata(&path).map_err(|e| e.to_string())?;
+
+    Ok(FileInfo {
+        name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string_lossy().into_owned()),
+        is_dir: metadata.is_dir(),
+        size: metadata.len(),
+        created: to_millis(metadata.created()),
+        modified: to_millis(metadata.modified()),
+        accessed: to_millis(metadata.accessed()),
+        content_type: content_type(&path),
+        kind: kind(&path),
+        tags: tags(&path),
+        icon: icon_data_url(&path, INFO_ICON_SIZE),
+        path,
+    })
+}
+
+/// Moves files to the Trash, the same way Finder does so they can be put back. Only files inside
+/// the home directory or `/Volumes` are accepted.
+#[tauri::command]
+pub async fn trash_files(app_handle: AppHandle<Wry>, paths: Vec<PathBuf>) -> Result<(), String> {
+    let paths = paths
+        .iter()
+        .map(|path| modifiable_path(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (tx, mut rx) = async_runtime::channel(1);
+
+    app_handle
+        .run_on_main_thread(move || unsafe {
+            let urls: id = msg_send![class!(NSMutableArray), array];
+            for path in &paths {
+                let _: () = msg_send![urls, addObject: file_url(path)];
+            }
+
+            let completion_handler = ConcreteBlock::new(move |_: id, error: id| {
+                let _ = tx.try_send(ns_error_to_result(error));
+            })
+            .copy();
+
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let _: () = msg_send![
+                workspace,
+                recycleURLs: urls
+                completionHandler: &*completion_handler
+            ];
+        })
+        .map_err(|e| e.to_string())?;
+
+    rx.recv()
+        .await
+        .unwrap_or_else(|| Err("Trash operation was interrupted".into()))
+}
+
+/// Duplicates a file next to the original ("file copy.txt") and returns the new path. Only files
+/// inside the home directory or `/Volumes` are accepted.
+#[tauri::command]
+pub async fn duplicate_file(app_handle: AppHandle<Wry>, path: PathBuf) -> Result<PathBuf, String> {
+    let path = modifiable_path(&path)?;
+
+    let (tx, mut rx) = async_runtime::channel(1);
+
+    app_handle
+        .run_on_main_thread(move || unsafe {
+            let urls: id = msg_send![class!(NSArray), arrayWithObject: file_url(&path)];
+
+            let completion_handler = ConcreteBlock::new(move |new_urls: id, error: id| {
+                let result = ns_error_to_result(error).and_then(|_| {
+                    duplicated_path(new_urls, &path)
+                        .ok_or_else(|| "Unable to duplicate file".to_string())
+                });
+
+                let _ = tx.try_send(result);
+            })
+            .copy();
+
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let _: () = msg_send![
+                workspace,
+                duplicateURLs: urls
+                completionHandler: &*completion_handler
+            ];
+        })
+        .map_err(|e| e.to_string())?;
+
+    rx.recv()
+        .await
+        .unwrap_or_else(|| Err("Duplicate operation was interrupted".into()))
+}
+
+/// Returns the recent documents of an app, most recent first, e.g. `com.microsoft.VSCode`.
+///
+/// Reads the shared file list Launch Services maintains for the Open Recent menu. Documents that
+/// no longer exist or live on unmounted volumes are skipped.
+#[tauri::command]
+pub async fn get_recent_documents(bundle_id: String) -> Result<Vec<FileEntry>, String> {
+    // Joined into a path, anything else could point outside the shared file lists
+    let valid = !bundle_id.is_empty()
+        && bundle_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    if !valid || bundle_id.starts_with('.') {
+        return Err(format!("Invalid bundle identifier: {}", bundle_id));
+    }
+
+    let home = tauri::api::path::home_dir().ok_or("Unable to find the home directory")?;
+    let dir = home.join(RECENT_DOCUMENTS_DIR);
+    let bundle_id = bundle_id.to_lowercase();
+
+    // sfl3 since macOS 14, sfl2 before
+    let list = ["sfl3", "sfl2"]
+        .iter()
+        .map(|extension| dir.join(format!("{}.{}", bundle_id, extension)))
+        .find(|path| path.exists())
+        .ok_or_else(|| format!("No recent documents found for {}", bundle_id))?;
+
+    let bytes = fs::read(&list).map_err(|e| e.to_string())?;
+
+    Ok(recent_document_paths(&bytes)?
+        .iter()
+        .filter_map(|path| FileEntry::from_path(path, true))
+        .collect())
+}
+
+/// Unarchives a shared file list and resolves the bookmarks of its items
+fn recent_document_paths(bytes: &[u8]) -> Result<Vec<PathBuf>, String> {
+    autoreleasepool(|| {
+        let mut error: id = nil;
+        let items: id = unsafe {
+            // Shared file lists only hold property list types, anything else is refused
+            let classes = [
+                class!(NSDictionary),
+                class!(NSArray),
+                class!(NSString),
+                class!(NSData),
+                class!(NSNumber),
+                class!(NSDate),
+                class!(NSUUID),
+            ]
+            .map(|class| class as *const Class as id);
+            let classes: id = msg_send![
+                class!(NSArray),
+                arrayWithObjects: classes.as_ptr()
+                count: classes.len()
+            ];
+            let classes: id = msg_send![class!(NSSet), setWithArray: classes];
+
+            let data: id = msg_send![
+                class!(NSData),
+                dataWithBytes: bytes.as_ptr()
+                length: bytes.len()
+            ];
+            let list: id = msg_send![
+                class!(NSKeyedUnarchiver),
+                unarchivedObjectOfClasses: classes
+                fromData: data
+                error: &mut error
+            ];
+            let key = NSString::alloc(nil).init_str("items");
+            let key: id = msg_send![key, autorelease];
+
+            msg_send![list, objectForKey: key]
+        };
+        ns_error_to_result(error)?;
+
+        let count: usize = unsafe { msg_send![items, count] };
+
+        Ok((0..count)
+            .filter_map(|index| {
+                let path: id = unsafe {
+                    let item: id = msg_send![items, objectAtIndex: index];
+                    let key = NSString::alloc(nil).init_str("Bookmark");
+                    let key: id = msg_send![key, autorelease];
+                    let bookmark: id = msg_send![item, objectForKey: key];
+
+                    let mut stale: BOOL = NO;
+                    let url: id = msg_send![
+                        class!(NSURL),
+                        URLByResolvingBookmarkData: bookmark
+                        options: BOOKMARK_RESOLUTION_OPTIONS
+                        relativeToURL: nil
+                        bookmarkDataIsStale: &mut stale
+                        error: nil
+                    ];
+
+                    msg_send![url, path]
+                };
+
+                nsstring_to_string!(path).map(PathBuf::from)
+            })
+            .collect())
+    })
+}
+
+/// Looks up the copy of `original` in the URL mapping handed to `duplicateURLs`'s handler
+fn duplicated_path(new_urls: id, original: &Path) -> Option<PathBuf> {
+    let new_url: id = unsafe { msg_send![new_urls, objectForKey: file_url(original)] };
+    let new_path: id = unsafe { msg_send![new_url, path] };
+
+    nsstring_to_string!(new_path).map(PathBuf::from)
+}
+
+fn file_url(path: &Path) -> id {
+    unsafe {
+        let path = NSString::alloc(nil).init_str(&path.to_string_lossy());
+        let path: id = msg_send![path, autorelease];
+        msg_send![class!(NSURL), fileURLWithPath: path]
+    }
+}
+
+/// Reads a resource value of a file URL, must be called inside an autorelease pool
+unsafe fn resource_value(path: &Path, key: id) -> id {
+    let mut value: id = nil;
+    let found: BOOL = msg_send![
+        file_url(path),
+        getResourceValue: &mut value
+        forKey: key
+        error: nil
+    ];
+
+    if found == YES {
+        value
+    } else {
+        nil
+    }
+}
+
+/// Returns the Uniform Type Identifier of a file
+pub(crate) fn content_type(path: &Path) -> Option<String> {
+    autoreleasepool(|| {
+        let content_type = unsafe { resource_value(path, NSURLTypeIdentifierKey) };
+        nsstring_to_string!(content_type)
+    })
+}
+
+fn kind(path: &Path) -> Option<String> {
+    autoreleasepool(|| {
+        let kind = unsafe { resource_value(path, NSURLLocalizedTypeDescriptionKey) };
+        nsstring_to_string!(kind)
+    })
+}
+
+fn tags(path: &Path) -> Vec<String> {
+    autoreleasepool(|| {
+        let tags = unsafe { resource_value(path, NSURLTagNamesKey) };
+        let count: usize = unsafe { msg_send![tags, count] };
+
+        (0..count)
+            .filter_map(|index| {
+                let tag: id = unsafe { msg_send![tags, objectAtIndex: index] };
+                nsstring_to_string!(tag)
+            })
+            .collect()
+    })
+}
+
+/// Returns the Finder icon of a file rendered as a PNG data URL
+pub(crate) fn icon_data_url(path: &Path, size: f64) -> Option<String> {
+    autoreleasepool(|| unsafe {
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let path = NSString::alloc(nil).init_str(&path.to_string_lossy());
+        let path: id = msg_send![path, autorelease];
+        let icon: id = msg_send![workspace, iconForFile: path];
+
+        let mut rect = NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(size, size));
+        let image: id = msg_send![icon, CGImageForProposedRect: &mut rect context: nil hints: nil];
+        if image == nil {
+            return None;
+        }
+
+        let bitmap: id = msg_send![class!(NSBitmapImageRep), alloc];
+        let bitmap: id = msg_send![bitmap, initWithCGImage: image];
+        let bitmap: id = msg_send![bitmap, autorelease];
+        let properties: id = msg_send![class!(NSDictionary), dictionary];
+        let data: id = msg_send![
+            bitmap,
+            representationUsingType: PNG_FILE_TYPE
+            properties: properties
+        ];
+        if data == nil {
+            return None;
+        }
+
+        let bytes: *const u8 = msg_send![data, bytes];
+        let length: usize = msg_send![data, length];
+        let png = std::slice::from_raw_parts(bytes, length);
+
+        Some(format!("data:image/png;base64,{}", STANDARD.encode(png)))
+    })
+}
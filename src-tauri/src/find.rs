@@ -0,0 +1,331 @@
+use std::{ffi::c_void, sync::Mutex};
+
+use block::ConcreteBlock;
+use cocoa::{
+    appkit::NSEventModifierFlags,
+    base::{id, nil, BOOL, NO, YES},
+    foundation::{NSPoint, NSRect, NSSize, NSString},
+};
+use objc::{
+    class,
+    declare::ClassDecl,
+    msg_send,
+    runtime::{self, Class, Object, Protocol, Sel},
+    sel, sel_impl, Message,
+};
+use objc_foundation::INSObject;
+use objc_id::{Id, ShareId};
+use serde::Deserialize;
+use tauri::{async_runtime, AppHandle, Manager, Wry};
+
+use crate::{nsstring_to_string, panel, webview};
+
+const TARGET_CLS_NAME: &str = "RawFindBarTarget";
+
+const FIND_BAR_HEIGHT: f64 = 36.0;
+const FIND_BAR_PADDING: f64 = 8.0;
+const SEARCH_FIELD_TAG: i64 = 1;
+
+/// NSVisualEffectMaterialHeaderView
+const MATERIAL_HEADER_VIEW: i64 = 10;
+/// NSViewWidthSizable
+const VIEW_WIDTH_SIZABLE: u64 = 1 << 1;
+/// NSViewMinYMargin, keeps the bar at the top of an unflipped view
+const VIEW_MIN_Y_MARGIN: u64 = 1 << 3;
+/// NSViewMaxYMargin, keeps the bar at the top of a flipped view
+const VIEW_MAX_Y_MARGIN: u64 = 1 << 5;
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FindOptions {
+    backwards: bool,
+    case_sensitive: bool,
+}
+
+struct FindBar {
+    view: ShareId<Object>,
+    #[allow(dead_code)]
+    target: Id<RawFindBarTarget>,
+}
+
+/// The find bar, while it's shown
+#[derive(Default)]
+pub struct State(Mutex<Option<FindBar>>);
+
+/// Shows or hides a native find bar at the top of the panel.
+///
+/// Typing searches the page, Enter and Shift+Enter go to the next and previous match, and Esc
+/// closes the bar.
+#[tauri::command]
+pub fn set_find_bar_visible(app_handle: AppHandle<Wry>, visible: bool) {
+    let state = app_handle.state::<State>();
+    let mut find_bar = state.0.lock().unwrap();
+    let panel = panel!(app_handle);
+
+    if !visible {
+        if let Some(find_bar) = find_bar.take() {
+            let webview = webview!(app_handle);
+
+            unsafe {
+                let field: id = msg_send![&*find_bar.view, viewWithTag: SEARCH_FIELD_TAG];
+                let _: () = msg_send![field, setDelegate: nil];
+                let _: () = msg_send![&*find_bar.view, removeFromSuperview];
+                let _: () = msg_send![&*panel, makeFirstResponder: webview.as_id()];
+            }
+        }
+
+        return;
+    }
+
+    if let Some(find_bar) = find_bar.as_ref() {
+        let field: id = unsafe { msg_send![&*find_bar.view, viewWithTag: SEARCH_FIELD_TAG] };
+        let _: () = unsafe { msg_send![&*panel, makeFirstResponder: field] };
+        return;
+    }
+
+    let content_view = panel.content_view();
+    let target = RawFindBarTarget::create(app_handle.clone());
+
+    unsafe {
+        let bounds: NSRect = msg_send![content_view, bounds];
+        let flipped: BOOL = msg_send![content_view, isFlipped];
+        let (y, top_margin) = if flipped == YES {
+            (0.0, VIEW_MAX_Y_MARGIN)
+        } else {
+            (bounds.size.height - FIND_BAR_HEIGHT, VIEW_MIN_Y_MARGIN)
+        };
+
+        let view: id = msg_send![class!(NSVisualEffectView), alloc];
+        let view: id = msg_send![
+            view,
+            initWithFrame: NSRect::new(
+                NSPoint::new(0.0, y),
+                NSSize::new(bounds.size.width, FIND_BAR_HEIGHT)
+            )
+        ];
+        let _: () = msg_send![view, setMaterial: MATERIAL_HEADER_VIEW];
+        let _: () = msg_send![view, setAutoresizingMask: VIEW_WIDTH_SIZABLE | top_margin];
+
+        let field: id = msg_send![class!(NSSearchField), alloc];
+        let field: id = msg_send![
+            field,
+            initWithFrame: NSRect::new(
+                NSPoint::new(FIND_BAR_PADDING, FIND_BAR_PADDING / 2.0),
+                NSSize::new(
+                    bounds.size.width - FIND_BAR_PADDING * 2.0,
+                    FIND_BAR_HEIGHT - FIND_BAR_PADDING
+                )
+            )
+        ];
+        let _: () = msg_send![field, setTag: SEARCH_FIELD_TAG];
+        let _: () = msg_send![field, setAutoresizingMask: VIEW_WIDTH_SIZABLE];
+        let _: () = msg_send![field, setDelegate: &*target];
+        let _: () = msg_send![field, setSendsSearchStringImmediately: YES];
+        let _: () = msg_send![view, addSubview: field];
+        let _: () = msg_send![field, release];
+
+        let _: () = msg_send![content_view, addSubview: view positioned: 1i64 relativeTo: nil];
+        let _: () = msg_send![&*panel, makeFirstResponder: field];
+
+        *find_bar = Some(FindBar {
+            view: ShareId::from_retained_ptr(view as *mut Object),
+            target,
+        });
+    }
+}
+
+/// Finds the next match of `query` in the panel's content and selects it, wrapping around.
+/// Resolves to whether there is a match.
+#[tauri::command]
+pub async fn find_in_panel(
+    app_handle: AppHandle<Wry>,
+    query: String,
+    options: Option<FindOptions>,
+) -> Result<bool, String> {
+    let (tx, mut rx) = async_runtime::channel(1);
+    let handle = app_handle.clone();
+
+    app_handle
+        .run_on_main_thread(move || {
+            find(&handle, &query, options.unwrap_or_default(), move |found| {
+                let _ = tx.try_send(found);
+            });
+        })
+        .map_err(|e| e.to_string())?;
+
+    rx.recv()
+        .await
+        .ok_or_else(|| "Find was interrupted".to_string())
+}
+
+/// Searches the webview, `on_result` is called with whether there's a match. Must be called on
+/// the main thread.
+fn find(
+    app_handle: &AppHandle<Wry>,
+    query: &str,
+    options: FindOptions,
+    on_result: impl Fn(bool) + 'static,
+) {
+    let webview = webview!(app_handle);
+
+    let completion_handler = ConcreteBlock::new(move |result: id| {
+        let found: BOOL = unsafe { msg_send![result, matchFound] };
+        on_result(found == YES);
+    })
+    .copy();
+
+    unsafe {
+        let configuration: id = msg_send![class!(WKFindConfiguration), new];
+        let backwards: BOOL = if options.backwards { YES } else { NO };
+        let case_sensitive: BOOL = if options.case_sensitive { YES } else { NO };
+        let _: () = msg_send![configuration, setBackwards: backwards];
+        let _: () = msg_send![configuration, setCaseSensitive: case_sensitive];
+        let _: () = msg_send![configuration, setWraps: YES];
+
+        let query = NSString::alloc(nil).init_str(query);
+        let _: () = msg_send![
+            webview.as_id(),
+            findString: query
+            withConfiguration: configuration
+            completionHandler: &*completion_handler
+        ];
+
+        let _: () = msg_send![query, release];
+        let _: () = msg_send![configuration, release];
+    }
+}
+
+/// Delegate of the find bar's search field
+struct RawFindBarTarget;
+
+impl RawFindBarTarget {
+    fn get_class() -> &'static Class {
+        Class::get(TARGET_CLS_NAME).unwrap_or_else(Self::define_class)
+    }
+
+    fn define_class() -> &'static Class {
+        let mut cls = ClassDecl::new(TARGET_CLS_NAME, class!(NSObject))
+            .unwrap_or_else(|| panic!("Unable to register {} class", TARGET_CLS_NAME));
+
+        cls.add_protocol(
+            Protocol::get("NSSearchFieldDelegate")
+                .expect("Failed to get NSSearchFieldDelegate protocol"),
+        );
+
+        unsafe {
+            cls.add_ivar::<*mut c_void>("_appHandle");
+
+            cls.add_method(
+                sel!(controlTextDidChange:),
+                Self::control_text_did_change as extern "C" fn(&Object, Sel, id),
+            );
+
+            cls.add_method(
+                sel!(control:textView:doCommandBySelector:),
+                Self::do_command_by_selector as extern "C" fn(&Object, Sel, id, id, Sel) -> BOOL,
+            );
+
+            cls.add_method(
+                sel!(dealloc),
+                Self::dealloc as extern "C" fn(&mut Object, Sel),
+            );
+        }
+
+        cls.register()
+    }
+
+    fn create(app_handle: AppHandle<Wry>) -> Id<Self> {
+        let target = Self::new();
+        let app_handle = Box::into_raw(Box::new(app_handle)) as *mut c_void;
+
+        unsafe {
+            let this = &mut *(&*target as *const Self as *mut Object);
+            this.set_ivar("_appHandle", app_handle);
+        }
+
+        target
+    }
+
+    fn app_handle(this: &Object) -> &AppHandle<Wry> {
+        unsafe { &*(*this.get_ivar::<*mut c_void>("_appHandle") as *const AppHandle<Wry>) }
+    }
+
+    /// Searches for the field's text and tints it red when there's no match
+    fn find(this: &Object, field: id, backwards: bool) {
+        let query: id = unsafe { msg_send![field, stringValue] };
+        let query = nsstring_to_string!(query).unwrap_or_default();
+
+        let field: ShareId<Object> = unsafe { ShareId::from_ptr(field as *mut Object) };
+        let options = FindOptions {
+            backwards,
+            case_sensitive: false,
+        };
+
+        find(
+            Self::app_handle(this),
+            &query,
+            options,
+            move |found| unsafe {
+                let color: id = if found || query.is_empty() {
+                    msg_send![class!(NSColor), controlTextColor]
+                } else {
+                    msg_send![class!(NSColor), systemRedColor]
+                };
+                let _: () = msg_send![&*field, setTextColor: color];
+            },
+        );
+    }
+
+    extern "C" fn control_text_did_change(this: &Object, _: Sel, notification: id) {
+        let field: id = unsafe { msg_send![notification, object] };
+        Self::find(this, field, false);
+    }
+
+    extern "C" fn do_command_by_selector(
+        this: &Object,
+        _: Sel,
+        control: id,
+        _text_view: id,
+        command: Sel,
+    ) -> BOOL {
+        if command == sel!(insertNewline:) {
+            let flags: NSEventModifierFlags = unsafe { msg_send![class!(NSEvent), modifierFlags] };
+            Self::find(
+                this,
+                control,
+                flags.contains(NSEventModifierFlags::NSShiftKeyMask),
+            );
+            return YES;
+        }
+
+        if command == sel!(cancelOperation:) {
+            // Closing releases this target, which must not happen while it's handling a command
+            let app_handle = Self::app_handle(this).clone();
+            let handle = app_handle.clone();
+            let _ = app_handle.run_on_main_thread(move || set_find_bar_visible(handle, false));
+            return YES;
+        }
+
+        NO
+    }
+
+    /// Releases the boxed AppHandle
+    extern "C" fn dealloc(this: &mut Object, _: Sel) {
+        unsafe {
+            let app_handle: *mut c_void = *this.get_ivar("_appHandle");
+            if !app_handle.is_null() {
+                drop(Box::from_raw(app_handle as *mut AppHandle<Wry>));
+            }
+
+            let _: () = msg_send![super(this, class!(NSObject)), dealloc];
+        }
+    }
+}
+
+unsafe impl Message for RawFindBarTarget {}
+
+impl INSObject for RawFindBarTarget {
+    fn class() -> &'static runtime::Class {
+        Self::get_class()
+    }
+}
@@ -0,0 +1,255 @@
+use std::sync::Mutex;
+
+use block::ConcreteBlock;
+use cocoa::{
+    base::{id, nil, BOOL, NO},
+    foundation::NSString,
+};
+use objc::{class, msg_send, sel, sel_impl};
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{
+    get_state, nsstring_to_string, spotlight,
+    trace::{self, TraceKind},
+};
+
+const SCREEN_LOCKED_NOTIFICATION: &str = "com.apple.screenIsLocked";
+const SCREEN_UNLOCKED_NOTIFICATION: &str = "com.apple.screenIsUnlocked";
+
+/// System processes that can be frontmost, e.g. while the screen is locked, but can't be handed
+/// focus back to. Matched against bundle identifiers and, for processes without one, names.
+const NON_RESTORABLE_APPS: &[&str] = &[
+    "com.apple.loginwindow",
+    "com.apple.dock",
+    "loginwindow",
+    "Dock",
+    "WindowServer",
+];
+
+/// NSApplicationActivateIgnoringOtherApps
+const ACTIVATE_IGNORING_OTHER_APPS: u64 = 1 << 1;
+
+/// App that was frontmost when the panel was shown
+#[derive(Clone)]
+struct FocusTarget {
+    pid: i32,
+}
+
+/// App focus is always handed back to when the panel hides, set with `set_focus_restore_target`
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FocusRestoreTarget {
+    BundleId(String),
+    Pid(i32),
+}
+
+impl FocusRestoreTarget {
+    /// Pid of the running app, the first instance for a bundle identifier
+    fn pid(&self) -> Option<i32> {
+        match self {
+            FocusRestoreTarget::Pid(pid) => Some(*pid),
+            FocusRestoreTarget::BundleId(bundle_id) => unsafe {
+                let bundle_id = NSString::alloc(nil).init_str(bundle_id);
+                let bundle_id: id = msg_send![bundle_id, autorelease];
+                let apps: id = msg_send![
+                    class!(NSRunningApplication),
+                    runningApplicationsWithBundleIdentifier: bundle_id
+                ];
+                let app: id = msg_send![apps, firstObject];
+
+                if app == nil {
+                    None
+                } else {
+                    Some(msg_send![app, processIdentifier])
+                }
+            },
+        }
+    }
+}
+
+#[derive(Default)]
+struct Focus {
+    remembered: Option<FocusTarget>,
+    forced: Option<FocusRestoreTarget>,
+}
+
+#[derive(Default)]
+pub struct State(Mutex<Focus>);
+
+/// Forces focus back to a specific app, `{ bundleId }` or `{ pid }`, every time the panel hides,
+/// whether or not it was frontmost or the panel took focus. `null` goes back to the app that was
+/// frontmost when the panel was shown.
+///
+/// Meant for kiosk and demo setups. Not persisted, and skipped if the app isn't running when the
+/// panel hides.
+#[tauri::command]
+pub fn set_focus_restore_target(app_handle: AppHandle<Wry>, target: Option<FocusRestoreTarget>) {
+    app_handle.state::<State>().0.lock().unwrap().forced = target;
+}
+
+/// Remembers the frontmost app so focus can be handed back to it when the panel hides
+pub(crate) fn remember_focus_target(app_handle: &AppHandle<Wry>) {
+    let app: id = unsafe {
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        msg_send![workspace, frontmostApplication]
+    };
+
+    if app == nil {
+        return;
+    }
+
+    let pid: i32 = unsafe { msg_send![app, processIdentifier] };
+
+    // Showing the panel again while we're frontmost keeps the original target
+    if pid == std::process::id() as i32 {
+        return;
+    }
+
+    let target = if is_restorable(app) {
+        Some(FocusTarget { pid })
+    } else {
+        None
+    };
+
+    app_handle.state::<State>().0.lock().unwrap().remembered = target;
+}
+
+/// Pid of the app that was frontmost when the panel was shown, until the panel hides
+pub(crate) fn focus_target_pid(app_handle: &AppHandle<Wry>) -> Option<i32> {
+    app_handle
+        .state::<State>()
+        .0
+        .lock()
+        .unwrap()
+        .remembered
+        .as_ref()
+        .map(|target| target.pid)
+}
+
+/// Hands focus back to the remembered app if the app took it while the panel was shown (e.g.
+/// through a dialog or another window). Does nothing if there's no restorable target.
+///
+/// A target set with `set_focus_restore_target` is activated instead, unconditionally.
+pub(crate) fn restore_focus(app_handle: &AppHandle<Wry>) {
+    let (remembered, forced) = {
+        let state = app_handle.state::<State>();
+        let mut focus = state.0.lock().unwrap();
+        (focus.remembered.take(), focus.forced.clone())
+    };
+
+    let pid = match forced {
+        Some(forced) => match forced.pid() {
+            Some(pid) => pid,
+            None => return,
+        },
+        None => {
+            let target = match remembered {
+                Some(target) => target,
+                None => return,
+            };
+
+            let active: BOOL = unsafe {
+                let ns_app: id = msg_send![class!(NSApplication), sharedApplication];
+                msg_send![ns_app, isActive]
+            };
+
+            // The panel never activates the app on its own, focus is still where it was
+            if active == NO {
+                return;
+            }
+
+            target.pid
+        }
+    };
+
+    if !activate(pid) {
+        return;
+    }
+    trace::record(TraceKind::FocusRestore, format!("activated pid {}", pid));
+}
+
+/// Activates the app with the given pid, returns whether it's still running
+fn activate(pid: i32) -> bool {
+    let app: id = unsafe {
+        msg_send![
+            class!(NSRunningApplication),
+            runningApplicationWithProcessIdentifier: pid
+        ]
+    };
+
+    if app == nil {
+        return false;
+    }
+
+    let _: BOOL = unsafe { msg_send![app, activateWithOptions: ACTIVATE_IGNORING_OTHER_APPS] };
+
+    true
+}
+
+fn is_restorable(app: id) -> bool {
+    let (bundle_id, name): (id, id) = unsafe {
+        (
+            msg_send![app, bundleIdentifier],
+            msg_send![app, localizedName],
+        )
+    };
+
+    let bundle_id = nsstring_to_string!(bundle_id);
+    let name = nsstring_to_string!(name);
+
+    ![bundle_id, name]
+        .iter()
+        .flatten()
+        .any(|identifier| NON_RESTORABLE_APPS.contains(&identifier.as_str()))
+}
+
+/// Hides the panel and forgets the focus target when the screen locks, and makes sure the toggle
+/// shortcut is still registered once it's unlocked. Must be called on the main thread.
+pub fn watch_screen_lock(app_handle: AppHandle<Wry>) {
+    let locked_handle = app_handle.clone();
+    let on_locked = ConcreteBlock::new(move |_notification: id| {
+        locked_handle
+            .state::<State>()
+            .0
+            .lock()
+            .unwrap()
+            .remembered
+            .take();
+
+        if get_state!(locked_handle, panel, is_some) {
+            spotlight::hide_spotlight(locked_handle.clone());
+        }
+    })
+    .copy();
+
+    let on_unlocked = ConcreteBlock::new(move |_notification: id| {
+        spotlight::check_shortcut(&app_handle);
+    })
+    .copy();
+
+    unsafe {
+        let center: id = msg_send![class!(NSDistributedNotificationCenter), defaultCenter];
+        let queue: id = msg_send![class!(NSOperationQueue), mainQueue];
+
+        let name = NSString::alloc(nil).init_str(SCREEN_LOCKED_NOTIFICATION);
+        let _: id = msg_send![
+            center,
+            addObserverForName: name
+            object: nil
+            queue: queue
+            usingBlock: &*on_locked
+        ];
+        let _: () = msg_send![name, release];
+
+        let name = NSString::alloc(nil).init_str(SCREEN_UNLOCKED_NOTIFICATION);
+        let _: id = msg_send![
+            center,
+            addObserverForName: name
+            object: nil
+            queue: queue
+            usingBlock: &*on_unlocked
+        ];
+        let _: () = msg_send![name, release];
+    }
+}
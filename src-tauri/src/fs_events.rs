@@ -0,0 +1,283 @@
+use std::{
+    collections::HashMap,
+    ffi::{c_void, CStr},
+    os::raw::c_char,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex,
+    },
+};
+
+use core_foundation::{
+    array::{CFArray, CFArrayRef},
+    base::TCFType,
+    string::CFString,
+};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{
+    dispatch::{self, DispatchObject},
+    events,
+};
+
+const PATH_CHANGED_EVENT: &str = "spotlight://path-changed";
+
+/// Default time FSEvents coalesces changes for before reporting them
+const DEFAULT_LATENCY_MS: u64 = 300;
+
+/// kFSEventStreamEventIdSinceNow
+const EVENT_ID_SINCE_NOW: u64 = 0xFFFF_FFFF_FFFF_FFFF;
+
+/// FSEventStreamCreateFlags
+const CREATE_FLAG_WATCH_ROOT: u32 = 0x04;
+const CREATE_FLAG_FILE_EVENTS: u32 = 0x10;
+
+/// FSEventStreamEventFlags
+const EVENT_FLAG_MUST_SCAN_SUB_DIRS: u32 = 0x01;
+const EVENT_FLAG_ROOT_CHANGED: u32 = 0x20;
+const EVENT_FLAG_ITEM_CREATED: u32 = 0x100;
+const EVENT_FLAG_ITEM_REMOVED: u32 = 0x200;
+const EVENT_FLAG_ITEM_RENAMED: u32 = 0x800;
+const EVENT_FLAG_ITEM_IS_DIR: u32 = 0x20000;
+
+#[repr(C)]
+struct FSEventStreamContext {
+    version: isize,
+    info: *mut c_void,
+    retain: Option<extern "C" fn(*const c_void) -> *const c_void>,
+    release: Option<extern "C" fn(*const c_void)>,
+    copy_description: *const c_void,
+}
+
+type FSEventStreamRef = *mut c_void;
+
+type FSEventStreamCallback = extern "C" fn(
+    stream: FSEventStreamRef,
+    info: *mut c_void,
+    num_events: usize,
+    event_paths: *mut c_void,
+    event_flags: *const u32,
+    event_ids: *const u64,
+);
+
+#[link(name = "CoreServices", kind = "framework")]
+extern "C" {
+    fn FSEventStreamCreate(
+        allocator: *const c_void,
+        callback: FSEventStreamCallback,
+        context: *const FSEventStreamContext,
+        paths_to_watch: CFArrayRef,
+        since_when: u64,
+        latency: f64,
+        flags: u32,
+    ) -> FSEventStreamRef;
+    fn FSEventStreamSetDispatchQueue(stream: FSEventStreamRef, queue: *const DispatchObject);
+    fn FSEventStreamStart(stream: FSEventStreamRef) -> bool;
+    fn FSEventStreamStop(stream: FSEventStreamRef);
+    fn FSEventStreamInvalidate(stream: FSEventStreamRef);
+    fn FSEventStreamRelease(stream: FSEventStreamRef);
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum ChangeKind {
+    Created,
+    Removed,
+    Renamed,
+    Modified,
+    /// Events were dropped or the watched path itself moved, the whole tree should be rescanned
+    Rescan,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Change {
+    path: String,
+    kind: ChangeKind,
+    is_dir: bool,
+}
+
+#[derive(Clone, Serialize)]
+struct PathChangedPayload {
+    id: u32,
+    changes: Vec<Change>,
+}
+
+/// Owned by the stream through its context and released with it
+struct WatchContext {
+    app_handle: AppHandle<Wry>,
+    id: u32,
+    root: PathBuf,
+    recursive: bool,
+}
+
+struct Watcher(FSEventStreamRef);
+
+// Streams are scheduled on the main queue, and only started and stopped from commands
+unsafe impl Send for Watcher {}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        unsafe {
+            FSEventStreamStop(self.0);
+            FSEventStreamInvalidate(self.0);
+            FSEventStreamRelease(self.0);
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct State {
+    next_id: AtomicU32,
+    watchers: Mutex<HashMap<u32, Watcher>>,
+}
+
+/// Watches a file or directory and returns a watch id.
+///
+/// Changes are coalesced for `latency` milliseconds (300 by default) and emitted in batches as
+/// `spotlight://path-changed`. Unless `recursive` is set, only changes to direct children of a
+/// directory are reported.
+#[tauri::command]
+pub fn watch_path(
+    app_handle: AppHandle<Wry>,
+    path: PathBuf,
+    recursive: bool,
+    latency: Option<u64>,
+) -> Result<u32, String> {
+    let root = path.canonicalize().map_err(|e| e.to_string())?;
+
+    let state = app_handle.state::<State>();
+    let id = state.next_id.fetch_add(1, Ordering::Relaxed);
+
+    let context = FSEventStreamContext {
+        version: 0,
+        info: Box::into_raw(Box::new(WatchContext {
+            app_handle: app_handle.clone(),
+            id,
+            root: root.clone(),
+            recursive,
+        })) as *mut c_void,
+        retain: None,
+        release: Some(release_context),
+        copy_description: std::ptr::null(),
+    };
+
+    let paths = CFArray::from_CFTypes(&[CFString::new(&root.to_string_lossy())]);
+    let latency = latency.unwrap_or(DEFAULT_LATENCY_MS) as f64 / 1000.0;
+
+    let stream = unsafe {
+        FSEventStreamCreate(
+            std::ptr::null(),
+            on_events,
+            &context,
+            paths.as_concrete_TypeRef(),
+            EVENT_ID_SINCE_NOW,
+            latency,
+            CREATE_FLAG_FILE_EVENTS | CREATE_FLAG_WATCH_ROOT,
+        )
+    };
+
+    if stream.is_null() {
+        release_context(context.info);
+        return Err(format!("Unable to watch \"{}\"", root.display()));
+    }
+
+    let watcher = Watcher(stream);
+
+    unsafe {
+        FSEventStreamSetDispatchQueue(stream, dispatch::main_queue());
+
+        if !FSEventStreamStart(stream) {
+            return Err(format!("Unable to watch \"{}\"", root.display()));
+        }
+    }
+
+    state.watchers.lock().unwrap().insert(id, watcher);
+
+    Ok(id)
+}
+
+/// Stops a watch started with `watch_path`
+#[tauri::command]
+pub fn unwatch(app_handle: AppHandle<Wry>, id: u32) -> Result<(), String> {
+    app_handle
+        .state::<State>()
+        .watchers
+        .lock()
+        .unwrap()
+        .remove(&id)
+        .map(drop)
+        .ok_or_else(|| format!("Watch {} does not exist", id))
+}
+
+extern "C" fn release_context(info: *const c_void) {
+    unsafe { drop(Box::from_raw(info as *mut WatchContext)) };
+}
+
+extern "C" fn on_events(
+    _: FSEventStreamRef,
+    info: *mut c_void,
+    num_events: usize,
+    event_paths: *mut c_void,
+    event_flags: *const u32,
+    _: *const u64,
+) {
+    let context = unsafe { &*(info as *const WatchContext) };
+    let paths =
+        unsafe { std::slice::from_raw_parts(event_paths as *const *const c_char, num_events) };
+    let flags = unsafe { std::slice::from_raw_parts(event_flags, num_events) };
+
+    let changes = paths
+        .iter()
+        .zip(flags)
+        .filter_map(|(&path, &flags)| {
+            let path = unsafe { CStr::from_ptr(path) }
+                .to_string_lossy()
+                .into_owned();
+
+            if !context.recursive && !is_direct_child(&context.root, Path::new(&path)) {
+                return None;
+            }
+
+            Some(Change {
+                kind: change_kind(flags),
+                is_dir: flags & EVENT_FLAG_ITEM_IS_DIR != 0,
+                path,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if changes.is_empty() {
+        return;
+    }
+
+    let _ = events::emit(
+        &context.app_handle,
+        PATH_CHANGED_EVENT,
+        PathChangedPayload {
+            id: context.id,
+            changes,
+        },
+    );
+}
+
+fn change_kind(flags: u32) -> ChangeKind {
+    if flags & (EVENT_FLAG_MUST_SCAN_SUB_DIRS | EVENT_FLAG_ROOT_CHANGED) != 0 {
+        ChangeKind::Rescan
+    } else if flags & EVENT_FLAG_ITEM_RENAMED != 0 {
+        ChangeKind::Renamed
+    } else if flags & EVENT_FLAG_ITEM_REMOVED != 0 {
+        ChangeKind::Removed
+    } else if flags & EVENT_FLAG_ITEM_CREATED != 0 {
+        ChangeKind::Created
+    } else {
+        ChangeKind::Modified
+    }
+}
+
+/// Whether `path` is `root` itself or directly inside it
+fn is_direct_child(root: &Path, path: &Path) -> bool {
+    path == root || path.parent() == Some(root)
+}
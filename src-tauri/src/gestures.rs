@@ -0,0 +1,162 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use block::ConcreteBlock;
+use cocoa::{
+    appkit::CGFloat,
+    base::{id, BOOL, YES},
+};
+use objc::{class, msg_send, sel, sel_impl};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{events, get_state};
+
+const SCROLL_EVENT: &str = "spotlight://scroll";
+const GESTURE_EVENT: &str = "spotlight://gesture";
+
+const NS_EVENT_TYPE_SCROLL_WHEEL: u64 = 22;
+const NS_EVENT_TYPE_MAGNIFY: u64 = 30;
+const NS_EVENT_TYPE_SWIPE: u64 = 31;
+
+#[derive(Default)]
+pub struct State(AtomicBool);
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum Phase {
+    None,
+    Began,
+    Stationary,
+    Changed,
+    Ended,
+    Cancelled,
+    MayBegin,
+}
+
+impl From<u64> for Phase {
+    /// Maps an `NSEventPhase` to a phase
+    fn from(phase: u64) -> Self {
+        match phase {
+            0x01 => Phase::Began,
+            0x02 => Phase::Stationary,
+            0x04 => Phase::Changed,
+            0x08 => Phase::Ended,
+            0x10 => Phase::Cancelled,
+            0x20 => Phase::MayBegin,
+            _ => Phase::None,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScrollPayload {
+    delta_x: f64,
+    delta_y: f64,
+    precise: bool,
+    phase: Phase,
+    momentum_phase: Phase,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum GesturePayload {
+    #[serde(rename_all = "camelCase")]
+    Magnify { magnification: f64, phase: Phase },
+    #[serde(rename_all = "camelCase")]
+    Swipe { delta_x: f64, delta_y: f64 },
+}
+
+/// Enables or disables forwarding of the panel's native scroll and gesture events.
+///
+/// When enabled, scroll wheel events are emitted as `spotlight://scroll` with their phase and
+/// momentum phase, and pinch/swipe gestures as `spotlight://gesture`. Off by default since these
+/// are high frequency streams.
+#[tauri::command]
+pub fn set_gesture_events_enabled(app_handle: AppHandle<Wry>, enabled: bool) {
+    app_handle
+        .state::<State>()
+        .0
+        .store(enabled, Ordering::Relaxed);
+}
+
+/// Installs a local monitor observing scroll and gesture events on the panel.
+///
+/// Events are only observed, never swallowed. Must be called on the main thread.
+pub fn install_gesture_monitor(app_handle: AppHandle<Wry>) {
+    let mask = (1u64 << NS_EVENT_TYPE_SCROLL_WHEEL)
+        | (1u64 << NS_EVENT_TYPE_MAGNIFY)
+        | (1u64 << NS_EVENT_TYPE_SWIPE);
+
+    let handler = ConcreteBlock::new(move |event: id| -> id {
+        if !app_handle.state::<State>().0.load(Ordering::Relaxed) {
+            return event;
+        }
+
+        let in_panel = get_state!(app_handle, panel, clone)
+            .map(|panel| {
+                let window: id = unsafe { msg_send![event, window] };
+                window == &*panel as *const _ as id
+            })
+            .unwrap_or(false);
+
+        if in_panel {
+            emit_event(&app_handle, event);
+        }
+
+        event
+    })
+    .copy();
+
+    let _: id = unsafe {
+        msg_send![
+            class!(NSEvent),
+            addLocalMonitorForEventsMatchingMask: mask
+            handler: &*handler
+        ]
+    };
+}
+
+fn emit_event(app_handle: &AppHandle<Wry>, event: id) {
+    let event_type: u64 = unsafe { msg_send![event, type] };
+
+    match event_type {
+        NS_EVENT_TYPE_SCROLL_WHEEL => {
+            let delta_x: CGFloat = unsafe { msg_send![event, scrollingDeltaX] };
+            let delta_y: CGFloat = unsafe { msg_send![event, scrollingDeltaY] };
+            let precise: BOOL = unsafe { msg_send![event, hasPreciseScrollingDeltas] };
+            let phase: u64 = unsafe { msg_send![event, phase] };
+            let momentum_phase: u64 = unsafe { msg_send![event, momentumPhase] };
+
+            let payload = ScrollPayload {
+                delta_x,
+                delta_y,
+                precise: precise == YES,
+                phase: phase.into(),
+                momentum_phase: momentum_phase.into(),
+            };
+
+            let _ = events::emit(app_handle, SCROLL_EVENT, payload);
+        }
+        NS_EVENT_TYPE_MAGNIFY => {
+            let magnification: CGFloat = unsafe { msg_send![event, magnification] };
+            let phase: u64 = unsafe { msg_send![event, phase] };
+
+            let payload = GesturePayload::Magnify {
+                magnification,
+                phase: phase.into(),
+            };
+
+            let _ = events::emit(app_handle, GESTURE_EVENT, payload);
+        }
+        NS_EVENT_TYPE_SWIPE => {
+            let delta_x: CGFloat = unsafe { msg_send![event, deltaX] };
+            let delta_y: CGFloat = unsafe { msg_send![event, deltaY] };
+
+            let payload = GesturePayload::Swipe { delta_x, delta_y };
+
+            let _ = events::emit(app_handle, GESTURE_EVENT, payload);
+        }
+        _ => {}
+    }
+}
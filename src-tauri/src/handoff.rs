@@ -0,0 +1,145 @@
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+    thread,
+    time::Duration,
+};
+
+use tauri::{AppHandle, Wry};
+
+use crate::{
+    paths, spotlight,
+    trace::{self, TraceKind},
+};
+
+const SOCKET_FILE: &str = "handoff.sock";
+
+/// Sent by the new instance to ask the running one for the shortcut
+const HANDOFF_REQUEST: &str = "handoff";
+/// Sent back by the running instance once its shortcut is unregistered, right before it exits
+const HANDOFF_READY: &str = "ready";
+
+/// How long the new instance waits for the running one to let go of the shortcut
+const HANDOFF_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Set by the instance relaunching itself after an update, only then does the new one ask for
+/// the shortcut
+const RELAUNCH_ENV: &str = "SPOTLIGHT_RELAUNCHED_FOR_UPDATE";
+
+fn socket_path(app_handle: &AppHandle<Wry>) -> PathBuf {
+    paths::app_dirs(app_handle).data.join(SOCKET_FILE)
+}
+
+/// Marks the instance about to be started by `restart()` as relaunched for an update, so it
+/// takes the shortcut over
+pub(crate) fn prepare_relaunch() {
+    env::set_var(RELAUNCH_ENV, "1");
+}
+
+/// Takes the toggle shortcut over from the instance an update is replacing, if it's still
+/// running, then listens for the next instance to do the same.
+///
+/// Only done when relaunched by the updater, other launches leave the running instance alone.
+/// It unregisters its shortcut, confirms and exits, and the shortcut is registered here right
+/// after, in the background. A socket left behind by an instance that crashed is replaced. Must
+/// be called before the panel is initialized.
+pub fn take_over_shortcut(app_handle: AppHandle<Wry>) {
+    let path = socket_path(&app_handle);
+
+    let relaunched = env::var_os(RELAUNCH_ENV).is_some();
+    env::remove_var(RELAUNCH_ENV);
+
+    // Connected before listening, which replaces the socket. Fails when the replaced instance
+    // already exited, with at most a socket left over.
+    let stream = if relaunched {
+        UnixStream::connect(&path).ok()
+    } else {
+        None
+    };
+
+    if let Some(stream) = stream {
+        let handle = app_handle.clone();
+
+        thread::spawn(move || match request_handoff(stream) {
+            Ok(()) => {
+                let main_handle = handle.clone();
+                let _ =
+                    handle.run_on_main_thread(move || spotlight::reclaim_shortcut(&main_handle));
+            }
+            Err(error) => {
+                trace::record(TraceKind::Shortcut, format!("handoff failed: {}", error));
+            }
+        });
+    }
+
+    listen_for_handoff(app_handle, path);
+}
+
+fn request_handoff(mut stream: UnixStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(HANDOFF_TIMEOUT))?;
+    writeln!(stream, "{}", HANDOFF_REQUEST)?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+
+    if reply.trim() != HANDOFF_READY {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!("unexpected reply {:?}", reply.trim()),
+        ));
+    }
+
+    Ok(())
+}
+
+fn listen_for_handoff(app_handle: AppHandle<Wry>, path: PathBuf) {
+    // Replaces the socket of the previous instance, which doesn't remove it when exiting
+    let _ = fs::remove_file(&path);
+
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(error) => {
+            trace::record(
+                TraceKind::Shortcut,
+                format!("unable to listen for handoffs: {}", error),
+            );
+            return;
+        }
+    };
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if hand_off(&app_handle, stream).is_ok() {
+                spotlight::prepare_for_exit(&app_handle);
+                app_handle.exit(0);
+                return;
+            }
+        }
+    });
+}
+
+/// Unregisters the shortcut for the instance asking for it and tells it it's free
+fn hand_off(app_handle: &AppHandle<Wry>, stream: UnixStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(HANDOFF_TIMEOUT))?;
+
+    let mut request = String::new();
+    BufReader::new(&stream).read_line(&mut request)?;
+
+    if request.trim() != HANDOFF_REQUEST {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "not a handoff request",
+        ));
+    }
+
+    spotlight::release_shortcut_for_handoff(app_handle);
+    trace::record(TraceKind::Shortcut, "handed off to a new instance");
+
+    writeln!(&stream, "{}", HANDOFF_READY)
+}
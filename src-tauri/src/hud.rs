@@ -0,0 +1,387 @@
+use std::{sync::Mutex, time::Duration};
+
+use block::ConcreteBlock;
+use cocoa::{
+    appkit::{NSMainMenuWindowLevel, NSWindowCollectionBehavior},
+    base::{id, nil, NO, YES},
+    foundation::{NSPoint, NSRect, NSSize, NSString},
+};
+use objc::{class, msg_send, runtime::Object, sel, sel_impl};
+use objc_id::ShareId;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{nsstring_to_string, spotlight::NSMouseInRect};
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGWindowLevelForKey(key: i32) -> i32;
+}
+
+const HUD_PADDING: f64 = 24.0;
+const HUD_CORNER_RADIUS: f64 = 12.0;
+const HUD_FONT_SIZE: f64 = 20.0;
+
+/// NSWindowStyleMaskBorderless | NSWindowStyleMaskNonactivatingPanel
+const HUD_STYLE_MASK: u64 = 1 << 7;
+/// NSBackingStoreBuffered
+const BACKING_STORE_BUFFERED: u64 = 2;
+/// NSVisualEffectMaterialHUDWindow
+const MATERIAL_HUD_WINDOW: i64 = 13;
+/// NSVisualEffectStateActive
+const EFFECT_STATE_ACTIVE: i64 = 1;
+
+const STRIP_PADDING: f64 = 8.0;
+const STRIP_MARGIN: f64 = 12.0;
+const STRIP_CORNER_RADIUS: f64 = 8.0;
+const STRIP_FONT_SIZE: f64 = 13.0;
+/// NSFontWeightMedium
+const FONT_WEIGHT_MEDIUM: f64 = 0.23;
+/// kCGDesktopWindowLevelKey
+const DESKTOP_WINDOW_LEVEL_KEY: i32 = 2;
+
+/// Abbreviated weekday, hour and minutes, e.g. "Fri 14:05"
+const CLOCK_FORMAT_TEMPLATE: &str = "EEEjmm";
+const CLOCK_UPDATE_INTERVAL: Duration = Duration::from_secs(10);
+
+const SCREEN_PARAMETERS_CHANGED_NOTIFICATION: &str =
+    "NSApplicationDidChangeScreenParametersNotification";
+
+/// Shows a line of text in a click-through, native HUD on the screen with the cursor for
+/// `duration`, without stealing focus. Must be called on the main thread.
+///
+/// Used for feedback that has to be visible while the panel is hidden, e.g. an expired timer.
+pub(crate) fn show_hud(text: &str, duration: Duration) {
+    unsafe {
+        let label = create_label(text, HUD_FONT_SIZE);
+        let label_size: NSSize = msg_send![label, fittingSize];
+        let size = NSSize::new(
+            label_size.width + HUD_PADDING * 2.0,
+            label_size.height + HUD_PADDING * 2.0,
+        );
+
+        let panel = create_hud_panel(
+            NSRect::new(hud_origin(size), size),
+            NSMainMenuWindowLevel + 2,
+            HUD_CORNER_RADIUS,
+        );
+        let _: () = msg_send![panel, setReleasedWhenClosed: YES];
+
+        let _: () = msg_send![label, setFrameOrigin: NSPoint::new(HUD_PADDING, HUD_PADDING)];
+        let content_view: id = msg_send![panel, contentView];
+        let _: () = msg_send![content_view, addSubview: label];
+
+        let _: () = msg_send![panel, orderFrontRegardless];
+
+        // Released when closed, the pending perform keeps it alive until then
+        let _: () = msg_send![
+            panel,
+            performSelector: sel!(close)
+            withObject: nil
+            afterDelay: duration.as_secs_f64()
+        ];
+    }
+}
+
+/// Creates a borderless, click-through panel with a HUD material background at `level`, shown on
+/// every Space. The caller owns it.
+unsafe fn create_hud_panel(frame: NSRect, level: i64, corner_radius: f64) -> id {
+    let panel: id = msg_send![class!(NSPanel), alloc];
+    let panel: id = msg_send![
+        panel,
+        initWithContentRect: frame
+        styleMask: HUD_STYLE_MASK
+        backing: BACKING_STORE_BUFFERED
+        defer: NO
+    ];
+
+    // Our app is rarely active, panels hide on deactivation by default
+    let _: () = msg_send![panel, setHidesOnDeactivate: NO];
+    let _: () = msg_send![panel, setLevel: level];
+    let _: () = msg_send![panel, setIgnoresMouseEvents: YES];
+    let _: () = msg_send![panel, setOpaque: NO];
+    let _: () = msg_send![panel, setHasShadow: YES];
+    let clear: id = msg_send![class!(NSColor), clearColor];
+    let _: () = msg_send![panel, setBackgroundColor: clear];
+    let _: () = msg_send![
+        panel,
+        setCollectionBehavior: NSWindowCollectionBehavior::NSWindowCollectionBehaviorCanJoinAllSpaces
+            | NSWindowCollectionBehavior::NSWindowCollectionBehaviorFullScreenAuxiliary
+    ];
+
+    let effect_view: id = msg_send![class!(NSVisualEffectView), alloc];
+    let effect_view: id = msg_send![
+        effect_view,
+        initWithFrame: NSRect::new(NSPoint::new(0.0, 0.0), frame.size)
+    ];
+    let _: () = msg_send![effect_view, setMaterial: MATERIAL_HUD_WINDOW];
+    let _: () = msg_send![effect_view, setState: EFFECT_STATE_ACTIVE];
+    let _: () = msg_send![effect_view, setWantsLayer: YES];
+    let layer: id = msg_send![effect_view, layer];
+    let _: () = msg_send![layer, setCornerRadius: corner_radius];
+    let _: () = msg_send![layer, setMasksToBounds: YES];
+
+    let _: () = msg_send![panel, setContentView: effect_view];
+    let _: () = msg_send![effect_view, release];
+
+    panel
+}
+
+unsafe fn create_label(text: &str, font_size: f64) -> id {
+    let text = NSString::alloc(nil).init_str(text);
+    let text: id = msg_send![text, autorelease];
+    let label: id = msg_send![class!(NSTextField), labelWithString: text];
+    let font: id = msg_send![class!(NSFont), boldSystemFontOfSize: font_size];
+    let _: () = msg_send![label, setFont: font];
+
+    label
+}
+
+/// Horizontally centered in the lower third of the screen with the cursor, like system HUDs
+fn hud_origin(size: NSSize) -> NSPoint {
+    unsafe {
+        let mouse_location: NSPoint = msg_send![class!(NSEvent), mouseLocation];
+        let screens: id = msg_send![class!(NSScreen), screens];
+        let count: usize = msg_send![screens, count];
+
+        let frame = (0..count)
+            .map(|index| -> NSRect {
+                let screen: id = msg_send![screens, objectAtIndex: index];
+                msg_send![screen, frame]
+            })
+            .find(|frame| NSMouseInRect(mouse_location, *frame, NO) == YES)
+            .unwrap_or_else(|| {
+                let screen: id = msg_send![class!(NSScreen), mainScreen];
+                msg_send![screen, frame]
+            });
+
+        NSPoint::new(
+            frame.origin.x + (frame.size.width - size.width) / 2.0,
+            frame.origin.y + frame.size.height / 6.0,
+        )
+    }
+}
+
+/// Shows a companion strip with the time and `status`, if any, in the bottom right corner of
+/// every display, or updates the status of the ones shown.
+///
+/// Unlike the panel, the strips are pinned to the desktop: they sit at the desktop window level
+/// behind every other window, stay put on all Spaces and let clicks through. They follow
+/// displays being connected, disconnected or rearranged until `hide_companion_hud`.
+#[tauri::command]
+pub fn show_companion_hud(app_handle: AppHandle<Wry>, status: Option<String>) {
+    let state = app_handle.state::<State>();
+    let mut companion = state.0.lock().unwrap();
+    companion.status = status;
+
+    if companion.observer.is_none() {
+        companion.observer = Some(observe_screen_changes(&app_handle));
+        companion.timer = Some(schedule_clock(&app_handle));
+        create_strips(&mut companion);
+    }
+
+    update_strips(&companion);
+}
+
+/// Removes the companion strips shown with `show_companion_hud`
+#[tauri::command]
+pub fn hide_companion_hud(app_handle: AppHandle<Wry>) {
+    let state = app_handle.state::<State>();
+    let mut companion = state.0.lock().unwrap();
+
+    unsafe {
+        if let Some(observer) = companion.observer.take() {
+            let center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+            let _: () = msg_send![center, removeObserver: &*observer];
+        }
+
+        if let Some(timer) = companion.timer.take() {
+            let _: () = msg_send![&*timer, invalidate];
+        }
+    }
+
+    close_strips(&mut companion);
+}
+
+/// Takes the companion strips off screen, or puts them back. They keep updating while hidden.
+pub(crate) fn set_companion_hidden(app_handle: &AppHandle<Wry>, hidden: bool) {
+    let state = app_handle.state::<State>();
+    let mut companion = state.0.lock().unwrap();
+    companion.hidden = hidden;
+
+    for strip in &companion.strips {
+        if hidden {
+            let _: () = unsafe { msg_send![&*strip.panel, orderOut: nil] };
+        } else {
+            let _: () = unsafe { msg_send![&*strip.panel, orderFrontRegardless] };
+        }
+    }
+}
+
+/// A companion strip and its label, on the display with the given visible frame
+struct Strip {
+    panel: ShareId<Object>,
+    label: ShareId<Object>,
+    screen_frame: NSRect,
+}
+
+#[derive(Default)]
+struct Companion {
+    strips: Vec<Strip>,
+    status: Option<String>,
+    /// Screen parameters observer and clock timer, set while the strips are shown
+    observer: Option<ShareId<Object>>,
+    timer: Option<ShareId<Object>>,
+    /// Kept off screen during presentation mode
+    hidden: bool,
+}
+
+/// The companion strips currently shown
+#[derive(Default)]
+pub struct State(Mutex<Companion>);
+
+/// One strip per display, resized to fit by `update_strips`
+fn create_strips(companion: &mut Companion) {
+    let level = unsafe { CGWindowLevelForKey(DESKTOP_WINDOW_LEVEL_KEY) } as i64 + 1;
+
+    unsafe {
+        let screens: id = msg_send![class!(NSScreen), screens];
+        let count: usize = msg_send![screens, count];
+
+        for index in 0..count {
+            let screen: id = msg_send![screens, objectAtIndex: index];
+            let screen_frame: NSRect = msg_send![screen, visibleFrame];
+
+            let panel = create_hud_panel(screen_frame, level, STRIP_CORNER_RADIUS);
+            let _: () = msg_send![panel, setReleasedWhenClosed: NO];
+            let _: () = msg_send![
+                panel,
+                setCollectionBehavior: NSWindowCollectionBehavior::NSWindowCollectionBehaviorCanJoinAllSpaces
+                    | NSWindowCollectionBehavior::NSWindowCollectionBehaviorStationary
+                    | NSWindowCollectionBehavior::NSWindowCollectionBehaviorIgnoresCycle
+            ];
+
+            let label = create_label("", STRIP_FONT_SIZE);
+            let font: id = msg_send![
+                class!(NSFont),
+                monospacedDigitSystemFontOfSize: STRIP_FONT_SIZE
+                weight: FONT_WEIGHT_MEDIUM
+            ];
+            let _: () = msg_send![label, setFont: font];
+            let _: () =
+                msg_send![label, setFrameOrigin: NSPoint::new(STRIP_PADDING, STRIP_PADDING)];
+            let content_view: id = msg_send![panel, contentView];
+            let _: () = msg_send![content_view, addSubview: label];
+
+            if !companion.hidden {
+                let _: () = msg_send![panel, orderFrontRegardless];
+            }
+
+            companion.strips.push(Strip {
+                panel: ShareId::from_retained_ptr(panel as *mut Object),
+                label: ShareId::from_ptr(label as *mut Object),
+                screen_frame,
+            });
+        }
+    }
+}
+
+fn close_strips(companion: &mut Companion) {
+    for strip in companion.strips.drain(..) {
+        let _: () = unsafe { msg_send![&*strip.panel, close] };
+    }
+}
+
+/// Sets the time and status of every strip and fits it to its text
+fn update_strips(companion: &Companion) {
+    let text = match &companion.status {
+        Some(status) => format!("{}  ·  {}", clock_text(), status),
+        None => clock_text(),
+    };
+
+    for strip in &companion.strips {
+        unsafe {
+            let string = NSString::alloc(nil).init_str(&text);
+            let string: id = msg_send![string, autorelease];
+            let _: () = msg_send![&*strip.label, setStringValue: string];
+
+            let label_size: NSSize = msg_send![&*strip.label, fittingSize];
+            let size = NSSize::new(
+                label_size.width + STRIP_PADDING * 2.0,
+                label_size.height + STRIP_PADDING * 2.0,
+            );
+            let origin = NSPoint::new(
+                strip.screen_frame.origin.x + strip.screen_frame.size.width
+                    - size.width
+                    - STRIP_MARGIN,
+                strip.screen_frame.origin.y + STRIP_MARGIN,
+            );
+
+            let _: () = msg_send![&*strip.panel, setFrame: NSRect::new(origin, size) display: YES];
+        }
+    }
+}
+
+/// The weekday and time, in the user's locale and 12 or 24-hour preference
+fn clock_text() -> String {
+    let text: id = unsafe {
+        let formatter: id = msg_send![class!(NSDateFormatter), new];
+        let template = NSString::alloc(nil).init_str(CLOCK_FORMAT_TEMPLATE);
+        let template: id = msg_send![template, autorelease];
+        let _: () = msg_send![formatter, setLocalizedDateFormatFromTemplate: template];
+
+        let now: id = msg_send![class!(NSDate), date];
+        let text: id = msg_send![formatter, stringFromDate: now];
+        let _: () = msg_send![formatter, release];
+        text
+    };
+
+    nsstring_to_string!(text).unwrap_or_default()
+}
+
+fn schedule_clock(app_handle: &AppHandle<Wry>) -> ShareId<Object> {
+    let handle = app_handle.clone();
+    let tick = ConcreteBlock::new(move |_timer: id| {
+        update_strips(&handle.state::<State>().0.lock().unwrap());
+    })
+    .copy();
+
+    unsafe {
+        let timer: id = msg_send![
+            class!(NSTimer),
+            scheduledTimerWithTimeInterval: CLOCK_UPDATE_INTERVAL.as_secs_f64()
+            repeats: YES
+            block: &*tick
+        ];
+        ShareId::from_ptr(timer as *mut Object)
+    }
+}
+
+/// Recreates the strips when displays are connected, disconnected, resized or rearranged
+fn observe_screen_changes(app_handle: &AppHandle<Wry>) -> ShareId<Object> {
+    let handle = app_handle.clone();
+    let on_change = ConcreteBlock::new(move |_notification: id| {
+        let state = handle.state::<State>();
+        let mut companion = state.0.lock().unwrap();
+
+        close_strips(&mut companion);
+        create_strips(&mut companion);
+        update_strips(&companion);
+    })
+    .copy();
+
+    unsafe {
+        let center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+        let queue: id = msg_send![class!(NSOperationQueue), mainQueue];
+        let name = NSString::alloc(nil).init_str(SCREEN_PARAMETERS_CHANGED_NOTIFICATION);
+        let name: id = msg_send![name, autorelease];
+
+        let observer: id = msg_send![
+            center,
+            addObserverForName: name
+            object: nil
+            queue: queue
+            usingBlock: &*on_change
+        ];
+        ShareId::from_ptr(observer as *mut Object)
+    }
+}
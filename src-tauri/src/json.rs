@@ -0,0 +1,55 @@
+use cocoa::base::{id, nil};
+use objc::{class, msg_send, sel, sel_impl};
+use serde_json::Value;
+
+/// Converts a JSON value to its Foundation counterpart (NSDictionary, NSArray, NSString, ...).
+///
+/// The returned object is autoreleased; `null` is returned as `nil`.
+pub fn to_ns_object(value: &Value) -> id {
+    let json = match serde_json::to_vec(value) {
+        Ok(json) => json,
+        Err(_) => return nil,
+    };
+
+    unsafe {
+        let data: id = msg_send![
+            class!(NSData),
+            dataWithBytes: json.as_ptr()
+            length: json.len()
+        ];
+
+        // NSJSONReadingFragmentsAllowed, so top level strings and numbers work too
+        msg_send![
+            class!(NSJSONSerialization),
+            JSONObjectWithData: data
+            options: 4u64
+            error: nil
+        ]
+    }
+}
+
+/// Converts a Foundation object made of JSON compatible types back to a JSON value
+pub fn from_ns_object(object: id) -> Option<Value> {
+    if object == nil {
+        return None;
+    }
+
+    unsafe {
+        // NSJSONWritingFragmentsAllowed
+        let data: id = msg_send![
+            class!(NSJSONSerialization),
+            dataWithJSONObject: object
+            options: 4u64
+            error: nil
+        ];
+
+        if data == nil {
+            return None;
+        }
+
+        let bytes: *const u8 = msg_send![data, bytes];
+        let length: usize = msg_send![data, length];
+
+        serde_json::from_slice(std::slice::from_raw_parts(bytes, length)).ok()
+    }
+}
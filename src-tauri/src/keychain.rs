@@ -0,0 +1,46 @@
+use security_framework::{
+    base::Error,
+    passwords::{delete_generic_password, get_generic_password, set_generic_password},
+};
+use tauri::{AppHandle, Wry};
+
+/// errSecItemNotFound
+const ERR_SEC_ITEM_NOT_FOUND: i32 = -25300;
+
+/// Secrets are stored as generic passwords under the app's bundle identifier
+fn service(app_handle: &AppHandle<Wry>) -> String {
+    app_handle.config().tauri.bundle.identifier.clone()
+}
+
+fn to_message(error: Error) -> String {
+    error
+        .message()
+        .unwrap_or_else(|| format!("Keychain error {}", error.code()))
+}
+
+/// Stores a secret in the login Keychain, replacing any previous value
+#[tauri::command]
+pub fn secret_set(app_handle: AppHandle<Wry>, key: String, value: String) -> Result<(), String> {
+    set_generic_password(&service(&app_handle), &key, value.as_bytes()).map_err(to_message)
+}
+
+/// Reads a secret from the login Keychain, `null` if there's none
+#[tauri::command]
+pub fn secret_get(app_handle: AppHandle<Wry>, key: String) -> Result<Option<String>, String> {
+    match get_generic_password(&service(&app_handle), &key) {
+        Ok(value) => String::from_utf8(value)
+            .map(Some)
+            .map_err(|e| e.to_string()),
+        Err(error) if error.code() == ERR_SEC_ITEM_NOT_FOUND => Ok(None),
+        Err(error) => Err(to_message(error)),
+    }
+}
+
+/// Removes a secret from the login Keychain, succeeds if there was none
+#[tauri::command]
+pub fn secret_delete(app_handle: AppHandle<Wry>, key: String) -> Result<(), String> {
+    match delete_generic_password(&service(&app_handle), &key) {
+        Err(error) if error.code() != ERR_SEC_ITEM_NOT_FOUND => Err(to_message(error)),
+        _ => Ok(()),
+    }
+}
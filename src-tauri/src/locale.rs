@@ -0,0 +1,181 @@
+use std::sync::Mutex;
+
+use cocoa::base::id;
+use objc::{class, msg_send, sel, sel_impl};
+
+use crate::nsstring_to_string;
+
+/// Language set with `set_locale`, `None` follows the system's preferred language
+static LANGUAGE_OVERRIDE: Mutex<Option<Language>> = Mutex::new(None);
+
+#[derive(Clone, Copy)]
+enum Language {
+    English,
+    French,
+    German,
+    Spanish,
+}
+
+impl Language {
+    /// Matches the language part of a locale identifier such as `fr-CA` or `de_DE`
+    fn from_identifier(identifier: &str) -> Option<Self> {
+        let language = identifier.split(['-', '_']).next()?;
+
+        match language.to_ascii_lowercase().as_str() {
+            "en" => Some(Language::English),
+            "fr" => Some(Language::French),
+            "de" => Some(Language::German),
+            "es" => Some(Language::Spanish),
+            _ => None,
+        }
+    }
+
+    fn current() -> Self {
+        LANGUAGE_OVERRIDE
+            .lock()
+            .unwrap()
+            .or_else(system_language)
+            .unwrap_or(Language::English)
+    }
+}
+
+/// User-visible strings produced on the native side.
+///
+/// Errors meant for developers (invalid ids, unknown bindings, ...) are left in English.
+#[derive(Clone, Copy)]
+pub enum Text {
+    OnboardingTitle,
+    MicrophoneNotGranted,
+    MicrophoneDenied,
+    SpeechRecognitionDenied,
+    SpeechRecognitionUnsupportedLocale,
+    SpeechRecognitionUnavailable,
+    DictationRunning,
+    AudioInputFailed,
+    AuthenticationInterrupted,
+    ScreenCaptureFailed,
+    UnsupportedSettingsVersion,
+    TimerFinished,
+    CalendarNotGranted,
+    CalendarDenied,
+    ContactsNotGranted,
+    ContactsDenied,
+    ScreenRecordingNotGranted,
+}
+
+/// Returns a string in the current language
+pub fn t(text: Text) -> &'static str {
+    match Language::current() {
+        Language::English => english(text),
+        Language::French => french(text),
+        Language::German => german(text),
+        Language::Spanish => spanish(text),
+    }
+}
+
+/// Overrides the language of native strings with a locale identifier such as `fr-FR`, or
+/// follows the system again with `null`. Unsupported languages fall back to English.
+#[tauri::command]
+pub fn set_locale(locale: Option<String>) {
+    *LANGUAGE_OVERRIDE.lock().unwrap() = match locale {
+        Some(locale) => Some(Language::from_identifier(&locale).unwrap_or(Language::English)),
+        None => None,
+    };
+}
+
+fn system_language() -> Option<Language> {
+    let language: id = unsafe {
+        let languages: id = msg_send![class!(NSLocale), preferredLanguages];
+        msg_send![languages, firstObject]
+    };
+
+    nsstring_to_string!(language).and_then(|language| Language::from_identifier(&language))
+}
+
+fn english(text: Text) -> &'static str {
+    match text {
+        Text::OnboardingTitle => "Welcome",
+        Text::MicrophoneNotGranted => "Microphone access was not granted",
+        Text::MicrophoneDenied => "Microphone access is denied, enable it in System Settings > Privacy & Security > Microphone",
+        Text::SpeechRecognitionDenied => "Speech recognition is not allowed, enable it in System Settings > Privacy & Security > Speech Recognition",
+        Text::SpeechRecognitionUnsupportedLocale => "Speech recognition is not supported for this locale",
+        Text::SpeechRecognitionUnavailable => "Speech recognition is currently unavailable",
+        Text::DictationRunning => "Dictation is already running",
+        Text::AudioInputFailed => "Unable to start audio input",
+        Text::AuthenticationInterrupted => "Authentication was interrupted",
+        Text::ScreenCaptureFailed => "Unable to capture the screen",
+        Text::UnsupportedSettingsVersion => "These settings were exported by a newer version of the app",
+        Text::TimerFinished => "Timer finished",
+        Text::CalendarNotGranted => "Calendar access was not granted",
+        Text::CalendarDenied => "Calendar access is denied, enable it in System Settings > Privacy & Security > Calendars",
+        Text::ContactsNotGranted => "Contacts access was not granted",
+        Text::ContactsDenied => "Contacts access is denied, enable it in System Settings > Privacy & Security > Contacts",
+        Text::ScreenRecordingNotGranted => "Screen Recording access is needed, enable it in System Settings > Privacy & Security > Screen & System Audio Recording",
+    }
+}
+
+fn french(text: Text) -> &'static str {
+    match text {
+        Text::OnboardingTitle => "Bienvenue",
+        Text::MicrophoneNotGranted => "L'accès au micro n'a pas été accordé",
+        Text::MicrophoneDenied => "L'accès au micro est refusé, activez-le dans Réglages Système > Confidentialité et sécurité > Micro",
+        Text::SpeechRecognitionDenied => "La reconnaissance vocale n'est pas autorisée, activez-la dans Réglages Système > Confidentialité et sécurité > Reconnaissance vocale",
+        Text::SpeechRecognitionUnsupportedLocale => "La reconnaissance vocale n'est pas disponible pour cette langue",
+        Text::SpeechRecognitionUnavailable => "La reconnaissance vocale est momentanément indisponible",
+        Text::DictationRunning => "La dictée est déjà en cours",
+        Text::AudioInputFailed => "Impossible de démarrer l'entrée audio",
+        Text::AuthenticationInterrupted => "L'authentification a été interrompue",
+        Text::ScreenCaptureFailed => "Impossible de capturer l'écran",
+        Text::UnsupportedSettingsVersion => "Ces réglages ont été exportés par une version plus récente de l'app",
+        Text::TimerFinished => "Minuteur terminé",
+        Text::CalendarNotGranted => "L'accès aux calendriers n'a pas été accordé",
+        Text::CalendarDenied => "L'accès aux calendriers est refusé, activez-le dans Réglages Système > Confidentialité et sécurité > Calendriers",
+        Text::ContactsNotGranted => "L'accès aux contacts n'a pas été accordé",
+        Text::ContactsDenied => "L'accès aux contacts est refusé, activez-le dans Réglages Système > Confidentialité et sécurité > Contacts",
+        Text::ScreenRecordingNotGranted => "L'accès à l'enregistrement de l'écran est nécessaire, activez-le dans Réglages Système > Confidentialité et sécurité > Enregistrement de l'écran et de l'audio système",
+    }
+}
+
+fn german(text: Text) -> &'static str {
+    match text {
+        Text::OnboardingTitle => "Willkommen",
+        Text::MicrophoneNotGranted => "Der Zugriff auf das Mikrofon wurde nicht erlaubt",
+        Text::MicrophoneDenied => "Der Zugriff auf das Mikrofon ist verweigert, aktiviere ihn unter Systemeinstellungen > Datenschutz & Sicherheit > Mikrofon",
+        Text::SpeechRecognitionDenied => "Die Spracherkennung ist nicht erlaubt, aktiviere sie unter Systemeinstellungen > Datenschutz & Sicherheit > Spracherkennung",
+        Text::SpeechRecognitionUnsupportedLocale => "Die Spracherkennung wird für diese Sprache nicht unterstützt",
+        Text::SpeechRecognitionUnavailable => "Die Spracherkennung ist derzeit nicht verfügbar",
+        Text::DictationRunning => "Das Diktat läuft bereits",
+        Text::AudioInputFailed => "Die Audioeingabe konnte nicht gestartet werden",
+        Text::AuthenticationInterrupted => "Die Authentifizierung wurde unterbrochen",
+        Text::ScreenCaptureFailed => "Der Bildschirm konnte nicht aufgenommen werden",
+        Text::UnsupportedSettingsVersion => "Diese Einstellungen wurden von einer neueren Version der App exportiert",
+        Text::TimerFinished => "Timer abgelaufen",
+        Text::CalendarNotGranted => "Der Zugriff auf Kalender wurde nicht erlaubt",
+        Text::CalendarDenied => "Der Zugriff auf Kalender ist verweigert, aktiviere ihn unter Systemeinstellungen > Datenschutz & Sicherheit > Kalender",
+        Text::ContactsNotGranted => "Der Zugriff auf Kontakte wurde nicht erlaubt",
+        Text::ContactsDenied => "Der Zugriff auf Kontakte ist verweigert, aktiviere ihn unter Systemeinstellungen > Datenschutz & Sicherheit > Kontakte",
+        Text::ScreenRecordingNotGranted => "Der Zugriff auf die Bildschirmaufnahme wird benötigt, aktiviere ihn unter Systemeinstellungen > Datenschutz & Sicherheit > Bildschirm- & Systemaudioaufnahme",
+    }
+}
+
+fn spanish(text: Text) -> &'static str {
+    match text {
+        Text::OnboardingTitle => "Bienvenido",
+        Text::MicrophoneNotGranted => "No se concedió el acceso al micrófono",
+        Text::MicrophoneDenied => "El acceso al micrófono está denegado, actívalo en Ajustes del Sistema > Privacidad y seguridad > Micrófono",
+        Text::SpeechRecognitionDenied => "El reconocimiento de voz no está permitido, actívalo en Ajustes del Sistema > Privacidad y seguridad > Reconocimiento de voz",
+        Text::SpeechRecognitionUnsupportedLocale => "El reconocimiento de voz no es compatible con este idioma",
+        Text::SpeechRecognitionUnavailable => "El reconocimiento de voz no está disponible en este momento",
+        Text::DictationRunning => "El dictado ya está en curso",
+        Text::AudioInputFailed => "No se pudo iniciar la entrada de audio",
+        Text::AuthenticationInterrupted => "Se interrumpió la autenticación",
+        Text::ScreenCaptureFailed => "No se pudo capturar la pantalla",
+        Text::UnsupportedSettingsVersion => "Estos ajustes se exportaron con una versión más reciente de la app",
+        Text::TimerFinished => "Temporizador finalizado",
+        Text::CalendarNotGranted => "No se concedió el acceso a los calendarios",
+        Text::CalendarDenied => "El acceso a los calendarios está denegado, actívalo en Ajustes del Sistema > Privacidad y seguridad > Calendarios",
+        Text::ContactsNotGranted => "No se concedió el acceso a los contactos",
+        Text::ContactsDenied => "El acceso a los contactos está denegado, actívalo en Ajustes del Sistema > Privacidad y seguridad > Contactos",
+        Text::ScreenRecordingNotGranted => "Se necesita acceso a la grabación de pantalla, actívalo en Ajustes del Sistema > Privacidad y seguridad > Grabación de pantalla y audio del sistema",
+    }
+}
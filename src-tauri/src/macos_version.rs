@@ -0,0 +1,117 @@
+use std::sync::Mutex;
+
+use cocoa::base::id;
+use objc::{class, msg_send, sel, sel_impl};
+use serde::Serialize;
+use tauri::{AppHandle, Wry};
+
+use crate::diagnostics;
+
+/// Newest major version the app was tested on
+const LATEST_TESTED_MAJOR: i64 = 15;
+
+const SONOMA_MAJOR: i64 = 14;
+const SEQUOIA_MAJOR: i64 = 15;
+
+/// NSOperatingSystemVersion
+#[repr(C)]
+struct OperatingSystemVersion {
+    major: i64,
+    minor: i64,
+    patch: i64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct MacOSVersion {
+    pub major: i64,
+    pub minor: i64,
+    pub patch: i64,
+}
+
+/// Read once, the version can't change while the app runs
+static VERSION: Mutex<Option<MacOSVersion>> = Mutex::new(None);
+
+impl MacOSVersion {
+    pub(crate) fn current() -> Self {
+        *VERSION.lock().unwrap().get_or_insert_with(|| {
+            let version: OperatingSystemVersion = unsafe {
+                let process_info: id = msg_send![class!(NSProcessInfo), processInfo];
+                msg_send![process_info, operatingSystemVersion]
+            };
+
+            Self {
+                major: version.major,
+                minor: version.minor,
+                patch: version.patch,
+            }
+        })
+    }
+
+    fn is_tested(&self) -> bool {
+        self.major <= LATEST_TESTED_MAJOR
+    }
+}
+
+/// Workarounds for behaviour that changed in a given macOS version, and whether they're on
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Workarounds {
+    /// Since Sonoma, showing the non-activating panel from the shortcut can switch to the Space
+    /// it was last shown on instead of moving it to the active one. The panel joins all Spaces
+    /// instead.
+    pub sonoma_spaces: bool,
+    /// Since Sequoia, every capture without the Screen Recording permission brings up the system
+    /// prompt again. Captures check the permission first and fail instead.
+    pub sequoia_capture_prompts: bool,
+}
+
+impl Workarounds {
+    pub(crate) fn current() -> Self {
+        let version = MacOSVersion::current();
+
+        Self {
+            sonoma_spaces: version.major >= SONOMA_MAJOR,
+            sequoia_capture_prompts: version.major >= SEQUOIA_MAJOR,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacOSVersionInfo {
+    version: MacOSVersion,
+    /// Whether the app was tested on this major version
+    tested: bool,
+    workarounds: Workarounds,
+}
+
+/// Returns the running macOS version along with the version-specific workarounds in effect
+#[tauri::command]
+pub fn get_macos_version() -> MacOSVersionInfo {
+    let version = MacOSVersion::current();
+
+    MacOSVersionInfo {
+        version,
+        tested: version.is_tested(),
+        workarounds: Workarounds::current(),
+    }
+}
+
+/// Warns the frontend when running on a major version newer than the ones the app was tested
+/// on, as panels, Spaces and permissions tend to change between them
+pub(crate) fn warn_if_untested(app_handle: &AppHandle<Wry>) {
+    let version = MacOSVersion::current();
+    if version.is_tested() {
+        return;
+    }
+
+    diagnostics::warn(
+        app_handle,
+        "untestedMacOSVersion",
+        format!(
+            "Running on macOS {}.{}.{}, which this app wasn't tested on. The panel may not \
+             behave as expected.",
+            version.major, version.minor, version.patch
+        ),
+    );
+}
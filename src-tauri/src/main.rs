@@ -3,21 +3,266 @@
     windows_subsystem = "windows"
 )]
 
+mod accessibility;
+mod appearance;
+mod appearance_schedule;
+mod architecture;
+mod audio;
+mod auth;
+mod calendar;
+mod configure;
+mod contacts;
+mod core_spotlight;
+mod diagnostics;
+mod dispatch;
+mod drop_targets;
+mod events;
+mod extensions;
+mod feature_flags;
+mod files;
+mod find;
+mod focus;
+mod fs_events;
+mod gestures;
+mod handoff;
+mod hud;
+mod json;
+mod keychain;
+mod locale;
+mod macos_version;
+mod memory;
+mod navigation;
+mod network;
+mod onboarding;
+mod overlay;
+mod paths;
+mod permissions;
+mod presentation;
+mod presentation_mode;
+mod print;
+mod privacy;
+mod process;
+mod screen;
+mod screen_capture;
+mod session;
+mod settings;
+mod share;
+mod shortcut_help;
+mod speech;
 mod spotlight;
+mod system_info;
+mod system_settings;
+mod tabs;
+mod text_input;
+mod timers;
+mod touch_bar;
+mod trace;
+mod updater;
+mod user_activity;
+mod webview;
+mod windows;
+mod wireless;
 
 fn main() {
     tauri::Builder::default()
         .invoke_handler(tauri::generate_handler![
             spotlight::init_spotlight_window,
             spotlight::show_spotlight,
-            spotlight::hide_spotlight
+            spotlight::hide_spotlight,
+            spotlight::get_native_handles,
+            spotlight::set_activation_delay,
+            configure::configure,
+            configure::rebuild_panel,
+            overlay::add_native_overlay,
+            overlay::remove_native_overlay,
+            text_input::set_text_input_rect,
+            text_input::set_press_and_hold_enabled,
+            text_input::start_system_dictation,
+            text_input::show_character_palette,
+            webview::set_text_checking,
+            webview::get_text_checking,
+            webview::reset_webview_data,
+            navigation::set_navigation_bindings,
+            gestures::set_gesture_events_enabled,
+            touch_bar::set_touch_bar_items,
+            user_activity::set_user_activity,
+            core_spotlight::index_items,
+            core_spotlight::remove_items,
+            files::list_directory,
+            files::search_in_folder,
+            files::cancel_folder_search,
+            files::get_file_info,
+            files::trash_files,
+            files::duplicate_file,
+            files::get_recent_documents,
+            audio::start_audio_level_meter,
+            audio::stop_audio_level_meter,
+            speech::start_dictation,
+            speech::stop_dictation,
+            speech::speak,
+            speech::stop_speaking,
+            screen::recognize_text_behind,
+            screen::pick_color,
+            appearance::get_system_appearance,
+            appearance_schedule::set_appearance_schedule,
+            network::get_network_status,
+            keychain::secret_set,
+            keychain::secret_get,
+            keychain::secret_delete,
+            auth::authenticate,
+            settings::get_settings,
+            settings::set_settings,
+            settings::export_settings,
+            settings::import_settings,
+            locale::set_locale,
+            timers::start_timer,
+            timers::list_timers,
+            timers::cancel_timer,
+            calendar::get_upcoming_events,
+            contacts::search_contacts,
+            system_info::get_system_snapshot,
+            wireless::get_wireless_status,
+            wireless::set_wifi_enabled,
+            wireless::set_bluetooth_enabled,
+            system_settings::get_system_toggles,
+            system_settings::set_system_dark_mode,
+            system_settings::set_night_shift,
+            system_settings::set_true_tone,
+            windows::list_windows_for_app,
+            windows::snap_window_behind,
+            drop_targets::set_drop_targets,
+            share::share_items,
+            print::print_panel,
+            print::export_panel_pdf,
+            find::set_find_bar_visible,
+            find::find_in_panel,
+            webview::set_webview_zoom,
+            webview::get_webview_zoom,
+            screen_capture::set_screen_capture_behaviour,
+            screen_capture::is_screen_captured,
+            events::subscribe,
+            events::unsubscribe,
+            macos_version::get_macos_version,
+            architecture::get_architecture,
+            hud::show_companion_hud,
+            hud::hide_companion_hud,
+            focus::set_focus_restore_target,
+            presentation_mode::enter_presentation_mode,
+            presentation_mode::exit_presentation_mode,
+            privacy::set_privacy_mode,
+            privacy::get_privacy_mode,
+            trace::get_native_trace,
+            feature_flags::list_feature_flags,
+            feature_flags::set_feature_flag,
+            tabs::add_tab,
+            tabs::switch_tab,
+            tabs::remove_tab,
+            tabs::preload_mode,
+            session::save_session,
+            session::load_session,
+            updater::check_for_update,
+            updater::install_update,
+            process::run_command,
+            process::cancel_command,
+            extensions::list_extensions,
+            extensions::run_extension,
+            fs_events::watch_path,
+            fs_events::unwatch,
+            shortcut_help::set_shortcut_help,
+            permissions::get_permissions,
+            permissions::request_permission,
+            onboarding::show_onboarding,
+            onboarding::finish_onboarding,
+            paths::get_app_dirs
         ])
         .manage(spotlight::State::default())
+        .manage(overlay::State::default())
+        .manage(navigation::State::default())
+        .manage(gestures::State::default())
+        .manage(touch_bar::State::default())
+        .manage(user_activity::State::default())
+        .manage(audio::State::default())
+        .manage(speech::State::default())
+        .manage(network::State::default())
+        .manage(settings::State::default())
+        .manage(process::State::default())
+        .manage(fs_events::State::default())
+        .manage(shortcut_help::State::default())
+        .manage(focus::State::default())
+        .manage(paths::State::default())
+        .manage(appearance_schedule::State::default())
+        .manage(timers::State::default())
+        .manage(system_info::State::default())
+        .manage(drop_targets::State::default())
+        .manage(find::State::default())
+        .manage(screen_capture::State::default())
+        .manage(events::State::default())
+        .manage(configure::State::default())
+        .manage(tabs::State::default())
+        .manage(updater::State::default())
+        .manage(hud::State::default())
+        .manage(presentation_mode::State::default())
+        .manage(privacy::State::default())
+        // Start every page without event subscriptions and with the zoom remembered for its window
+        .on_page_load(|window, _| {
+            events::reset_subscriptions(&window);
+            webview::restore_webview_zoom(&window);
+        })
         .setup(move |app| {
             // Set activation poicy to Accessory to prevent the app icon from showing on the dock
             app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+
+            // Honor --config-dir and portable mode before anything touches the disk
+            paths::resolve_app_dirs(&app.handle());
+
+            settings::load_settings(&app.handle());
+
+            // Take the shortcut over from the instance an update is replacing, if it's still up
+            handoff::take_over_shortcut(app.handle());
+
+            // Walk first-time users through granting permissions
+            onboarding::show_onboarding_if_needed(&app.handle());
+
+            // Let native subsystems and the frontend shed caches under memory pressure
+            memory::watch_memory_pressure(app.handle());
+
+            // Forward emacs-style navigation keys while the panel is visible
+            navigation::install_navigation_monitor(app.handle());
+
+            // Forward native scroll phases and gestures when the frontend asks for them
+            gestures::install_gesture_monitor(app.handle());
+
+            // Restore the panel when a Handoff activity is continued
+            user_activity::install_continuation_handler(app.handle());
+
+            // Let the frontend follow theme, accent and highlight color changes
+            appearance::watch_appearance(app.handle());
+
+            // Switch the panel between light and dark on the user's schedule, if any
+            appearance_schedule::watch_appearance_schedule(app.handle());
+
+            // Accept drops on the status item or screen edge, if enabled
+            drop_targets::install_drop_targets(&app.handle());
+
+            // Keep the panel where the user wants it while the screen is recorded or shared
+            screen_capture::watch_screen_capture(app.handle());
+
+            // Keep track of connectivity for online-dependent result sources
+            network::watch_network(app.handle());
+
+            // Don't try to hand focus back to loginwindow, and re-arm the shortcut on unlock
+            focus::watch_screen_lock(app.handle());
+
+            // Check for updates in the background, announcing them without a dialog
+            updater::watch_for_updates(app.handle());
+
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                spotlight::prepare_for_exit(app_handle);
+            }
+        });
 }
@@ -0,0 +1,100 @@
+use std::ffi::c_void;
+
+use serde::Serialize;
+use tauri::{AppHandle, Wry};
+
+use crate::{
+    dispatch::{self, DispatchObject},
+    events,
+};
+
+/// Emitted on memory pressure changes. Subsystems holding caches listen to it to evict them, and
+/// the frontend can do the same.
+pub const MEMORY_PRESSURE_EVENT: &str = "spotlight://memory-pressure";
+
+const DISPATCH_MEMORYPRESSURE_NORMAL: usize = 0x01;
+const DISPATCH_MEMORYPRESSURE_WARN: usize = 0x02;
+const DISPATCH_MEMORYPRESSURE_CRITICAL: usize = 0x04;
+
+extern "C" {
+    static _dispatch_source_type_memorypressure: DispatchObject;
+
+    fn dispatch_source_create(
+        kind: *const DispatchObject,
+        handle: usize,
+        mask: usize,
+        queue: *const DispatchObject,
+    ) -> *mut DispatchObject;
+    fn dispatch_source_get_data(source: *mut DispatchObject) -> usize;
+    fn dispatch_set_context(object: *mut DispatchObject, context: *mut c_void);
+    fn dispatch_source_set_event_handler_f(
+        source: *mut DispatchObject,
+        handler: extern "C" fn(*mut c_void),
+    );
+    fn dispatch_resume(object: *mut DispatchObject);
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MemoryPressureLevel {
+    Normal,
+    Warning,
+    Critical,
+}
+
+#[derive(Clone, Serialize)]
+struct MemoryPressurePayload {
+    level: MemoryPressureLevel,
+}
+
+struct MemoryPressureContext {
+    app_handle: AppHandle<Wry>,
+    source: *mut DispatchObject,
+}
+
+/// Listens for memory pressure notifications for the lifetime of the app.
+///
+/// Handled on the main queue; every level change is emitted as `spotlight://memory-pressure`.
+pub fn watch_memory_pressure(app_handle: AppHandle<Wry>) {
+    unsafe {
+        let source = dispatch_source_create(
+            &_dispatch_source_type_memorypressure,
+            0,
+            DISPATCH_MEMORYPRESSURE_NORMAL
+                | DISPATCH_MEMORYPRESSURE_WARN
+                | DISPATCH_MEMORYPRESSURE_CRITICAL,
+            dispatch::main_queue(),
+        );
+
+        // The source and its context are never released, they live as long as the app does
+        let context = Box::into_raw(Box::new(MemoryPressureContext { app_handle, source }));
+
+        dispatch_set_context(source, context as *mut c_void);
+        dispatch_source_set_event_handler_f(source, handle_memory_pressure);
+        dispatch_resume(source);
+    }
+}
+
+extern "C" fn handle_memory_pressure(context: *mut c_void) {
+    let context = unsafe { &*(context as *const MemoryPressureContext) };
+    let data = unsafe { dispatch_source_get_data(context.source) };
+
+    let level = if data & DISPATCH_MEMORYPRESSURE_CRITICAL != 0 {
+        MemoryPressureLevel::Critical
+    } else if data & DISPATCH_MEMORYPRESSURE_WARN != 0 {
+        MemoryPressureLevel::Warning
+    } else {
+        MemoryPressureLevel::Normal
+    };
+
+    if !matches!(level, MemoryPressureLevel::Normal) {
+        crate::webview::purge_memory_cache(&context.app_handle);
+        crate::tabs::evict_warm_tabs(&context.app_handle, level);
+    }
+
+    let _ = events::emit(
+        &context.app_handle,
+        MEMORY_PRESSURE_EVENT,
+        MemoryPressurePayload { level },
+    );
+}
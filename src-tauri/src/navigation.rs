@@ -0,0 +1,139 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use block::ConcreteBlock;
+use cocoa::{
+    appkit::NSEventModifierFlags,
+    base::{id, nil},
+};
+use objc::{class, msg_send, sel, sel_impl};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{events, get_state, nsstring_to_string};
+
+const NAVIGATE_EVENT: &str = "spotlight://navigate";
+
+/// NSEventMaskKeyDown
+const KEY_DOWN_MASK: u64 = 1 << 10;
+
+/// A key with the exact set of modifiers that must be held, e.g. `Ctrl+N`
+#[derive(PartialEq, Eq, Hash)]
+struct Binding {
+    modifiers: NSEventModifierFlags,
+    key: String,
+}
+
+impl Binding {
+    fn parse(accelerator: &str) -> Result<Self, String> {
+        let mut modifiers = NSEventModifierFlags::empty();
+        let mut tokens: Vec<&str> = accelerator.split('+').map(str::trim).collect();
+        let key = tokens
+            .pop()
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| format!("Invalid binding \"{}\"", accelerator))?;
+
+        for token in tokens {
+            modifiers |= match token.to_lowercase().as_str() {
+                "cmd" | "command" => NSEventModifierFlags::NSCommandKeyMask,
+                "ctrl" | "control" => NSEventModifierFlags::NSControlKeyMask,
+                "alt" | "option" => NSEventModifierFlags::NSAlternateKeyMask,
+                "shift" => NSEventModifierFlags::NSShiftKeyMask,
+                _ => {
+                    return Err(format!(
+                        "Unknown modifier \"{}\" in \"{}\"",
+                        token, accelerator
+                    ))
+                }
+            };
+        }
+
+        Ok(Self {
+            modifiers,
+            key: key.to_lowercase(),
+        })
+    }
+
+    fn from_event(event: id) -> Option<Self> {
+        let flags: NSEventModifierFlags = unsafe { msg_send![event, modifierFlags] };
+        let characters: id = unsafe { msg_send![event, charactersIgnoringModifiers] };
+
+        Some(Self {
+            modifiers: flags
+                & (NSEventModifierFlags::NSCommandKeyMask
+                    | NSEventModifierFlags::NSControlKeyMask
+                    | NSEventModifierFlags::NSAlternateKeyMask
+                    | NSEventModifierFlags::NSShiftKeyMask),
+            key: nsstring_to_string!(characters)?.to_lowercase(),
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct State(Mutex<HashMap<Binding, String>>);
+
+#[derive(Clone, Serialize)]
+struct NavigatePayload {
+    action: String,
+}
+
+/// Sets key bindings that are only active while the panel is visible, e.g.
+/// `{ "Ctrl+N": "down", "Ctrl+P": "up" }`.
+///
+/// Matching key presses are swallowed and emitted as `spotlight://navigate` with the bound
+/// action, regardless of where focus is inside the webview. Pass `{}` to remove all bindings.
+#[tauri::command]
+pub fn set_navigation_bindings(
+    app_handle: AppHandle<Wry>,
+    bindings: HashMap<String, String>,
+) -> Result<(), String> {
+    let bindings = bindings
+        .into_iter()
+        .map(|(accelerator, action)| Ok((Binding::parse(&accelerator)?, action)))
+        .collect::<Result<HashMap<_, _>, String>>()?;
+
+    *app_handle.state::<State>().0.lock().unwrap() = bindings;
+
+    Ok(())
+}
+
+/// Installs a local key down monitor that turns bound key presses into navigation events.
+///
+/// Must be called on the main thread. The monitor lives as long as the app does.
+pub fn install_navigation_monitor(app_handle: AppHandle<Wry>) {
+    let handler = ConcreteBlock::new(move |event: id| -> id {
+        let visible = get_state!(app_handle, panel, clone)
+            .map(|panel| panel.is_visible())
+            .unwrap_or(false);
+
+        if !visible {
+            return event;
+        }
+
+        let action = Binding::from_event(event).and_then(|binding| {
+            app_handle
+                .state::<State>()
+                .0
+                .lock()
+                .unwrap()
+                .get(&binding)
+                .cloned()
+        });
+
+        match action {
+            Some(action) => {
+                let _ = events::emit(&app_handle, NAVIGATE_EVENT, NavigatePayload { action });
+                nil
+            }
+            None => event,
+        }
+    })
+    .copy();
+
+    let _: id = unsafe {
+        msg_send![
+            class!(NSEvent),
+            addLocalMonitorForEventsMatchingMask: KEY_DOWN_MASK
+            handler: &*handler
+        ]
+    };
+}
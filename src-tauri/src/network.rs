@@ -0,0 +1,127 @@
+use std::{ffi::c_void, sync::Mutex};
+
+use block::ConcreteBlock;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{
+    dispatch::{self, DispatchObject},
+    events,
+};
+
+const NETWORK_STATUS_CHANGED_EVENT: &str = "spotlight://network-status-changed";
+
+/// nw_path_status_t
+const NW_PATH_STATUS_SATISFIED: i32 = 1;
+const NW_PATH_STATUS_SATISFIABLE: i32 = 3;
+
+/// nw_interface_type_t
+const NW_INTERFACE_TYPE_WIFI: i32 = 1;
+const NW_INTERFACE_TYPE_CELLULAR: i32 = 2;
+const NW_INTERFACE_TYPE_WIRED: i32 = 3;
+
+#[link(name = "Network", kind = "framework")]
+extern "C" {
+    fn nw_path_monitor_create() -> *mut c_void;
+    fn nw_path_monitor_set_queue(monitor: *mut c_void, queue: *const DispatchObject);
+    fn nw_path_monitor_set_update_handler(
+        monitor: *mut c_void,
+        handler: &block::Block<(*mut c_void,), ()>,
+    );
+    fn nw_path_monitor_start(monitor: *mut c_void);
+    fn nw_path_get_status(path: *mut c_void) -> i32;
+    fn nw_path_uses_interface_type(path: *mut c_void, interface_type: i32) -> bool;
+    fn nw_path_is_expensive(path: *mut c_void) -> bool;
+    fn nw_path_is_constrained(path: *mut c_void) -> bool;
+}
+
+#[derive(Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Connectivity {
+    /// No update was received yet
+    #[default]
+    Unknown,
+    Online,
+    Offline,
+    /// Offline, but a connection can be established on demand (e.g. VPN on demand)
+    Satisfiable,
+}
+
+#[derive(Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Interface {
+    #[default]
+    Other,
+    Wifi,
+    Cellular,
+    Wired,
+}
+
+#[derive(Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkStatus {
+    connectivity: Connectivity,
+    interface: Interface,
+    /// Cellular or personal hotspot
+    expensive: bool,
+    /// Low Data Mode
+    constrained: bool,
+}
+
+impl NetworkStatus {
+    fn from_path(path: *mut c_void) -> Self {
+        unsafe {
+            let connectivity = match nw_path_get_status(path) {
+                NW_PATH_STATUS_SATISFIED => Connectivity::Online,
+                NW_PATH_STATUS_SATISFIABLE => Connectivity::Satisfiable,
+                _ => Connectivity::Offline,
+            };
+
+            let interface = if nw_path_uses_interface_type(path, NW_INTERFACE_TYPE_WIRED) {
+                Interface::Wired
+            } else if nw_path_uses_interface_type(path, NW_INTERFACE_TYPE_WIFI) {
+                Interface::Wifi
+            } else if nw_path_uses_interface_type(path, NW_INTERFACE_TYPE_CELLULAR) {
+                Interface::Cellular
+            } else {
+                Interface::Other
+            };
+
+            Self {
+                connectivity,
+                interface,
+                expensive: nw_path_is_expensive(path),
+                constrained: nw_path_is_constrained(path),
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct State(Mutex<NetworkStatus>);
+
+/// Returns the last known network status
+#[tauri::command]
+pub fn get_network_status(app_handle: AppHandle<Wry>) -> NetworkStatus {
+    *app_handle.state::<State>().0.lock().unwrap()
+}
+
+/// Monitors the network path for the lifetime of the app, emitting
+/// `spotlight://network-status-changed` on every change
+pub fn watch_network(app_handle: AppHandle<Wry>) {
+    let handler = ConcreteBlock::new(move |path: *mut c_void| {
+        let status = NetworkStatus::from_path(path);
+        *app_handle.state::<State>().0.lock().unwrap() = status;
+
+        let _ = events::emit(&app_handle, NETWORK_STATUS_CHANGED_EVENT, status);
+    })
+    .copy();
+
+    // The monitor is never cancelled nor released
+    unsafe {
+        let monitor = nw_path_monitor_create();
+        nw_path_monitor_set_update_handler(monitor, &handler);
+        nw_path_monitor_set_queue(monitor, dispatch::main_queue());
+        nw_path_monitor_start(monitor);
+    }
+}
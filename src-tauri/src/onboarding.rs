@@ -0,0 +1,140 @@
+use std::{thread, time::Duration};
+
+use cocoa::base::{id, YES};
+use objc::{class, msg_send, sel, sel_impl};
+use tauri::{AppHandle, Manager, WindowBuilder, WindowEvent, WindowUrl, Wry};
+
+use crate::{
+    diagnostics, events,
+    locale::{t, Text},
+    permissions::Permissions,
+    settings::{self, Settings},
+};
+
+pub const ONBOARDING_WINDOW_LABEL: &str = "onboarding";
+
+const PERMISSIONS_CHANGED_EVENT: &str = "spotlight://permissions-changed";
+const ONBOARDING_COMPLETE_EVENT: &str = "spotlight://onboarding-complete";
+
+const PERMISSIONS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// NSApplicationActivationPolicy
+const ACTIVATION_POLICY_REGULAR: i64 = 0;
+const ACTIVATION_POLICY_ACCESSORY: i64 = 1;
+
+/// Shows the onboarding window on first run
+pub fn show_onboarding_if_needed(app_handle: &AppHandle<Wry>) {
+    if settings::settings(app_handle).onboarding_completed {
+        return;
+    }
+
+    // Deferred until the event loop runs, as the Accessory activation policy set during setup is
+    // only applied once the app finished launching and would override ours
+    let handle = app_handle.clone();
+    let scheduled = app_handle.run_on_main_thread(move || {
+        if let Err(error) = show_onboarding(handle.clone()) {
+            warn_onboarding_unavailable(&handle, error);
+        }
+    });
+
+    if let Err(error) = scheduled {
+        warn_onboarding_unavailable(app_handle, error.to_string());
+    }
+}
+
+fn warn_onboarding_unavailable(app_handle: &AppHandle<Wry>, error: String) {
+    diagnostics::warn(
+        app_handle,
+        "onboardingUnavailable",
+        format!("Unable to show the onboarding window: {}", error),
+    );
+}
+
+/// Opens the onboarding window, walking the user through granting the permissions the app
+/// relies on.
+///
+/// The app becomes a regular app with a Dock icon for as long as the window is open, so it can
+/// be found again after a trip to System Settings. Permissions are checked every second while it
+/// is open and changes are emitted as `spotlight://permissions-changed`.
+#[tauri::command]
+pub fn show_onboarding(app_handle: AppHandle<Wry>) -> Result<(), String> {
+    if let Some(window) = app_handle.get_window(ONBOARDING_WINDOW_LABEL) {
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+
+    let window = WindowBuilder::new(
+        &app_handle,
+        ONBOARDING_WINDOW_LABEL,
+        WindowUrl::App("index.html".into()),
+    )
+    .title(t(Text::OnboardingTitle))
+    .inner_size(520.0, 440.0)
+    .resizable(false)
+    .center()
+    .build()
+    .map_err(|e| e.to_string())?;
+
+    set_activation_policy(ACTIVATION_POLICY_REGULAR);
+
+    window.on_window_event(|event| {
+        if let WindowEvent::Destroyed = event {
+            set_activation_policy(ACTIVATION_POLICY_ACCESSORY);
+        }
+    });
+
+    watch_permissions(app_handle);
+
+    Ok(())
+}
+
+/// Marks the onboarding as completed, closes its window and emits
+/// `spotlight://onboarding-complete` with the permissions granted at that point
+#[tauri::command]
+pub fn finish_onboarding(app_handle: AppHandle<Wry>) -> Result<(), String> {
+    let settings = Settings {
+        onboarding_completed: true,
+        ..settings::settings(&app_handle)
+    };
+    settings::set_settings(app_handle.clone(), settings)?;
+
+    if let Some(window) = app_handle.get_window(ONBOARDING_WINDOW_LABEL) {
+        window.close().map_err(|e| e.to_string())?;
+    }
+
+    events::emit(
+        &app_handle,
+        ONBOARDING_COMPLETE_EVENT,
+        Permissions::current(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Emits permission changes until the onboarding window is gone
+fn watch_permissions(app_handle: AppHandle<Wry>) {
+    thread::spawn(move || {
+        let mut permissions = Permissions::current();
+
+        while app_handle.get_window(ONBOARDING_WINDOW_LABEL).is_some() {
+            thread::sleep(PERMISSIONS_POLL_INTERVAL);
+
+            let current = Permissions::current();
+            if current == permissions {
+                continue;
+            }
+
+            permissions = current;
+            let _ = events::emit(&app_handle, PERMISSIONS_CHANGED_EVENT, permissions);
+        }
+    });
+}
+
+fn set_activation_policy(policy: i64) {
+    unsafe {
+        let app: id = msg_send![class!(NSApplication), sharedApplication];
+        let _: () = msg_send![app, setActivationPolicy: policy];
+
+        if policy == ACTIVATION_POLICY_REGULAR {
+            let _: () = msg_send![app, activateIgnoringOtherApps: YES];
+        }
+    }
+}
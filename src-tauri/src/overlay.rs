@@ -0,0 +1,296 @@
+use std::{collections::HashMap, ffi::c_void, sync::Mutex};
+
+use cocoa::{
+    base::{id, nil, BOOL, NO, YES},
+    foundation::{NSPoint, NSRect, NSSize, NSString},
+};
+use objc::{
+    class,
+    declare::ClassDecl,
+    msg_send,
+    runtime::{self, Class, Object, Protocol, Sel},
+    sel, sel_impl, Message,
+};
+use objc_foundation::INSObject;
+use objc_id::{Id, ShareId};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{events, nsstring_to_string, panel};
+
+const OVERLAY_INPUT_EVENT: &str = "spotlight://overlay-input";
+const OVERLAY_SUBMIT_EVENT: &str = "spotlight://overlay-submit";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OverlayKind {
+    Spinner,
+    SearchField,
+}
+
+/// Frame of an overlay in webview coordinates (CSS pixels, top-left origin)
+#[derive(Deserialize)]
+pub struct OverlayFrame {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+#[derive(Clone, Serialize)]
+struct OverlayPayload {
+    id: String,
+    value: String,
+}
+
+struct Overlay {
+    view: ShareId<RawNSView>,
+    #[allow(dead_code)]
+    target: Option<Id<RawOverlayTarget>>,
+}
+
+#[derive(Default)]
+pub struct State(Mutex<HashMap<String, Overlay>>);
+
+/// Adds a native control on top of the webview.
+///
+/// Supported controls are an indeterminate spinner and a search field. The search field emits
+/// `spotlight://overlay-input` as the user types and `spotlight://overlay-submit` on Enter.
+#[tauri::command]
+pub fn add_native_overlay(
+    app_handle: AppHandle<Wry>,
+    id: String,
+    kind: OverlayKind,
+    frame: OverlayFrame,
+    placeholder: Option<String>,
+) -> Result<(), String> {
+    let state = app_handle.state::<State>();
+    let mut overlays = state.0.lock().unwrap();
+
+    if overlays.contains_key(&id) {
+        return Err(format!("Overlay \"{}\" already exists", id));
+    }
+
+    let content_view: id = panel!(app_handle).content_view();
+    let rect = to_native_frame(content_view, &frame);
+
+    let overlay = match kind {
+        OverlayKind::Spinner => Overlay {
+            view: create_spinner(rect),
+            target: None,
+        },
+        OverlayKind::SearchField => {
+            let target = RawOverlayTarget::create(app_handle.clone(), &id);
+            let view = create_search_field(rect, placeholder, &target);
+
+            Overlay {
+                view,
+                target: Some(target),
+            }
+        }
+    };
+
+    let _: () = unsafe {
+        msg_send![content_view, addSubview: &*overlay.view positioned: 1i64 relativeTo: nil]
+    };
+
+    overlays.insert(id, overlay);
+
+    Ok(())
+}
+
+/// Removes a native control previously added with `add_native_overlay`
+#[tauri::command]
+pub fn remove_native_overlay(app_handle: AppHandle<Wry>, id: String) -> Result<(), String> {
+    let overlay = app_handle
+        .state::<State>()
+        .0
+        .lock()
+        .unwrap()
+        .remove(&id)
+        .ok_or_else(|| format!("Overlay \"{}\" does not exist", id))?;
+
+    let _: () = unsafe { msg_send![overlay.view, removeFromSuperview] };
+
+    Ok(())
+}
+
+/// Removes every native control added with `add_native_overlay`
+pub(crate) fn remove_all_native_overlays(app_handle: &AppHandle<Wry>) {
+    let overlays: Vec<Overlay> = app_handle
+        .state::<State>()
+        .0
+        .lock()
+        .unwrap()
+        .drain()
+        .map(|(_, overlay)| overlay)
+        .collect();
+
+    for overlay in overlays {
+        let _: () = unsafe { msg_send![overlay.view, removeFromSuperview] };
+    }
+}
+
+/// Converts a top-left based frame to a view's coordinate system
+pub(crate) fn to_native_frame(view: id, frame: &OverlayFrame) -> NSRect {
+    let bounds: NSRect = unsafe { msg_send![view, bounds] };
+    let flipped: BOOL = unsafe { msg_send![view, isFlipped] };
+
+    let y = if flipped == YES {
+        frame.y
+    } else {
+        bounds.size.height - frame.y - frame.height
+    };
+
+    NSRect::new(
+        NSPoint::new(frame.x, y),
+        NSSize::new(frame.width, frame.height),
+    )
+}
+
+fn create_spinner(rect: NSRect) -> ShareId<RawNSView> {
+    let spinner: id = unsafe { msg_send![class!(NSProgressIndicator), alloc] };
+    let spinner: id = unsafe { msg_send![spinner, initWithFrame: rect] };
+
+    unsafe {
+        // NSProgressIndicatorStyleSpinning
+        let _: () = msg_send![spinner, setStyle: 1u64];
+        let _: () = msg_send![spinner, setIndeterminate: YES];
+        let _: () = msg_send![spinner, startAnimation: nil];
+
+        ShareId::from_retained_ptr(spinner as *mut RawNSView)
+    }
+}
+
+fn create_search_field(
+    rect: NSRect,
+    placeholder: Option<String>,
+    target: &Id<RawOverlayTarget>,
+) -> ShareId<RawNSView> {
+    let field: id = unsafe { msg_send![class!(NSSearchField), alloc] };
+    let field: id = unsafe { msg_send![field, initWithFrame: rect] };
+
+    unsafe {
+        if let Some(placeholder) = placeholder {
+            let placeholder = NSString::alloc(nil).init_str(&placeholder);
+            let _: () = msg_send![field, setPlaceholderString: placeholder];
+        }
+
+        let _: () = msg_send![field, setDelegate: &**target];
+        let _: () = msg_send![field, setTarget: &**target];
+        let _: () = msg_send![field, setAction: sel!(submit:)];
+        // Only fire the action on Enter, not on every keystroke
+        let _: () = msg_send![field, setSendsSearchStringImmediately: NO];
+        let _: () = msg_send![field, setSendsWholeSearchString: YES];
+
+        ShareId::from_retained_ptr(field as *mut RawNSView)
+    }
+}
+
+/// Any NSView subclass managed as an overlay
+pub struct RawNSView;
+
+unsafe impl Message for RawNSView {}
+
+const TARGET_CLS_NAME: &str = "RawOverlayTarget";
+
+/// Target and delegate of overlay controls, forwards their changes to the frontend
+struct RawOverlayTarget;
+
+impl RawOverlayTarget {
+    fn get_class() -> &'static Class {
+        Class::get(TARGET_CLS_NAME).unwrap_or_else(Self::define_class)
+    }
+
+    fn define_class() -> &'static Class {
+        let mut cls = ClassDecl::new(TARGET_CLS_NAME, class!(NSObject))
+            .unwrap_or_else(|| panic!("Unable to register {} class", TARGET_CLS_NAME));
+
+        cls.add_protocol(
+            Protocol::get("NSSearchFieldDelegate")
+                .expect("Failed to get NSSearchFieldDelegate protocol"),
+        );
+
+        unsafe {
+            cls.add_ivar::<*mut c_void>("_appHandle");
+            cls.add_ivar::<id>("_overlayId");
+
+            cls.add_method(
+                sel!(controlTextDidChange:),
+                Self::control_text_did_change as extern "C" fn(&Object, Sel, id),
+            );
+
+            cls.add_method(
+                sel!(submit:),
+                Self::submit as extern "C" fn(&Object, Sel, id),
+            );
+
+            cls.add_method(
+                sel!(dealloc),
+                Self::dealloc as extern "C" fn(&mut Object, Sel),
+            );
+        }
+
+        cls.register()
+    }
+
+    fn create(app_handle: AppHandle<Wry>, overlay_id: &str) -> Id<Self> {
+        let target = Self::new();
+        let app_handle = Box::into_raw(Box::new(app_handle)) as *mut c_void;
+
+        unsafe {
+            let this = &mut *(&*target as *const Self as *mut Object);
+            let overlay_id = NSString::alloc(nil).init_str(overlay_id);
+            this.set_ivar("_appHandle", app_handle);
+            this.set_ivar("_overlayId", overlay_id);
+        }
+
+        target
+    }
+
+    fn emit(this: &Object, event: &str, sender: id) {
+        let app_handle =
+            unsafe { &*(*this.get_ivar::<*mut c_void>("_appHandle") as *const AppHandle<Wry>) };
+        let overlay_id: id = unsafe { *this.get_ivar("_overlayId") };
+        let value: id = unsafe { msg_send![sender, stringValue] };
+
+        let payload = OverlayPayload {
+            id: nsstring_to_string!(overlay_id).unwrap_or_default(),
+            value: nsstring_to_string!(value).unwrap_or_default(),
+        };
+
+        let _ = events::emit(app_handle, event, payload);
+    }
+
+    extern "C" fn control_text_did_change(this: &Object, _: Sel, notification: id) {
+        let sender: id = unsafe { msg_send![notification, object] };
+        Self::emit(this, OVERLAY_INPUT_EVENT, sender);
+    }
+
+    extern "C" fn submit(this: &Object, _: Sel, sender: id) {
+        Self::emit(this, OVERLAY_SUBMIT_EVENT, sender);
+    }
+
+    /// Releases the boxed AppHandle and the overlay id
+    extern "C" fn dealloc(this: &mut Object, _: Sel) {
+        unsafe {
+            let app_handle: *mut c_void = *this.get_ivar("_appHandle");
+            if !app_handle.is_null() {
+                drop(Box::from_raw(app_handle as *mut AppHandle<Wry>));
+            }
+
+            let overlay_id: id = *this.get_ivar("_overlayId");
+            let _: () = msg_send![overlay_id, release];
+
+            let _: () = msg_send![super(this, class!(NSObject)), dealloc];
+        }
+    }
+}
+
+unsafe impl Message for RawOverlayTarget {}
+
+impl INSObject for RawOverlayTarget {
+    fn class() -> &'static runtime::Class {
+        Self::get_class()
+    }
+}
@@ -0,0 +1,120 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Wry};
+
+const CONFIG_DIR_FLAG: &str = "--config-dir";
+const PORTABLE_FLAG: &str = "--portable";
+
+/// Directories the app keeps its files in
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppDirs {
+    pub config: PathBuf,
+    pub data: PathBuf,
+    pub cache: PathBuf,
+    pub log: PathBuf,
+    /// Whether everything lives in a single directory rather than the user's Library
+    pub portable: bool,
+}
+
+impl AppDirs {
+    /// Everything under one root, as used for `--config-dir` and portable mode
+    fn under(root: PathBuf) -> Self {
+        Self {
+            config: root.clone(),
+            data: root.join("data"),
+            cache: root.join("cache"),
+            log: root.join("logs"),
+            portable: true,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct State(Mutex<Option<AppDirs>>);
+
+/// Resolves where settings, data, caches and logs live. Must run before anything reads from
+/// them.
+///
+/// In order of precedence:
+/// - `--config-dir <path>` puts everything under `path`
+/// - `--portable`, or an existing `<product name> Data` directory next to the app bundle, puts
+///   everything in that directory, for running the app from a folder synced across Macs
+/// - otherwise the usual directories in `~/Library` are used
+pub fn resolve_app_dirs(app_handle: &AppHandle<Wry>) {
+    let args = env::args().collect::<Vec<_>>();
+
+    let dirs = match config_dir_arg(&args) {
+        Some(root) => AppDirs::under(root),
+        None => match portable_dir(app_handle, args.iter().any(|arg| arg == PORTABLE_FLAG)) {
+            Some(root) => AppDirs::under(root),
+            None => {
+                let resolver = app_handle.path_resolver();
+
+                AppDirs {
+                    config: resolver.app_config_dir().unwrap(),
+                    data: resolver.app_data_dir().unwrap(),
+                    cache: resolver.app_cache_dir().unwrap(),
+                    log: resolver.app_log_dir().unwrap(),
+                    portable: false,
+                }
+            }
+        },
+    };
+
+    *app_handle.state::<State>().0.lock().unwrap() = Some(dirs);
+}
+
+/// Returns the directories resolved at startup
+pub fn app_dirs(app_handle: &AppHandle<Wry>) -> AppDirs {
+    app_handle
+        .state::<State>()
+        .0
+        .lock()
+        .unwrap()
+        .clone()
+        .expect("App directories are not resolved yet")
+}
+
+#[tauri::command]
+pub fn get_app_dirs(app_handle: AppHandle<Wry>) -> AppDirs {
+    app_dirs(&app_handle)
+}
+
+/// Accepts both `--config-dir <path>` and `--config-dir=<path>`
+fn config_dir_arg(args: &[String]) -> Option<PathBuf> {
+    args.iter().enumerate().find_map(|(index, arg)| {
+        if arg == CONFIG_DIR_FLAG {
+            args.get(index + 1).map(PathBuf::from)
+        } else {
+            arg.strip_prefix(CONFIG_DIR_FLAG)
+                .and_then(|value| value.strip_prefix('='))
+                .map(PathBuf::from)
+        }
+    })
+}
+
+/// `<product name> Data` next to the app bundle, if it exists or `create` is set
+fn portable_dir(app_handle: &AppHandle<Wry>, create: bool) -> Option<PathBuf> {
+    let exe = env::current_exe().ok()?;
+    let dir = bundle_parent(&exe)?.join(format!("{} Data", app_handle.package_info().name));
+
+    if dir.is_dir() || (create && std::fs::create_dir_all(&dir).is_ok()) {
+        Some(dir)
+    } else {
+        None
+    }
+}
+
+/// Directory containing the `.app` bundle, or the executable itself when not bundled
+fn bundle_parent(exe: &Path) -> Option<&Path> {
+    exe.ancestors()
+        .find(|path| path.extension().map_or(false, |ext| ext == "app"))
+        .and_then(Path::parent)
+        .or_else(|| exe.parent())
+}
@@ -0,0 +1,111 @@
+use cocoa::{
+    base::{id, nil},
+    foundation::NSString,
+};
+use core_foundation::{
+    base::TCFType,
+    boolean::CFBoolean,
+    dictionary::{CFDictionary, CFDictionaryRef},
+    string::{CFString, CFStringRef},
+};
+use objc::{class, msg_send, sel, sel_impl};
+use serde::{Deserialize, Serialize};
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    static kAXTrustedCheckOptionPrompt: CFStringRef;
+
+    fn AXIsProcessTrusted() -> bool;
+    fn AXIsProcessTrustedWithOptions(options: CFDictionaryRef) -> bool;
+}
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGPreflightScreenCaptureAccess() -> bool;
+    fn CGRequestScreenCaptureAccess() -> bool;
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Permission {
+    Accessibility,
+    ScreenRecording,
+}
+
+impl Permission {
+    /// Privacy pane of System Settings where the permission is granted
+    fn settings_url(&self) -> &'static str {
+        match self {
+            Permission::Accessibility => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility"
+            }
+            Permission::ScreenRecording => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture"
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Permissions {
+    pub accessibility: bool,
+    pub screen_recording: bool,
+}
+
+impl Permissions {
+    /// Checks the permissions without prompting the user
+    pub fn current() -> Self {
+        unsafe {
+            Self {
+                accessibility: AXIsProcessTrusted(),
+                screen_recording: CGPreflightScreenCaptureAccess(),
+            }
+        }
+    }
+
+    pub fn all_granted(&self) -> bool {
+        self.accessibility && self.screen_recording
+    }
+}
+
+#[tauri::command]
+pub fn get_permissions() -> Permissions {
+    Permissions::current()
+}
+
+/// Asks the system for a permission and opens its pane in System Settings.
+///
+/// Neither permission can be granted in-app; the system prompt only adds the app to the list, the
+/// user still has to flip the switch. Returns whether it's already granted.
+#[tauri::command]
+pub fn request_permission(permission: Permission) -> bool {
+    let granted = unsafe {
+        match permission {
+            Permission::Accessibility => {
+                let options = CFDictionary::from_CFType_pairs(&[(
+                    CFString::wrap_under_get_rule(kAXTrustedCheckOptionPrompt),
+                    CFBoolean::true_value(),
+                )]);
+
+                AXIsProcessTrustedWithOptions(options.as_concrete_TypeRef())
+            }
+            Permission::ScreenRecording => CGRequestScreenCaptureAccess(),
+        }
+    };
+
+    if !granted {
+        open_url(permission.settings_url());
+    }
+
+    granted
+}
+
+fn open_url(url: &str) {
+    unsafe {
+        let url = NSString::alloc(nil).init_str(url);
+        let url: id = msg_send![class!(NSURL), URLWithString: url];
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let _: () = msg_send![workspace, openURL: url];
+    }
+}
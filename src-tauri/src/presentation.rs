@@ -0,0 +1,77 @@
+use cocoa::{
+    appkit::NSWindowCollectionBehavior,
+    base::{id, BOOL, NO},
+    foundation::NSRect,
+};
+use objc::{class, msg_send, sel, sel_impl};
+
+use crate::{macos_version::Workarounds, spotlight::RawNSPanel, windows};
+
+/// Collection behaviour of the panel depending on whether the app behind it is fullscreen.
+///
+/// Moving to the active Space is what we want on regular desktops, but with a fullscreen app
+/// behind it makes the system switch Spaces, which exits fullscreen playback. See
+/// `Workarounds::sonoma_spaces` for newer versions.
+pub(crate) fn collection_behaviour(fullscreen_behind: bool) -> NSWindowCollectionBehavior {
+    let space_behaviour = if fullscreen_behind || Workarounds::current().sonoma_spaces {
+        NSWindowCollectionBehavior::NSWindowCollectionBehaviorCanJoinAllSpaces
+            | NSWindowCollectionBehavior::NSWindowCollectionBehaviorStationary
+    } else {
+        NSWindowCollectionBehavior::NSWindowCollectionBehaviorMoveToActiveSpace
+    };
+
+    space_behaviour
+        | NSWindowCollectionBehavior::NSWindowCollectionBehaviorTransient
+        | NSWindowCollectionBehavior::NSWindowCollectionBehaviorFullScreenAuxiliary
+}
+
+/// Matches the panel's behaviour to the frontmost app before showing it, so a fullscreen app
+/// behind it (e.g. video playback) stays fullscreen
+pub(crate) fn adapt_to_app_behind(panel: &RawNSPanel) {
+    let fullscreen = frontmost_app_is_fullscreen();
+
+    panel.set_collection_behaviour(collection_behaviour(fullscreen));
+}
+
+/// Whether the frontmost app's front window covers a whole screen while the menu bar is hidden
+fn frontmost_app_is_fullscreen() -> bool {
+    let menu_bar_visible: BOOL = unsafe { msg_send![class!(NSMenu), menuBarVisible] };
+    if menu_bar_visible != NO {
+        return false;
+    }
+
+    let pid: i32 = unsafe {
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let app: id = msg_send![workspace, frontmostApplication];
+        msg_send![app, processIdentifier]
+    };
+
+    match front_window_bounds(pid) {
+        Some(bounds) => screen_sizes()
+            .iter()
+            .any(|size| size.0 == bounds.size.width && size.1 == bounds.size.height),
+        None => false,
+    }
+}
+
+/// Bounds of the frontmost normal window of a process, in global display coordinates
+fn front_window_bounds(pid: i32) -> Option<NSRect> {
+    windows::window_infos(pid, true)
+        .into_iter()
+        .find_map(|info| info.bounds)
+}
+
+fn screen_sizes() -> Vec<(f64, f64)> {
+    unsafe {
+        let screens: id = msg_send![class!(NSScreen), screens];
+        let count: usize = msg_send![screens, count];
+
+        (0..count)
+            .map(|index| {
+                let screen: id = msg_send![screens, objectAtIndex: index];
+                let frame: NSRect = msg_send![screen, frame];
+                (frame.size.width, frame.size.height)
+            })
+            .collect()
+    }
+}
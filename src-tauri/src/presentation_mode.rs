@@ -0,0 +1,119 @@
+use std::{
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{drop_targets, events, get_state, hud, panel, spotlight};
+
+const PRESENTATION_MODE_EVENT: &str = "spotlight://presentation-mode";
+
+#[derive(Default)]
+struct PresentationMode {
+    ends_at: Option<Instant>,
+    /// Bumped on every change, so an earlier call's restore doesn't end a later one
+    generation: u64,
+}
+
+#[derive(Default)]
+pub struct State(Mutex<PresentationMode>);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PresentationModePayload {
+    active: bool,
+    remaining_ms: u64,
+}
+
+/// Guarantees the panel doesn't show up for `duration_ms`, e.g. during a demo, then restores
+/// everything on its own. Calling it again while active extends or shortens it.
+///
+/// Hides the panel and the companion strips, ignores every trigger (shortcut, drop targets,
+/// Handoff, timers), takes the badge off the status item and holds back timer notifications,
+/// HUDs and the shortcut help. Emits `spotlight://presentation-mode` when entered and exited.
+/// Fails if `duration_ms` is too long to be represented.
+#[tauri::command]
+pub fn enter_presentation_mode(app_handle: AppHandle<Wry>, duration_ms: u64) -> Result<(), String> {
+    let duration = Duration::from_millis(duration_ms);
+    let ends_at = Instant::now()
+        .checked_add(duration)
+        .ok_or("Presentation mode duration is too long")?;
+
+    let generation = {
+        let state = app_handle.state::<State>();
+        let mut mode = state.0.lock().unwrap();
+        mode.ends_at = Some(ends_at);
+        mode.generation += 1;
+        mode.generation
+    };
+
+    if get_state!(app_handle, panel, is_some) && panel!(app_handle).is_visible() {
+        spotlight::hide_spotlight(app_handle.clone());
+    }
+
+    drop_targets::refresh_status_item_badge(&app_handle);
+    hud::set_companion_hidden(&app_handle, true);
+
+    let _ = events::emit(
+        &app_handle,
+        PRESENTATION_MODE_EVENT,
+        PresentationModePayload {
+            active: true,
+            remaining_ms: duration_ms,
+        },
+    );
+
+    thread::spawn(move || {
+        thread::sleep(duration);
+
+        let handle = app_handle.clone();
+        let _ = app_handle.run_on_main_thread(move || {
+            let current = handle.state::<State>().0.lock().unwrap().generation;
+            if current == generation {
+                exit_presentation_mode(handle);
+            }
+        });
+    });
+
+    Ok(())
+}
+
+/// Ends presentation mode before its time is up
+#[tauri::command]
+pub fn exit_presentation_mode(app_handle: AppHandle<Wry>) {
+    {
+        let state = app_handle.state::<State>();
+        let mut mode = state.0.lock().unwrap();
+
+        if mode.ends_at.take().is_none() {
+            return;
+        }
+        mode.generation += 1;
+    }
+
+    drop_targets::refresh_status_item_badge(&app_handle);
+    hud::set_companion_hidden(&app_handle, false);
+
+    let _ = events::emit(
+        &app_handle,
+        PRESENTATION_MODE_EVENT,
+        PresentationModePayload {
+            active: false,
+            remaining_ms: 0,
+        },
+    );
+}
+
+/// Whether the panel must stay hidden and the agent quiet
+pub(crate) fn is_active(app_handle: &AppHandle<Wry>) -> bool {
+    app_handle
+        .state::<State>()
+        .0
+        .lock()
+        .unwrap()
+        .ends_at
+        .map_or(false, |ends_at| Instant::now() < ends_at)
+}
@@ -0,0 +1,89 @@
+use std::{fs, path::PathBuf};
+
+use block::ConcreteBlock;
+use cocoa::{
+    base::{id, nil, YES},
+    foundation::NSRect,
+};
+use objc::{class, msg_send, sel, sel_impl};
+use tauri::{async_runtime, AppHandle, Wry};
+
+use crate::{nsstring_to_string, panel, webview};
+
+/// Opens the print dialog for the panel's content, as a sheet on the panel.
+///
+/// Auto hide is suspended while the dialog is open and restored once the panel is key again.
+#[tauri::command]
+pub fn print_panel(app_handle: AppHandle<Wry>) {
+    let panel = panel!(app_handle);
+    let webview = webview!(app_handle);
+
+    panel.suspend_auto_hide_until_key();
+
+    unsafe {
+        let print_info: id = msg_send![class!(NSPrintInfo), sharedPrintInfo];
+        let operation: id = msg_send![webview.as_id(), printOperationWithPrintInfo: print_info];
+        let _: () = msg_send![operation, setShowsPrintPanel: YES];
+        let _: () = msg_send![operation, setShowsProgressPanel: YES];
+
+        // WKWebView's print view starts out with an empty frame and prints blank pages otherwise
+        let view: id = msg_send![operation, view];
+        let bounds: NSRect = msg_send![webview.as_id(), bounds];
+        let _: () = msg_send![view, setFrame: bounds];
+
+        // Running it modally with `runOperation` doesn't work for WKWebView
+        let _: () = msg_send![
+            operation,
+            runOperationModalForWindow: &*panel
+            delegate: nil
+            didRunSelector: nil
+            contextInfo: nil
+        ];
+    }
+}
+
+/// Renders the panel's content to a PDF file at `path`, without a print dialog
+#[tauri::command]
+pub async fn export_panel_pdf(app_handle: AppHandle<Wry>, path: PathBuf) -> Result<(), String> {
+    let (tx, mut rx) = async_runtime::channel(1);
+    let handle = app_handle.clone();
+
+    app_handle
+        .run_on_main_thread(move || {
+            let webview = webview!(handle);
+
+            let completion_handler = ConcreteBlock::new(move |data: id, error: id| {
+                let result = if data == nil {
+                    let description: id = unsafe { msg_send![error, localizedDescription] };
+                    Err(nsstring_to_string!(description)
+                        .unwrap_or_else(|| "Unable to create the PDF".into()))
+                } else {
+                    let bytes = unsafe {
+                        let bytes: *const u8 = msg_send![data, bytes];
+                        let length: usize = msg_send![data, length];
+                        std::slice::from_raw_parts(bytes, length).to_vec()
+                    };
+                    Ok(bytes)
+                };
+
+                let _ = tx.try_send(result);
+            })
+            .copy();
+
+            let _: () = unsafe {
+                msg_send![
+                    webview.as_id(),
+                    createPDFWithConfiguration: nil
+                    completionHandler: &*completion_handler
+                ]
+            };
+        })
+        .map_err(|e| e.to_string())?;
+
+    let pdf = rx
+        .recv()
+        .await
+        .ok_or_else(|| "PDF export was interrupted".to_string())??;
+
+    fs::write(path, pdf).map_err(|e| e.to_string())
+}
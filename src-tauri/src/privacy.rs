@@ -0,0 +1,59 @@
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{events, memory::MemoryPressureLevel, tabs, user_activity, webview};
+
+const PRIVACY_MODE_EVENT: &str = "spotlight://privacy-mode";
+
+#[derive(Default)]
+pub struct State(Mutex<bool>);
+
+#[derive(Clone, Serialize)]
+struct PrivacyModePayload {
+    enabled: bool,
+}
+
+/// Turns privacy mode on or off, like a browser's private window, for as long as the app runs.
+///
+/// While on, nothing the user types or opens outlives it natively: session snapshots aren't
+/// saved, the query isn't published for Handoff, and turning it on drops the webview's memory
+/// cache and the preloaded modes. History kept by the frontend (frecency, recent queries, usage
+/// stats, ...) is up to it: `spotlight://privacy-mode` is emitted so it can stop recording and
+/// hide its history UI.
+#[tauri::command]
+pub fn set_privacy_mode(app_handle: AppHandle<Wry>, enabled: bool) {
+    {
+        let state = app_handle.state::<State>();
+        let mut privacy_mode = state.0.lock().unwrap();
+
+        if *privacy_mode == enabled {
+            return;
+        }
+        *privacy_mode = enabled;
+    }
+
+    if enabled {
+        user_activity::set_user_activity(app_handle.clone(), None);
+        webview::purge_memory_cache(&app_handle);
+        tabs::evict_warm_tabs(&app_handle, MemoryPressureLevel::Critical);
+    }
+
+    let _ = events::emit(
+        &app_handle,
+        PRIVACY_MODE_EVENT,
+        PrivacyModePayload { enabled },
+    );
+}
+
+/// Returns whether privacy mode is on
+#[tauri::command]
+pub fn get_privacy_mode(app_handle: AppHandle<Wry>) -> bool {
+    is_enabled(&app_handle)
+}
+
+/// Whether subsystems should refrain from keeping anything the user typed or opened
+pub(crate) fn is_enabled(app_handle: &AppHandle<Wry>) -> bool {
+    *app_handle.state::<State>().0.lock().unwrap()
+}
@@ -0,0 +1,268 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{events, paths};
+
+const COMMAND_OUTPUT_EVENT: &str = "spotlight://command-output";
+const COMMAND_EXIT_EVENT: &str = "spotlight://command-exit";
+
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// JSON array of the programs `run_command` may execute, by name or absolute path. Only ever
+/// edited by hand, so the webview can't grant itself more.
+const ALLOWED_COMMANDS_FILE: &str = "allowed_commands.json";
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RunOptions {
+    cwd: Option<PathBuf>,
+    env: HashMap<String, String>,
+    /// Kills the command after this many milliseconds
+    timeout: Option<u64>,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Stream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Clone, Serialize)]
+struct CommandOutputPayload {
+    id: u32,
+    stream: Stream,
+    line: String,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CommandExitPayload {
+    id: u32,
+    /// `null` if the command was killed by a signal
+    code: Option<i32>,
+    timed_out: bool,
+    cancelled: bool,
+}
+
+#[derive(Default)]
+pub struct State {
+    next_id: AtomicU32,
+    children: Mutex<HashMap<u32, Arc<Mutex<Child>>>>,
+}
+
+/// Runs a program listed in `allowed_commands.json` in the config directory and returns an id
+/// for it.
+///
+/// Output is streamed line by line as `spotlight://command-output` and completion is reported as
+/// `spotlight://command-exit`, both carrying the id. The command isn't run through a shell, and
+/// `PATH` and `DYLD_*` can't be overridden, so an allowed name can't resolve to another program.
+#[tauri::command]
+pub fn run_command(
+    app_handle: AppHandle<Wry>,
+    cmd: String,
+    args: Vec<String>,
+    options: Option<RunOptions>,
+) -> Result<u32, String> {
+    let options = options.unwrap_or_default();
+
+    if !allowed_commands(&app_handle).contains(&cmd) {
+        return Err(format!("\"{}\" is not in the allowed commands", cmd));
+    }
+
+    let env = options
+        .env
+        .into_iter()
+        .filter(|(key, _)| key != "PATH" && !key.starts_with("DYLD_"));
+
+    let mut command = Command::new(&cmd);
+    command.args(args).envs(env);
+
+    if let Some(cwd) = options.cwd {
+        command.current_dir(cwd);
+    }
+
+    let handle = app_handle.clone();
+    spawn(
+        app_handle,
+        command,
+        None,
+        options.timeout.map(Duration::from_millis),
+        move |id, stream, line| {
+            let payload = CommandOutputPayload { id, stream, line };
+            let _ = events::emit(&handle, COMMAND_OUTPUT_EVENT, payload);
+        },
+    )
+}
+
+/// Read on every run, so edits apply without a restart. Missing or invalid means none.
+fn allowed_commands(app_handle: &AppHandle<Wry>) -> Vec<String> {
+    fs::read(
+        paths::app_dirs(app_handle)
+            .config
+            .join(ALLOWED_COMMANDS_FILE),
+    )
+    .ok()
+    .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+    .unwrap_or_default()
+}
+
+/// Spawns a command, feeding it `stdin` if given, and calls `on_line` with every line it outputs.
+///
+/// The command is tracked like those started by `run_command`: it can be cancelled with
+/// `cancel_command` and its completion is reported as `spotlight://command-exit`.
+pub(crate) fn spawn<F>(
+    app_handle: AppHandle<Wry>,
+    mut command: Command,
+    stdin: Option<Vec<u8>>,
+    timeout: Option<Duration>,
+    on_line: F,
+) -> Result<u32, String>
+where
+    F: Fn(u32, Stream, String) + Send + Sync + 'static,
+{
+    let mut child = command
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    if let (Some(mut pipe), Some(input)) = (child.stdin.take(), stdin) {
+        // Written from a thread so a command that doesn't read its input can't block us
+        thread::spawn(move || pipe.write_all(&input));
+    }
+
+    let state = app_handle.state::<State>();
+    let id = state.next_id.fetch_add(1, Ordering::Relaxed);
+    let on_line = Arc::new(on_line);
+
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(stream_output(id, Stream::Stdout, stdout, on_line.clone()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(stream_output(id, Stream::Stderr, stderr, on_line));
+    }
+
+    let child = Arc::new(Mutex::new(child));
+    state.children.lock().unwrap().insert(id, child.clone());
+
+    thread::spawn(move || wait_for_exit(app_handle, id, child, readers, timeout));
+
+    Ok(id)
+}
+
+/// Kills a command started with `run_command`
+#[tauri::command]
+pub fn cancel_command(app_handle: AppHandle<Wry>, id: u32) -> Result<(), String> {
+    let child = app_handle
+        .state::<State>()
+        .children
+        .lock()
+        .unwrap()
+        .remove(&id)
+        .ok_or_else(|| format!("No running command with id {}", id))?;
+
+    let mut child = child.lock().unwrap();
+    child.kill().map_err(|e| e.to_string())
+}
+
+fn stream_output<R, F>(id: u32, stream: Stream, output: R, on_line: Arc<F>) -> JoinHandle<()>
+where
+    R: Read + Send + 'static,
+    F: Fn(u32, Stream, String) + Send + Sync + 'static,
+{
+    thread::spawn(move || {
+        let mut reader = BufReader::new(output);
+        let mut line = Vec::new();
+
+        // Read bytes rather than lines, invalid UTF-8 must not stop the pipe from being drained
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+
+            if line.ends_with(b"\n") {
+                line.pop();
+                if line.ends_with(b"\r") {
+                    line.pop();
+                }
+            }
+
+            on_line(id, stream, String::from_utf8_lossy(&line).into_owned());
+        }
+    })
+}
+
+fn wait_for_exit(
+    app_handle: AppHandle<Wry>,
+    id: u32,
+    child: Arc<Mutex<Child>>,
+    readers: Vec<JoinHandle<()>>,
+    timeout: Option<Duration>,
+) {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut timed_out = false;
+
+    let status = loop {
+        let mut child = child.lock().unwrap();
+
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) => {}
+            Err(_) => break None,
+        }
+
+        if !timed_out && deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+            timed_out = true;
+            let _ = child.kill();
+        }
+
+        drop(child);
+        thread::sleep(EXIT_POLL_INTERVAL);
+    };
+
+    // Output still buffered in the pipes goes out before the exit
+    for reader in readers {
+        let _ = reader.join();
+    }
+
+    // A cancelled command was already removed by `cancel_command`
+    let cancelled = app_handle
+        .state::<State>()
+        .children
+        .lock()
+        .unwrap()
+        .remove(&id)
+        .is_none();
+
+    let payload = CommandExitPayload {
+        id,
+        code: status.and_then(|status| status.code()),
+        timed_out,
+        cancelled,
+    };
+
+    let _ = events::emit(&app_handle, COMMAND_EXIT_EVENT, payload);
+}
@@ -0,0 +1,300 @@
+use std::ffi::c_void;
+
+use block::ConcreteBlock;
+use cocoa::{
+    appkit::CGFloat,
+    base::{id, nil, BOOL},
+    foundation::{NSPoint, NSRect, NSSize},
+};
+use objc::{class, msg_send, rc::autoreleasepool, sel, sel_impl};
+use serde::{Deserialize, Serialize};
+use tauri::{async_runtime, AppHandle, Manager, Wry};
+
+use crate::{
+    locale::{t, Text},
+    macos_version::Workarounds,
+    nsstring_to_string, panel,
+    permissions::Permissions,
+    spotlight::on_main_thread,
+};
+
+#[link(name = "Vision", kind = "framework")]
+extern "C" {}
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGWindowListCreateImage(
+        screen_bounds: NSRect,
+        list_option: u32,
+        window_id: u32,
+        image_option: u32,
+    ) -> *mut c_void;
+    fn CGImageRelease(image: *mut c_void);
+}
+
+const K_CG_WINDOW_LIST_OPTION_ON_SCREEN_BELOW_WINDOW: u32 = 1 << 2;
+const K_CG_WINDOW_IMAGE_BEST_RESOLUTION: u32 = 1 << 3;
+
+/// VNRequestTextRecognitionLevelAccurate
+const RECOGNITION_LEVEL_ACCURATE: i64 = 0;
+
+/// A rect in global screen coordinates, in points with the origin at the top-left corner of the
+/// main display
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub struct ScreenRect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl ScreenRect {
+    /// Converts a Cocoa frame, whose origin is the bottom-left corner of the main display
+    pub(crate) fn from_frame(frame: NSRect) -> Self {
+        let main_screen_height = main_screen_height();
+
+        Self {
+            x: frame.origin.x,
+            y: main_screen_height - frame.origin.y - frame.size.height,
+            width: frame.size.width,
+            height: frame.size.height,
+        }
+    }
+}
+
+/// From a rect already in top-left based global coordinates, such as CGWindowList or AX bounds
+impl From<NSRect> for ScreenRect {
+    fn from(rect: NSRect) -> Self {
+        Self {
+            x: rect.origin.x,
+            y: rect.origin.y,
+            width: rect.size.width,
+            height: rect.size.height,
+        }
+    }
+}
+
+impl From<ScreenRect> for NSRect {
+    fn from(rect: ScreenRect) -> Self {
+        NSRect::new(
+            NSPoint::new(rect.x, rect.y),
+            NSSize::new(rect.width, rect.height),
+        )
+    }
+}
+
+fn main_screen_height() -> f64 {
+    unsafe {
+        let screens: id = msg_send![class!(NSScreen), screens];
+        let main_screen: id = msg_send![screens, firstObject];
+        let frame: NSRect = msg_send![main_screen, frame];
+        frame.size.height
+    }
+}
+
+#[derive(Serialize)]
+pub struct RecognizedText {
+    text: String,
+    confidence: f32,
+    /// Where the text was found on screen
+    bounds: ScreenRect,
+}
+
+/// Recognizes text in the windows behind the panel using Vision.
+///
+/// `rect` defaults to the panel's frame. The panel itself is never part of the capture. Requires
+/// the Screen Recording permission, without it only the desktop picture is captured, or nothing
+/// on macOS 15 and later.
+#[tauri::command]
+pub async fn recognize_text_behind(
+    app_handle: AppHandle<Wry>,
+    rect: Option<ScreenRect>,
+) -> Result<Vec<RecognizedText>, String> {
+    let handle = app_handle.clone();
+    let (window_number, rect) = on_main_thread(&app_handle, move || {
+        let panel = panel!(handle);
+        let rect = rect.unwrap_or_else(|| ScreenRect::from_frame(panel.frame()));
+
+        (panel.window_number() as u32, rect)
+    })
+    .await?;
+
+    if Workarounds::current().sequoia_capture_prompts && !Permissions::current().screen_recording {
+        return Err(t(Text::ScreenRecordingNotGranted).into());
+    }
+
+    autoreleasepool(|| {
+        let image = unsafe {
+            CGWindowListCreateImage(
+                rect.into(),
+                K_CG_WINDOW_LIST_OPTION_ON_SCREEN_BELOW_WINDOW,
+                window_number,
+                K_CG_WINDOW_IMAGE_BEST_RESOLUTION,
+            )
+        };
+
+        if image.is_null() {
+            return Err(t(Text::ScreenCaptureFailed).to_string());
+        }
+
+        let observations = unsafe {
+            let request: id = msg_send![class!(VNRecognizeTextRequest), new];
+            let request: id = msg_send![request, autorelease];
+            let _: () = msg_send![request, setRecognitionLevel: RECOGNITION_LEVEL_ACCURATE];
+
+            let options: id = msg_send![class!(NSDictionary), dictionary];
+            let handler: id = msg_send![class!(VNImageRequestHandler), alloc];
+            let handler: id = msg_send![handler, initWithCGImage: image options: options];
+            let handler: id = msg_send![handler, autorelease];
+
+            let requests: id = msg_send![class!(NSArray), arrayWithObject: request];
+            let _: BOOL = msg_send![handler, performRequests: requests error: nil];
+
+            CGImageRelease(image);
+
+            let results: id = msg_send![request, results];
+            results
+        };
+
+        let count: usize = unsafe { msg_send![observations, count] };
+
+        Ok((0..count)
+            .filter_map(|index| {
+                let observation: id = unsafe { msg_send![observations, objectAtIndex: index] };
+                recognized_text(observation, rect)
+            })
+            .collect())
+    })
+}
+
+/// Reads the best candidate of a VNRecognizedTextObservation and maps its normalized bounding
+/// box, whose origin is the bottom-left corner of the image, back to screen coordinates
+fn recognized_text(observation: id, rect: ScreenRect) -> Option<RecognizedText> {
+    let candidates: id = unsafe { msg_send![observation, topCandidates: 1usize] };
+    let candidate: id = unsafe { msg_send![candidates, firstObject] };
+    if candidate == nil {
+        return None;
+    }
+
+    let text: id = unsafe { msg_send![candidate, string] };
+    let confidence: f32 = unsafe { msg_send![candidate, confidence] };
+    let bounding_box: NSRect = unsafe { msg_send![observation, boundingBox] };
+
+    Some(RecognizedText {
+        text: nsstring_to_string!(text)?,
+        confidence,
+        bounds: ScreenRect {
+            x: rect.x + bounding_box.origin.x * rect.width,
+            y: rect.y + (1.0 - bounding_box.origin.y - bounding_box.size.height) * rect.height,
+            width: bounding_box.size.width * rect.width,
+            height: bounding_box.size.height * rect.height,
+        },
+    })
+}
+
+#[derive(Serialize)]
+pub struct Rgba {
+    r: u8,
+    g: u8,
+    b: u8,
+    a: f64,
+}
+
+#[derive(Serialize)]
+pub struct Hsb {
+    /// 0 to 360
+    h: f64,
+    /// 0 to 100
+    s: f64,
+    /// 0 to 100
+    b: f64,
+}
+
+/// A color in sRGB
+#[derive(Serialize)]
+pub struct Color {
+    /// `#rrggbb`
+    hex: String,
+    rgba: Rgba,
+    hsb: Hsb,
+    /// `rgb(r, g, b)`
+    css: String,
+}
+
+impl Color {
+    pub(crate) fn from_ns_color(color: id) -> Self {
+        let (red, green, blue, alpha, hue, saturation, brightness) = unsafe {
+            let color_space: id = msg_send![class!(NSColorSpace), sRGBColorSpace];
+            let color: id = msg_send![color, colorUsingColorSpace: color_space];
+
+            let red: CGFloat = msg_send![color, redComponent];
+            let green: CGFloat = msg_send![color, greenComponent];
+            let blue: CGFloat = msg_send![color, blueComponent];
+            let alpha: CGFloat = msg_send![color, alphaComponent];
+            let hue: CGFloat = msg_send![color, hueComponent];
+            let saturation: CGFloat = msg_send![color, saturationComponent];
+            let brightness: CGFloat = msg_send![color, brightnessComponent];
+
+            (red, green, blue, alpha, hue, saturation, brightness)
+        };
+
+        let to_byte = |component: f64| (component.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let rgba = Rgba {
+            r: to_byte(red),
+            g: to_byte(green),
+            b: to_byte(blue),
+            a: alpha,
+        };
+
+        Self {
+            hex: format!("#{:02x}{:02x}{:02x}", rgba.r, rgba.g, rgba.b),
+            css: format!("rgb({}, {}, {})", rgba.r, rgba.g, rgba.b),
+            rgba,
+            hsb: Hsb {
+                h: hue * 360.0,
+                s: saturation * 100.0,
+                b: brightness * 100.0,
+            },
+        }
+    }
+}
+
+/// Lets the user sample a color anywhere on screen with the system color sampler.
+///
+/// Auto hide is suspended while sampling and the panel is made key again afterwards. Resolves to
+/// `null` if the user cancels with Esc.
+#[tauri::command]
+pub async fn pick_color(app_handle: AppHandle<Wry>) -> Result<Option<Color>, String> {
+    let (tx, mut rx) = async_runtime::channel(1);
+    let handle = app_handle.clone();
+
+    app_handle
+        .run_on_main_thread(move || {
+            let panel = panel!(handle);
+            panel.suspend_auto_hide_until_key();
+
+            let selection_handler = ConcreteBlock::new(move |color: id| {
+                let color = if color == nil {
+                    None
+                } else {
+                    Some(Color::from_ns_color(color))
+                };
+
+                let _ = tx.try_send(color);
+                panel.make_key_window();
+            })
+            .copy();
+
+            unsafe {
+                let sampler: id = msg_send![class!(NSColorSampler), new];
+                let _: () =
+                    msg_send![sampler, showSamplerWithSelectionHandler: &*selection_handler];
+                let _: () = msg_send![sampler, autorelease];
+            }
+        })
+        .map_err(|e| e.to_string())?;
+
+    rx.recv()
+        .await
+        .ok_or_else(|| "Color sampling was interrupted".to_string())
+}
@@ -0,0 +1,151 @@
+use std::{sync::Mutex, thread, time::Duration};
+
+use cocoa::appkit::NSMainMenuWindowLevel;
+use objc::{msg_send, sel, sel_impl};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{
+    events, get_state,
+    settings::{self, Settings},
+    spotlight,
+    trace::{self, TraceKind},
+};
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    // Not in the public headers, but the only way to tell whether the screen is being watched
+    // (recorded, shared or mirrored) from outside the capturing process
+    fn CGSIsScreenWatcherPresent() -> bool;
+}
+
+const SCREEN_CAPTURE_CHANGED_EVENT: &str = "spotlight://screen-capture-changed";
+
+const SCREEN_CAPTURE_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Level of the panel while boosted, NSScreenSaverWindowLevel
+const BOOSTED_WINDOW_LEVEL: i32 = 1000;
+
+/// NSWindowSharingNone
+const WINDOW_SHARING_NONE: u64 = 0;
+/// NSWindowSharingReadOnly
+const WINDOW_SHARING_READ_ONLY: u64 = 1;
+
+/// What the panel does while the screen is being recorded or shared
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScreenCaptureBehaviour {
+    #[default]
+    Ignore,
+    /// Raises the panel above the windows capture tools put on top of everything
+    Boost,
+    /// Keeps the panel out of recordings and screen sharing, it stays visible to the user
+    ExcludeFromCapture,
+    /// Hides the panel when capture starts
+    Hide,
+}
+
+#[derive(Clone, Serialize)]
+struct ScreenCapturePayload {
+    active: bool,
+}
+
+/// Whether the screen was being captured at the last check
+#[derive(Default)]
+pub struct State(Mutex<bool>);
+
+/// Sets what the panel does while the screen is being captured, persists it and applies it
+/// right away
+#[tauri::command]
+pub fn set_screen_capture_behaviour(
+    app_handle: AppHandle<Wry>,
+    behaviour: ScreenCaptureBehaviour,
+) -> Result<(), String> {
+    let settings = Settings {
+        screen_capture_behaviour: behaviour,
+        ..settings::settings(&app_handle)
+    };
+    settings::set_settings(app_handle.clone(), settings)?;
+
+    let active = *app_handle.state::<State>().0.lock().unwrap();
+    apply_behaviour(&app_handle, active);
+
+    Ok(())
+}
+
+/// Whether the screen is being recorded, shared or mirrored
+#[tauri::command]
+pub fn is_screen_captured() -> bool {
+    unsafe { CGSIsScreenWatcherPresent() }
+}
+
+/// Checks for screen capture every couple of seconds for the lifetime of the app, applies the
+/// behaviour from the settings and emits `spotlight://screen-capture-changed` on changes
+pub fn watch_screen_capture(app_handle: AppHandle<Wry>) {
+    thread::spawn(move || loop {
+        let active = is_screen_captured();
+
+        let changed = {
+            let state = app_handle.state::<State>();
+            let mut current = state.0.lock().unwrap();
+            let changed = *current != active;
+            *current = active;
+            changed
+        };
+
+        if changed {
+            let handle = app_handle.clone();
+            let _ = app_handle.run_on_main_thread(move || {
+                apply_behaviour(&handle, active);
+                let _ = events::emit(
+                    &handle,
+                    SCREEN_CAPTURE_CHANGED_EVENT,
+                    ScreenCapturePayload { active },
+                );
+            });
+        }
+
+        thread::sleep(SCREEN_CAPTURE_CHECK_INTERVAL);
+    });
+}
+
+/// Sets the panel's level and sharing type for the current capture state. Must be called on the
+/// main thread.
+fn apply_behaviour(app_handle: &AppHandle<Wry>, active: bool) {
+    let panel = match get_state!(app_handle, panel, clone) {
+        Some(panel) => panel,
+        None => return,
+    };
+
+    let behaviour = if active {
+        settings::settings(app_handle).screen_capture_behaviour
+    } else {
+        ScreenCaptureBehaviour::Ignore
+    };
+
+    let level = if behaviour == ScreenCaptureBehaviour::Boost {
+        BOOSTED_WINDOW_LEVEL
+    } else {
+        NSMainMenuWindowLevel + 1
+    };
+
+    let sharing_type = if behaviour == ScreenCaptureBehaviour::ExcludeFromCapture {
+        WINDOW_SHARING_NONE
+    } else {
+        WINDOW_SHARING_READ_ONLY
+    };
+
+    trace::record(
+        TraceKind::Level,
+        format!("{} while the screen is captured", level),
+    );
+
+    unsafe {
+        let _: () = msg_send![&*panel, setLevel: level];
+        let _: () = msg_send![&*panel, setSharingType: sharing_type];
+    }
+
+    if behaviour == ScreenCaptureBehaviour::Hide && panel.is_visible() {
+        spotlight::hide_spotlight(app_handle.clone());
+    }
+}
@@ -0,0 +1,44 @@
+use std::{fs, path::PathBuf};
+
+use serde_json::Value;
+use tauri::{AppHandle, Wry};
+
+use crate::{paths, privacy};
+
+const SESSION_FILE: &str = "session.json";
+
+fn session_path(app_handle: &AppHandle<Wry>) -> PathBuf {
+    paths::app_dirs(app_handle).data.join(SESSION_FILE)
+}
+
+/// Persists a snapshot of where the user is in the panel (mode, query, scroll position, ...),
+/// replacing the previous one. The content is up to the frontend.
+///
+/// Written to a temporary file first, so a relaunch mid-write still finds the previous snapshot.
+/// Does nothing in privacy mode.
+#[tauri::command]
+pub fn save_session(app_handle: AppHandle<Wry>, session: Value) -> Result<(), String> {
+    if privacy::is_enabled(&app_handle) {
+        return Ok(());
+    }
+
+    let path = session_path(&app_handle);
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+
+    let json = serde_json::to_vec(&session).map_err(|e| e.to_string())?;
+    let temp_path = path.with_extension("json.tmp");
+
+    fs::write(&temp_path, json).map_err(|e| e.to_string())?;
+    fs::rename(temp_path, path).map_err(|e| e.to_string())
+}
+
+/// Returns the snapshot saved with `save_session`, `null` if there's none or it can't be read
+#[tauri::command]
+pub fn load_session(app_handle: AppHandle<Wry>) -> Option<Value> {
+    fs::read(session_path(&app_handle))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+}
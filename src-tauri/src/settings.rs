@@ -0,0 +1,191 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{
+    appearance_schedule::AppearanceSchedule,
+    diagnostics,
+    drop_targets::DropTargets,
+    feature_flags::FeatureFlag,
+    locale::{t, Text},
+    paths,
+    screen_capture::ScreenCaptureBehaviour,
+};
+
+const SETTINGS_FILE: &str = "settings.json";
+/// Where a settings file that couldn't be read is moved, so saving doesn't overwrite it
+const INVALID_SETTINGS_FILE: &str = "settings.invalid.json";
+
+/// Version of the export format, bumped on breaking changes
+const BUNDLE_VERSION: u32 = 1;
+
+/// User settings persisted as JSON in the app's config directory, see `paths`
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Settings {
+    /// Set once the user went through the onboarding window
+    pub onboarding_completed: bool,
+    /// Switches the panel between light and dark on a schedule instead of following the system
+    pub appearance_schedule: Option<AppearanceSchedule>,
+    /// Status item and screen edge accepting drops
+    pub drop_targets: DropTargets,
+    /// Page zoom of each window's webview by window label, missing means 100%
+    pub webview_zoom: HashMap<String, f64>,
+    /// What the panel does while the screen is being recorded or shared
+    pub screen_capture_behaviour: ScreenCaptureBehaviour,
+    /// Experimental code paths turned on or off, missing means their default
+    pub feature_flags: HashMap<FeatureFlag, bool>,
+}
+
+/// Everything needed to move the app to another machine, as written by `export_settings`
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SettingsBundle {
+    version: u32,
+    settings: Settings,
+    /// Data owned by the frontend (snippets, frecency, shortcuts, ...), kept as is
+    #[serde(default)]
+    frontend: Value,
+}
+
+#[derive(Default)]
+pub struct State(pub Mutex<Settings>);
+
+/// Why the settings file was set aside at launch, until the frontend is told
+static INVALID_SETTINGS: Mutex<Option<String>> = Mutex::new(None);
+
+/// Set when an invalid settings file couldn't be set aside, saving would overwrite it
+static SAVING_BLOCKED: AtomicBool = AtomicBool::new(false);
+
+fn settings_path(app_handle: &AppHandle<Wry>) -> PathBuf {
+    paths::app_dirs(app_handle).config.join(SETTINGS_FILE)
+}
+
+/// Loads the settings from disk into the app state, falling back to defaults if the file is
+/// missing or invalid. An invalid file is moved to `settings.invalid.json` rather than
+/// overwritten, see `warn_if_invalid`.
+pub fn load_settings(app_handle: &AppHandle<Wry>) {
+    let path = settings_path(app_handle);
+
+    let settings = match fs::read(&path) {
+        Ok(bytes) => match serde_json::from_slice(&bytes) {
+            Ok(settings) => settings,
+            Err(error) => {
+                let backup = path.with_file_name(INVALID_SETTINGS_FILE);
+                let message = match fs::rename(&path, &backup) {
+                    Ok(()) => format!(
+                        "Settings couldn't be read ({}), defaults are used. The file was moved to \
+                         {}.",
+                        error,
+                        backup.display()
+                    ),
+                    Err(rename_error) => {
+                        SAVING_BLOCKED.store(true, Ordering::Relaxed);
+                        format!(
+                            "Settings couldn't be read ({}) nor set aside ({}), defaults are used \
+                             and changes won't be saved.",
+                            error, rename_error
+                        )
+                    }
+                };
+
+                *INVALID_SETTINGS.lock().unwrap() = Some(message);
+                Settings::default()
+            }
+        },
+        Err(_) => Settings::default(),
+    };
+
+    *app_handle.state::<State>().0.lock().unwrap() = settings;
+}
+
+/// Warns the frontend when the settings file couldn't be read at launch. Called once the panel
+/// is initialized, nothing listens before.
+pub(crate) fn warn_if_invalid(app_handle: &AppHandle<Wry>) {
+    if let Some(message) = INVALID_SETTINGS.lock().unwrap().take() {
+        diagnostics::warn(app_handle, "invalidSettings", message);
+    }
+}
+
+fn save_settings(app_handle: &AppHandle<Wry>, settings: &Settings) -> Result<(), String> {
+    if SAVING_BLOCKED.load(Ordering::Relaxed) {
+        return Err("The settings file couldn't be read, it won't be overwritten".into());
+    }
+
+    let path = settings_path(app_handle);
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+
+    let json = serde_json::to_vec_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| e.to_string())
+}
+
+/// Returns a copy of the current settings
+pub fn settings(app_handle: &AppHandle<Wry>) -> Settings {
+    app_handle.state::<State>().0.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn get_settings(app_handle: AppHandle<Wry>) -> Settings {
+    settings(&app_handle)
+}
+
+/// Replaces the settings and persists them
+#[tauri::command]
+pub fn set_settings(app_handle: AppHandle<Wry>, settings: Settings) -> Result<(), String> {
+    save_settings(&app_handle, &settings)?;
+    *app_handle.state::<State>().0.lock().unwrap() = settings;
+
+    Ok(())
+}
+
+/// Writes the settings, along with the frontend's own data, to a single JSON file
+#[tauri::command]
+pub fn export_settings(
+    app_handle: AppHandle<Wry>,
+    path: PathBuf,
+    frontend: Option<Value>,
+) -> Result<(), String> {
+    let bundle = SettingsBundle {
+        version: BUNDLE_VERSION,
+        settings: settings(&app_handle),
+        frontend: frontend.unwrap_or_default(),
+    };
+
+    let json = serde_json::to_vec_pretty(&bundle).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| e.to_string())
+}
+
+/// Replaces the settings with the ones from a file written by `export_settings` and returns the
+/// frontend's data for it to restore.
+///
+/// Whether the onboarding was completed is kept, as permissions are granted per machine.
+#[tauri::command]
+pub fn import_settings(app_handle: AppHandle<Wry>, path: PathBuf) -> Result<Value, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let bundle: SettingsBundle = serde_json::from_slice(&bytes).map_err(|e| e.to_string())?;
+
+    if bundle.version > BUNDLE_VERSION {
+        return Err(t(Text::UnsupportedSettingsVersion).to_string());
+    }
+
+    let settings = Settings {
+        onboarding_completed: settings(&app_handle).onboarding_completed,
+        ..bundle.settings
+    };
+    set_settings(app_handle, settings)?;
+
+    Ok(bundle.frontend)
+}
@@ -0,0 +1,75 @@
+use cocoa::{
+    base::{id, nil},
+    foundation::NSString,
+};
+use objc::{class, msg_send, rc::autoreleasepool, sel, sel_impl};
+use tauri::{AppHandle, Wry};
+
+use crate::{
+    overlay::{to_native_frame, OverlayFrame},
+    panel, webview,
+};
+
+/// NSRectEdgeMinY, below the anchor in the webview's flipped coordinates
+const PREFERRED_EDGE_MIN_Y: u64 = 1;
+
+/// Presents the system share picker (AirDrop, Messages, Mail, ...) for file paths and URLs,
+/// anchored to a rect in webview coordinates such as the shared result's row.
+///
+/// Auto hide is suspended while the picker or the chosen service's UI is open and restored once
+/// the panel is key again.
+#[tauri::command]
+pub fn share_items(
+    app_handle: AppHandle<Wry>,
+    items: Vec<String>,
+    anchor_rect: OverlayFrame,
+) -> Result<(), String> {
+    if items.is_empty() {
+        return Err("Nothing to share".into());
+    }
+
+    let panel = panel!(app_handle);
+    let webview = webview!(app_handle);
+
+    panel.suspend_auto_hide_until_key();
+
+    autoreleasepool(|| unsafe {
+        let share_items: id = msg_send![class!(NSMutableArray), array];
+        for item in &items {
+            let _: () = msg_send![share_items, addObject: to_url(item)];
+        }
+
+        let picker: id = msg_send![class!(NSSharingServicePicker), alloc];
+        let picker: id = msg_send![picker, initWithItems: share_items];
+        let _: () = msg_send![
+            picker,
+            showRelativeToRect: to_native_frame(webview.as_id(), &anchor_rect)
+            ofView: webview.as_id()
+            preferredEdge: PREFERRED_EDGE_MIN_Y
+        ];
+        // Kept alive by its menu while it's shown
+        let _: () = msg_send![picker, autorelease];
+    });
+
+    Ok(())
+}
+
+/// URLs with a scheme are shared as links, anything else as a file
+fn to_url(item: &str) -> id {
+    unsafe {
+        let string = NSString::alloc(nil).init_str(item);
+        let string: id = msg_send![string, autorelease];
+
+        let url: id = if item.contains("://") {
+            msg_send![class!(NSURL), URLWithString: string]
+        } else {
+            nil
+        };
+
+        if url != nil {
+            return url;
+        }
+
+        msg_send![class!(NSURL), fileURLWithPath: string]
+    }
+}
@@ -0,0 +1,253 @@
+use std::{
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use block::ConcreteBlock;
+use cocoa::{
+    appkit::{NSEventModifierFlags, NSMainMenuWindowLevel, NSWindowCollectionBehavior},
+    base::{id, YES},
+};
+use objc::{class, msg_send, sel, sel_impl};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{
+    events, presentation_mode,
+    spotlight::{self, position_window_at_the_center_of_the_monitor_with_cursor},
+};
+
+/// Label of the hidden window rendering the shortcut help, see `tauri.conf.json`
+pub const HELP_WINDOW_LABEL: &str = "help";
+
+const SHORTCUT_HELP_EVENT: &str = "spotlight://shortcut-help";
+
+/// How long the trigger modifier has to be held alone before the help is shown
+const HOLD_DURATION: Duration = Duration::from_secs(1);
+
+/// NSEventTypeFlagsChanged
+const NS_EVENT_TYPE_FLAGS_CHANGED: u64 = 12;
+
+/// kCGEventSourceStateCombinedSessionState
+const EVENT_SOURCE_STATE_COMBINED_SESSION: i32 = 0;
+/// kCGEventKeyDown
+const EVENT_TYPE_KEY_DOWN: u32 = 10;
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGEventSourceSecondsSinceLastEventType(state_id: i32, event_type: u32) -> f64;
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ShortcutHelpItem {
+    keys: String,
+    description: String,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ShortcutHelpSection {
+    /// Mode the shortcuts apply in, e.g. "Results" or "Preview"
+    title: String,
+    items: Vec<ShortcutHelpItem>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ShortcutHelpPayload {
+    toggle_shortcut: String,
+    sections: Vec<ShortcutHelpSection>,
+}
+
+#[derive(Default)]
+pub struct State(Mutex<Vec<ShortcutHelpSection>>);
+
+struct Hold {
+    /// When the trigger modifier started being held alone
+    since: Option<Instant>,
+    /// Bumped on every modifier change, so a pending check doesn't act on an earlier hold
+    generation: u64,
+    shown: bool,
+}
+
+static HOLD: Mutex<Hold> = Mutex::new(Hold {
+    since: None,
+    generation: 0,
+    shown: false,
+});
+
+/// Sets the shortcuts and modes listed by the help window, besides the toggle shortcut
+#[tauri::command]
+pub fn set_shortcut_help(app_handle: AppHandle<Wry>, sections: Vec<ShortcutHelpSection>) {
+    *app_handle.state::<State>().0.lock().unwrap() = sections;
+}
+
+/// Prepares the help window as a click-through HUD and shows it while the trigger modifier of the
+/// toggle shortcut, its first one, is held alone for more than a second.
+///
+/// Modifier changes in other apps are only seen with the Accessibility permission, without it
+/// the help only shows while the panel is key. Must be called on the main thread.
+pub fn install_shortcut_help(app_handle: AppHandle<Wry>) {
+    let window = match app_handle.get_window(HELP_WINDOW_LABEL) {
+        Some(window) => window,
+        None => return,
+    };
+
+    let handle: id = window.ns_window().unwrap() as _;
+
+    unsafe {
+        let _: () = msg_send![handle, setLevel: NSMainMenuWindowLevel + 2];
+        let _: () = msg_send![handle, setIgnoresMouseEvents: YES];
+        let _: () = msg_send![
+            handle,
+            setCollectionBehavior: NSWindowCollectionBehavior::NSWindowCollectionBehaviorCanJoinAllSpaces
+                | NSWindowCollectionBehavior::NSWindowCollectionBehaviorFullScreenAuxiliary
+        ];
+    }
+
+    let mask = 1u64 << NS_EVENT_TYPE_FLAGS_CHANGED;
+
+    let global_handle = app_handle.clone();
+    let global_handler = ConcreteBlock::new(move |event: id| {
+        on_flags_changed(&global_handle, event);
+    })
+    .copy();
+
+    let local_handler = ConcreteBlock::new(move |event: id| -> id {
+        on_flags_changed(&app_handle, event);
+        event
+    })
+    .copy();
+
+    unsafe {
+        let _: id = msg_send![
+            class!(NSEvent),
+            addGlobalMonitorForEventsMatchingMask: mask
+            handler: &*global_handler
+        ];
+        let _: id = msg_send![
+            class!(NSEvent),
+            addLocalMonitorForEventsMatchingMask: mask
+            handler: &*local_handler
+        ];
+    }
+}
+
+/// Starts timing a hold when the trigger modifier is pressed alone, and hides the help as soon as
+/// anything else is held or it's released
+fn on_flags_changed(app_handle: &AppHandle<Wry>, event: id) {
+    let flags: NSEventModifierFlags = unsafe { msg_send![event, modifierFlags] };
+    let held_alone = trigger_modifier(&spotlight::shortcut(app_handle))
+        .map_or(false, |trigger| held_modifiers(flags) == trigger);
+
+    let (since, generation, was_shown) = {
+        let mut hold = HOLD.lock().unwrap();
+        if held_alone && hold.since.is_some() {
+            return;
+        }
+
+        hold.generation += 1;
+        hold.since = if held_alone {
+            Some(Instant::now())
+        } else {
+            None
+        };
+        let was_shown = hold.shown;
+        hold.shown = false;
+
+        (hold.since, hold.generation, was_shown)
+    };
+
+    if was_shown {
+        set_help_visible(app_handle, false);
+    }
+
+    let since = match since {
+        Some(since) => since,
+        None => return,
+    };
+
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        thread::sleep(HOLD_DURATION);
+
+        let handle = app_handle.clone();
+        let _ = app_handle.run_on_main_thread(move || {
+            {
+                let mut hold = HOLD.lock().unwrap();
+
+                // Holding the modifier as part of a shortcut is not a request for help
+                if hold.generation != generation
+                    || typed_since(since)
+                    || presentation_mode::is_active(&handle)
+                {
+                    return;
+                }
+                hold.shown = true;
+            }
+
+            set_help_visible(&handle, true);
+        });
+    });
+}
+
+fn set_help_visible(app_handle: &AppHandle<Wry>, visible: bool) {
+    let window = match app_handle.get_window(HELP_WINDOW_LABEL) {
+        Some(window) => window,
+        None => return,
+    };
+
+    let ns_window: id = window.ns_window().unwrap() as _;
+
+    if !visible {
+        let _: () = unsafe { msg_send![ns_window, orderOut: ns_window] };
+        return;
+    }
+
+    let payload = ShortcutHelpPayload {
+        toggle_shortcut: spotlight::shortcut(app_handle),
+        sections: app_handle.state::<State>().0.lock().unwrap().clone(),
+    };
+
+    let _ = events::emit(app_handle, SHORTCUT_HELP_EVENT, payload);
+
+    position_window_at_the_center_of_the_monitor_with_cursor(&window);
+
+    // Never made key, so the frontmost app keeps focus
+    let _: () = unsafe { msg_send![ns_window, orderFrontRegardless] };
+}
+
+/// Modifier of an accelerator like `CommandOrControl+Shift+Space` that triggers the help, its
+/// first one
+fn trigger_modifier(shortcut: &str) -> Option<NSEventModifierFlags> {
+    shortcut
+        .split('+')
+        .find_map(|key| match key.trim().to_uppercase().as_str() {
+            "SHIFT" => Some(NSEventModifierFlags::NSShiftKeyMask),
+            "CONTROL" | "CTRL" => Some(NSEventModifierFlags::NSControlKeyMask),
+            "ALT" | "OPTION" => Some(NSEventModifierFlags::NSAlternateKeyMask),
+            "SUPER" | "COMMAND" | "CMD" | "COMMANDORCONTROL" | "COMMANDORCTRL" | "CMDORCTRL"
+            | "CMDORCONTROL" => Some(NSEventModifierFlags::NSCommandKeyMask),
+            _ => None,
+        })
+}
+
+fn held_modifiers(flags: NSEventModifierFlags) -> NSEventModifierFlags {
+    flags
+        & (NSEventModifierFlags::NSShiftKeyMask
+            | NSEventModifierFlags::NSControlKeyMask
+            | NSEventModifierFlags::NSAlternateKeyMask
+            | NSEventModifierFlags::NSCommandKeyMask)
+}
+
+/// Whether a key was pressed anywhere since `instant`
+fn typed_since(instant: Instant) -> bool {
+    let seconds = unsafe {
+        CGEventSourceSecondsSinceLastEventType(
+            EVENT_SOURCE_STATE_COMBINED_SESSION,
+            EVENT_TYPE_KEY_DOWN,
+        )
+    };
+
+    Duration::from_secs_f64(seconds) < instant.elapsed()
+}
@@ -0,0 +1,301 @@
+use std::sync::Mutex;
+
+use block::ConcreteBlock;
+use cocoa::{
+    base::{id, nil, BOOL, YES},
+    foundation::NSString,
+};
+use objc::{class, msg_send, rc::autoreleasepool, runtime::Object, sel, sel_impl};
+use objc_id::Id;
+use serde::Serialize;
+use tauri::{async_runtime, AppHandle, Manager, Wry};
+
+use crate::{
+    audio, events,
+    locale::{t, Text},
+    nsstring_to_string,
+    spotlight::ns_error_to_result,
+};
+
+#[link(name = "Speech", kind = "framework")]
+extern "C" {}
+
+const DICTATION_RESULT_EVENT: &str = "spotlight://dictation-result";
+const DICTATION_ERROR_EVENT: &str = "spotlight://dictation-error";
+
+/// SFSpeechRecognizerAuthorizationStatusAuthorized
+const SPEECH_AUTHORIZED: i64 = 3;
+
+const TAP_BUFFER_SIZE: u32 = 1024;
+
+/// AVSpeechBoundaryImmediate
+const SPEECH_BOUNDARY_IMMEDIATE: i64 = 0;
+
+/// A running dictation, kept until its final result so the recognizer outlives its task
+struct Dictation {
+    recognizer: Id<Object>,
+    engine: Id<Object>,
+    request: Id<Object>,
+    task: Id<Object>,
+}
+
+impl Dictation {
+    /// Stops feeding the microphone to the recognizer, can be called more than once
+    fn stop_audio(&self) {
+        unsafe {
+            let input_node: id = msg_send![self.engine, inputNode];
+            let _: () = msg_send![input_node, removeTapOnBus: 0usize];
+            let _: () = msg_send![self.engine, stop];
+            let _: () = msg_send![self.request, endAudio];
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct State {
+    dictation: Mutex<Option<Dictation>>,
+    synthesizer: Mutex<Option<Id<Object>>>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DictationResultPayload {
+    text: String,
+    is_final: bool,
+}
+
+#[derive(Clone, Serialize)]
+struct DictationErrorPayload {
+    message: String,
+}
+
+async fn request_speech_recognition_access() -> Result<(), String> {
+    let (tx, mut rx) = async_runtime::channel(1);
+
+    // Blocks aren't Send, keep this one out of the await below
+    {
+        let handler = ConcreteBlock::new(move |status: i64| {
+            let _ = tx.try_send(status == SPEECH_AUTHORIZED);
+        })
+        .copy();
+
+        let _: () =
+            unsafe { msg_send![class!(SFSpeechRecognizer), requestAuthorization: &*handler] };
+    }
+
+    match rx.recv().await {
+        Some(true) => Ok(()),
+        _ => Err(t(Text::SpeechRecognitionDenied).into()),
+    }
+}
+
+/// Starts transcribing the microphone with the system speech recognizer.
+///
+/// Partial and final transcriptions are emitted as `spotlight://dictation-result`, failures as
+/// `spotlight://dictation-error`. `locale` is a locale identifier such as `en-US`, defaulting to
+/// the system's. Asks for microphone and speech recognition access the first time.
+#[tauri::command]
+pub async fn start_dictation(
+    app_handle: AppHandle<Wry>,
+    locale: Option<String>,
+) -> Result<(), String> {
+    audio::request_microphone_access().await?;
+    request_speech_recognition_access().await?;
+
+    let state = app_handle.state::<State>();
+    let mut dictation = state.dictation.lock().unwrap();
+
+    if dictation.is_some() {
+        return Err(t(Text::DictationRunning).into());
+    }
+
+    autoreleasepool(|| unsafe {
+        let recognizer: id = msg_send![class!(SFSpeechRecognizer), alloc];
+        let recognizer: id = match &locale {
+            Some(locale) => {
+                let identifier = NSString::alloc(nil).init_str(locale);
+                let identifier: id = msg_send![identifier, autorelease];
+                let locale: id =
+                    msg_send![class!(NSLocale), localeWithLocaleIdentifier: identifier];
+                msg_send![recognizer, initWithLocale: locale]
+            }
+            None => msg_send![recognizer, init],
+        };
+
+        if recognizer == nil {
+            return Err(t(Text::SpeechRecognitionUnsupportedLocale).to_string());
+        }
+
+        let recognizer = Id::<Object>::from_retained_ptr(recognizer);
+        if !is_available(&recognizer) {
+            return Err(t(Text::SpeechRecognitionUnavailable).to_string());
+        }
+
+        let request: id = msg_send![class!(SFSpeechAudioBufferRecognitionRequest), new];
+        let _: () = msg_send![request, setShouldReportPartialResults: YES];
+
+        let handle = app_handle.clone();
+        let result_handler = ConcreteBlock::new(move |result: id, error: id| {
+            emit_recognition_result(&handle, result, error);
+        })
+        .copy();
+
+        let task: id = msg_send![
+            recognizer,
+            recognitionTaskWithRequest: request
+            resultHandler: &*result_handler
+        ];
+        let task: id = msg_send![task, retain];
+
+        let engine: id = msg_send![class!(AVAudioEngine), new];
+        let input_node: id = msg_send![engine, inputNode];
+        let format: id = msg_send![input_node, outputFormatForBus: 0usize];
+
+        let tap_request = Id::<Object>::from_ptr(request);
+        let tap = ConcreteBlock::new(move |buffer: id, _when: id| {
+            let _: () = msg_send![tap_request, appendAudioPCMBuffer: buffer];
+        })
+        .copy();
+
+        let _: () = msg_send![
+            input_node,
+            installTapOnBus: 0usize
+            bufferSize: TAP_BUFFER_SIZE
+            format: format
+            block: &*tap
+        ];
+
+        let _: () = msg_send![engine, prepare];
+
+        let mut error: id = nil;
+        let started: BOOL = msg_send![engine, startAndReturnError: &mut error];
+
+        let engine = Id::from_retained_ptr(engine);
+        let request = Id::from_retained_ptr(request);
+        let task = Id::from_retained_ptr(task);
+
+        if started != YES {
+            let _: () = msg_send![input_node, removeTapOnBus: 0usize];
+            let _: () = msg_send![task, cancel];
+            return Err(ns_error_to_result(error)
+                .err()
+                .unwrap_or_else(|| t(Text::AudioInputFailed).to_string()));
+        }
+
+        *dictation = Some(Dictation {
+            recognizer,
+            engine,
+            request,
+            task,
+        });
+
+        Ok(())
+    })
+}
+
+/// Stops listening, the final transcription is still emitted once the recognizer is done
+#[tauri::command]
+pub fn stop_dictation(app_handle: AppHandle<Wry>) {
+    if let Some(dictation) = &*app_handle.state::<State>().dictation.lock().unwrap() {
+        dictation.stop_audio();
+        let _: () = unsafe { msg_send![dictation.task, finish] };
+    }
+}
+
+/// Releases the dictation once the recognizer is done with it, after a final result or an error
+fn end_dictation(app_handle: &AppHandle<Wry>) -> Option<Dictation> {
+    let dictation = app_handle.state::<State>().dictation.lock().unwrap().take();
+
+    if let Some(dictation) = &dictation {
+        dictation.stop_audio();
+    }
+
+    dictation
+}
+
+fn emit_recognition_result(app_handle: &AppHandle<Wry>, result: id, error: id) {
+    if let Err(message) = ns_error_to_result(error) {
+        // Server-based recognition fails that way when it drops, e.g. when going offline
+        let message = match end_dictation(app_handle) {
+            Some(dictation) if !is_available(&dictation.recognizer) => {
+                t(Text::SpeechRecognitionUnavailable).to_string()
+            }
+            _ => message,
+        };
+
+        let _ = events::emit(
+            app_handle,
+            DICTATION_ERROR_EVENT,
+            DictationErrorPayload { message },
+        );
+        return;
+    }
+
+    let transcription: id = unsafe { msg_send![result, bestTranscription] };
+    let text: id = unsafe { msg_send![transcription, formattedString] };
+    let is_final: BOOL = unsafe { msg_send![result, isFinal] };
+
+    let payload = DictationResultPayload {
+        text: nsstring_to_string!(text).unwrap_or_default(),
+        is_final: is_final == YES,
+    };
+
+    if payload.is_final {
+        end_dictation(app_handle);
+    }
+
+    let _ = events::emit(app_handle, DICTATION_RESULT_EVENT, payload);
+}
+
+fn is_available(recognizer: &Id<Object>) -> bool {
+    let available: BOOL = unsafe { msg_send![&**recognizer, isAvailable] };
+    available == YES
+}
+
+/// Reads text aloud, interrupting anything currently being spoken.
+///
+/// `voice` is either a voice identifier (`com.apple.voice.compact.en-US.Samantha`) or a language
+/// (`en-US`), defaulting to the system voice. `rate` goes from 0.0 to 1.0, 0.5 being the default.
+#[tauri::command]
+pub fn speak(app_handle: AppHandle<Wry>, text: String, voice: Option<String>, rate: Option<f32>) {
+    let state = app_handle.state::<State>();
+    let mut synthesizer = state.synthesizer.lock().unwrap();
+    let synthesizer = synthesizer.get_or_insert_with(|| unsafe {
+        Id::from_retained_ptr(msg_send![class!(AVSpeechSynthesizer), new])
+    });
+
+    autoreleasepool(|| unsafe {
+        let _: BOOL = msg_send![&**synthesizer, stopSpeakingAtBoundary: SPEECH_BOUNDARY_IMMEDIATE];
+
+        let text = NSString::alloc(nil).init_str(&text);
+        let text: id = msg_send![text, autorelease];
+        let utterance: id = msg_send![class!(AVSpeechUtterance), speechUtteranceWithString: text];
+
+        if let Some(voice) = voice {
+            let voice = NSString::alloc(nil).init_str(&voice);
+            let voice: id = msg_send![voice, autorelease];
+            let mut speech_voice: id =
+                msg_send![class!(AVSpeechSynthesisVoice), voiceWithIdentifier: voice];
+            if speech_voice == nil {
+                speech_voice = msg_send![class!(AVSpeechSynthesisVoice), voiceWithLanguage: voice];
+            }
+
+            let _: () = msg_send![utterance, setVoice: speech_voice];
+        }
+
+        if let Some(rate) = rate {
+            let _: () = msg_send![utterance, setRate: rate.clamp(0.0, 1.0)];
+        }
+
+        let _: () = msg_send![&**synthesizer, speakUtterance: utterance];
+    });
+}
+
+/// Stops reading text aloud
+#[tauri::command]
+pub fn stop_speaking(app_handle: AppHandle<Wry>) {
+    if let Some(synthesizer) = &*app_handle.state::<State>().synthesizer.lock().unwrap() {
+        let _: BOOL =
+            unsafe { msg_send![&**synthesizer, stopSpeakingAtBoundary: SPEECH_BOUNDARY_IMMEDIATE] };
+    }
+}
@@ -1,19 +1,25 @@
-use std::sync::{Mutex, Once};
+use std::{
+    sync::{Mutex, Once},
+    thread,
+    time::{Duration, Instant},
+};
 
 use bitflags::bitflags;
+use block::ConcreteBlock;
 
 use objc_id::{Id, ShareId};
 use tauri::{
-    AppHandle, GlobalShortcutManager, Manager, PhysicalPosition, PhysicalSize, Window, Wry,
+    async_runtime, AppHandle, GlobalShortcutManager, Manager, PhysicalPosition, PhysicalSize,
+    Window, WindowBuilder, WindowEvent, Wry,
 };
 
 use cocoa::{
     appkit::{
-        CGFloat, NSMainMenuWindowLevel, NSView, NSViewHeightSizable, NSViewWidthSizable,
-        NSWindowCollectionBehavior,
+        CGFloat, NSEventModifierFlags, NSMainMenuWindowLevel, NSView, NSViewHeightSizable,
+        NSViewWidthSizable, NSWindowCollectionBehavior,
     },
     base::{id, nil, BOOL, NO, YES},
-    foundation::{NSPoint, NSRect},
+    foundation::{NSPoint, NSRect, NSString},
 };
 use objc::{
     class,
@@ -24,6 +30,8 @@ use objc::{
 };
 use objc_foundation::INSObject;
 
+use crate::trace::{self, TraceKind};
+
 #[link(name = "Foundation", kind = "framework")]
 extern "C" {
     pub fn NSMouseInRect(aPoint: NSPoint, aRect: NSRect, flipped: BOOL) -> BOOL;
@@ -41,6 +49,15 @@ bitflags! {
 #[derive(Default)]
 pub struct Store {
     panel: Option<ShareId<RawNSPanel>>,
+    webview: Option<ShareId<RawWKWebView>>,
+    shortcut_failures: u32,
+    activation_delay: Duration,
+    shortcut: Option<String>,
+    placement: Placement,
+    /// Set once the shortcut was handed to a new instance, the watchdog must leave it alone
+    handed_off: bool,
+    /// Set once the app is quitting or relaunching, the panel's window mustn't come back
+    exiting: bool,
 }
 
 #[derive(Default)]
@@ -102,6 +119,22 @@ macro_rules! panel {
     }};
 }
 
+#[macro_export]
+macro_rules! webview {
+    ($app_handle:expr) => {{
+        let handle = $app_handle.app_handle();
+        let webview = handle
+            .state::<$crate::spotlight::State>()
+            .0
+            .lock()
+            .unwrap()
+            .webview
+            .clone();
+
+        webview.unwrap()
+    }};
+}
+
 #[macro_export]
 macro_rules! nsstring_to_string {
     ($ns_string:expr) => {{
@@ -123,66 +156,506 @@ macro_rules! nsstring_to_string {
     }};
 }
 
+/// Turns an optional NSError, as handed to completion handlers, into a result
+pub(crate) fn ns_error_to_result(error: id) -> Result<(), String> {
+    if error == nil {
+        return Ok(());
+    }
+
+    let description: id = unsafe { msg_send![error, localizedDescription] };
+    Err(nsstring_to_string!(description).unwrap_or_default())
+}
+
+/// Runs a closure on the main thread and waits for its result
+pub(crate) async fn on_main_thread<T, F>(app_handle: &AppHandle<Wry>, f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (tx, mut rx) = async_runtime::channel(1);
+
+    app_handle
+        .run_on_main_thread(move || {
+            let _ = tx.try_send(f());
+        })
+        .map_err(|e| e.to_string())?;
+
+    rx.recv()
+        .await
+        .ok_or_else(|| "Main thread task was dropped".to_string())
+}
+
 static INIT: Once = Once::new();
 static PANEL_LABEL: &str = "main";
+pub(crate) static DEFAULT_SHORTCUT: &str = "Cmd+k";
+static SHORTCUT_LOST_EVENT: &str = "spotlight://shortcut-lost";
+const SHORTCUT_WATCHDOG_INTERVAL: Duration = Duration::from_secs(30);
+const MODIFIER_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+const WORKSPACE_DID_ACTIVATE_APPLICATION_NOTIFICATION: &str =
+    "NSWorkspaceDidActivateApplicationNotification";
+/// NSWorkspaceApplicationKey
+const WORKSPACE_APPLICATION_KEY: &str = "NSWorkspaceApplicationKey";
+
+/// Where the panel goes on the monitor with the cursor each time it's toggled
+#[derive(Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Placement {
+    #[default]
+    Center,
+    /// Horizontally centered with its top a fifth of the way down, like Spotlight
+    Top,
+}
 
+/// Turns the window into the spotlight panel. Called by the frontend on every load, only the
+/// first call for a given window does anything.
 #[tauri::command]
 pub fn init_spotlight_window(app_handle: AppHandle<Wry>, window: Window<Wry>) {
+    if get_state!(app_handle, panel, is_some) {
+        return;
+    }
+
+    set_state!(app_handle, panel, Some(create_spotlight_panel(&window)));
+    store_webview(&window);
+    recreate_window_on_destroy(&window);
+
+    // The shortcut handler holds on to the window, a recreated one needs a fresh registration
+    let _ = app_handle
+        .global_shortcut_manager()
+        .unregister(&shortcut(&app_handle));
+    // Another app, or the instance being replaced, can still hold it; the watchdog retries
+    if register_shortcut(app_handle.clone()).is_err() {
+        report_lost_shortcut(&app_handle, false);
+    }
+
     INIT.call_once(|| {
-        set_state!(app_handle, panel, Some(create_spotlight_panel(&window)));
-        register_shortcut(app_handle);
+        crate::macos_version::warn_if_untested(&app_handle);
+        crate::architecture::warn_if_translated(&app_handle);
+        crate::settings::warn_if_invalid(&app_handle);
+        crate::shortcut_help::install_shortcut_help(app_handle.clone());
+        watch_app_activation(app_handle.clone());
+        watch_shortcut(app_handle);
     });
 }
 
-fn register_shortcut(app_handle: AppHandle<Wry>) {
-    let mut shortcut_manager = app_handle.global_shortcut_manager();
-    let window = app_handle.get_window(PANEL_LABEL).unwrap();
+/// Restores auto hide suspended with `suspend_auto_hide_until_key` when another app becomes
+/// active, so it doesn't stay off if the panel never becomes key again. The panel isn't hidden
+/// there, system UI like the Touch ID prompt runs in its own process.
+fn watch_app_activation(app_handle: AppHandle<Wry>) {
+    let on_activate = ConcreteBlock::new(move |notification: id| {
+        if !get_state!(app_handle, panel, is_some) {
+            return;
+        }
 
-    let panel = panel!(app_handle);
-    shortcut_manager
-        .register("Cmd+k", move || {
-            position_window_at_the_center_of_the_monitor_with_cursor(&window);
-
-            if panel.is_visible() {
-                hide_spotlight(window.app_handle());
-            } else {
-                show_spotlight(window.app_handle());
-            };
+        let pid: i32 = unsafe {
+            let user_info: id = msg_send![notification, userInfo];
+            let key = NSString::alloc(nil).init_str(WORKSPACE_APPLICATION_KEY);
+            let key: id = msg_send![key, autorelease];
+            let app: id = msg_send![user_info, objectForKey: key];
+            msg_send![app, processIdentifier]
+        };
+
+        if pid != std::process::id() as i32 {
+            panel!(app_handle).restore_auto_hide();
+        }
+    })
+    .copy();
+
+    unsafe {
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let center: id = msg_send![workspace, notificationCenter];
+        let queue: id = msg_send![class!(NSOperationQueue), mainQueue];
+        let name = NSString::alloc(nil).init_str(WORKSPACE_DID_ACTIVATE_APPLICATION_NOTIFICATION);
+        let name: id = msg_send![name, autorelease];
+
+        let _: id = msg_send![
+            center,
+            addObserverForName: name
+            object: nil
+            queue: queue
+            usingBlock: &*on_activate
+        ];
+    }
+}
+
+/// Keeps a reference to the panel's WKWebView so native code can reach it later
+fn store_webview(window: &Window<Wry>) {
+    let app_handle = window.app_handle();
+
+    window
+        .with_webview(move |webview| {
+            let webview = unsafe { ShareId::from_ptr(webview.inner() as *mut RawWKWebView) };
+            crate::text_input::install_text_input_client(&app_handle, webview.as_id());
+            crate::webview::install_crash_recovery(app_handle.clone(), webview.as_id());
+            set_state!(app_handle, webview, Some(webview));
+            crate::configure::apply_pending_config(&app_handle);
         })
         .unwrap();
 }
 
+/// Releases the toggle shortcut, resets the panel's placement and activation delay, and closes
+/// its window. The window is then recreated from its config and initialized again by its
+/// frontend, with the shortcut currently set.
+pub(crate) fn teardown_panel(app_handle: &AppHandle<Wry>) {
+    trace::record(
+        TraceKind::Shortcut,
+        format!("released {} to rebuild the panel", shortcut(app_handle)),
+    );
+    let _ = app_handle
+        .global_shortcut_manager()
+        .unregister(&shortcut(app_handle));
+
+    panel!(app_handle).order_out(None);
+
+    {
+        let state = app_handle.state::<State>();
+        let mut store = state.0.lock().unwrap();
+        store.shortcut_failures = 0;
+        store.activation_delay = Duration::ZERO;
+        store.placement = Placement::default();
+    }
+
+    if let Some(window) = app_handle.get_window(PANEL_LABEL) {
+        if let Err(error) = window.close() {
+            crate::diagnostics::warn(
+                app_handle,
+                "panelCloseFailed",
+                format!("Unable to close the spotlight window: {}", error),
+            );
+        }
+    }
+}
+
+/// Creates the panel's window again from its config if it's ever destroyed, so the app doesn't
+/// keep running headless with a dead shortcut. The new window's frontend runs the
+/// initialization again.
+fn recreate_window_on_destroy(window: &Window<Wry>) {
+    let app_handle = window.app_handle();
+
+    window.on_window_event(move |event| {
+        if !matches!(event, WindowEvent::Destroyed) || get_state!(app_handle, exiting) {
+            return;
+        }
+
+        set_state!(app_handle, panel, None);
+        set_state!(app_handle, webview, None);
+
+        let config = app_handle
+            .config()
+            .tauri
+            .windows
+            .iter()
+            .find(|config| config.label == PANEL_LABEL)
+            .cloned();
+
+        if let Some(config) = config {
+            if let Err(error) = WindowBuilder::from_config(&app_handle, config).build() {
+                crate::diagnostics::warn(
+                    &app_handle,
+                    "panelRecreateFailed",
+                    format!("Unable to recreate the spotlight window: {}", error),
+                );
+            }
+        }
+    });
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NativeHandles {
+    ns_window: usize,
+    window_number: isize,
+    webview: usize,
+}
+
+/// Returns raw pointers to the panel's NSWindow and WKWebView along with its window number.
+///
+/// This is meant for advanced users embedding additional native views (Metal layers,
+/// NSTextFinder, ...). The pointers are only valid while the panel is alive and must only be
+/// messaged from the main thread. Nothing here is memory safe; you're on your own.
+#[tauri::command]
+pub fn get_native_handles(app_handle: AppHandle<Wry>) -> NativeHandles {
+    let panel = panel!(app_handle);
+    let webview = webview!(app_handle);
+
+    NativeHandles {
+        ns_window: &*panel as *const RawNSPanel as usize,
+        window_number: panel.window_number(),
+        webview: webview.as_id() as usize,
+    }
+}
+
+/// The shortcut toggling the panel
+pub(crate) fn shortcut(app_handle: &AppHandle<Wry>) -> String {
+    get_state!(app_handle, shortcut, clone).unwrap_or_else(|| DEFAULT_SHORTCUT.to_string())
+}
+
+/// Replaces the shortcut toggling the panel. The new one is registered before the current one is
+/// released, which is kept if registration fails.
+pub(crate) fn set_shortcut(app_handle: &AppHandle<Wry>, shortcut: &str) -> Result<(), String> {
+    let current = self::shortcut(app_handle);
+    if current == shortcut {
+        return Ok(());
+    }
+
+    register_shortcut_with(app_handle.clone(), shortcut).map_err(|e| e.to_string())?;
+
+    let _ = app_handle.global_shortcut_manager().unregister(&current);
+    trace::record(
+        TraceKind::Shortcut,
+        format!("replaced {} with {}", current, shortcut),
+    );
+    set_state!(app_handle, shortcut, Some(shortcut.to_string()));
+    set_state!(app_handle, shortcut_failures, 0);
+
+    Ok(())
+}
+
+fn register_shortcut(app_handle: AppHandle<Wry>) -> tauri::Result<()> {
+    let shortcut = shortcut(&app_handle);
+    register_shortcut_with(app_handle, &shortcut)
+}
+
+fn register_shortcut_with(app_handle: AppHandle<Wry>, shortcut: &str) -> tauri::Result<()> {
+    let mut shortcut_manager = app_handle.global_shortcut_manager();
+    let handle = app_handle.clone();
+
+    shortcut_manager.register(shortcut, move || {
+        trace::record(TraceKind::Shortcut, "pressed");
+        toggle_spotlight(&handle)
+    })
+}
+
+/// Shows the panel on the monitor with the cursor, or hides it if it's visible. Must be called
+/// on the main thread.
+pub(crate) fn toggle_spotlight(app_handle: &AppHandle<Wry>) {
+    let (window, panel) = match (
+        app_handle.get_window(PANEL_LABEL),
+        get_state!(app_handle, panel, clone),
+    ) {
+        (Some(window), Some(panel)) => (window, panel),
+        _ => return,
+    };
+
+    position_window_on_the_monitor_with_cursor(&window, get_state!(app_handle, placement));
+
+    if panel.is_visible() {
+        hide_spotlight(app_handle.clone());
+    } else {
+        show_spotlight(app_handle.clone());
+    };
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ShortcutLostPayload {
+    shortcut: String,
+    failures: u32,
+    reregistered: bool,
+}
+
+/// Periodically checks that the toggle shortcut is still registered, as it can silently drop
+/// after certain system events, and registers it again when it's gone
+fn watch_shortcut(app_handle: AppHandle<Wry>) {
+    thread::spawn(move || loop {
+        thread::sleep(SHORTCUT_WATCHDOG_INTERVAL);
+        check_shortcut(&app_handle);
+    });
+}
+
+/// Registers the toggle shortcut again and emits `spotlight://shortcut-lost` if it's gone
+pub(crate) fn check_shortcut(app_handle: &AppHandle<Wry>) {
+    if get_state!(app_handle, handed_off) {
+        return;
+    }
+
+    let shortcut = shortcut(app_handle);
+    let registered = app_handle
+        .global_shortcut_manager()
+        .is_registered(&shortcut)
+        .unwrap_or(false);
+
+    if registered {
+        return;
+    }
+
+    let reregistered = register_shortcut(app_handle.clone()).is_ok();
+    report_lost_shortcut(app_handle, reregistered);
+}
+
+/// Releases the toggle shortcut for good, for a new instance to take over. The watchdog won't
+/// register it again.
+pub(crate) fn release_shortcut_for_handoff(app_handle: &AppHandle<Wry>) {
+    set_state!(app_handle, handed_off, true);
+
+    let _ = app_handle
+        .global_shortcut_manager()
+        .unregister(&shortcut(app_handle));
+}
+
+/// Keeps the panel's window from being recreated when it's destroyed, to be called before the app
+/// exits or restarts
+pub(crate) fn prepare_for_exit(app_handle: &AppHandle<Wry>) {
+    set_state!(app_handle, exiting, true);
+}
+
+/// Registers the toggle shortcut once the instance it was handed off from let go of it. Must be
+/// called on the main thread.
+pub(crate) fn reclaim_shortcut(app_handle: &AppHandle<Wry>) {
+    let shortcut = shortcut(app_handle);
+    let registered = app_handle
+        .global_shortcut_manager()
+        .is_registered(&shortcut)
+        .unwrap_or(false);
+
+    if !registered && register_shortcut(app_handle.clone()).is_ok() {
+        trace::record(
+            TraceKind::Shortcut,
+            format!("registered {} after a handoff", shortcut),
+        );
+    }
+}
+
+/// Counts a failure to hold the toggle shortcut and emits `spotlight://shortcut-lost`
+fn report_lost_shortcut(app_handle: &AppHandle<Wry>, reregistered: bool) {
+    let failures = get_state!(app_handle, shortcut_failures) + 1;
+    set_state!(app_handle, shortcut_failures, failures);
+
+    let payload = ShortcutLostPayload {
+        shortcut: shortcut(app_handle),
+        failures,
+        reregistered,
+    };
+    trace::record(
+        TraceKind::Shortcut,
+        format!(
+            "lost {} ({} times), registered again: {}",
+            payload.shortcut, payload.failures, payload.reregistered
+        ),
+    );
+
+    let _ = crate::events::emit(app_handle, SHORTCUT_LOST_EVENT, payload);
+}
+
+/// Shows the panel, unless in presentation mode
 #[tauri::command]
 pub fn show_spotlight(app_handle: AppHandle<Wry>) {
-    panel!(app_handle).show();
+    if crate::presentation_mode::is_active(&app_handle) {
+        return;
+    }
+
+    crate::focus::remember_focus_target(&app_handle);
+    trace::record(TraceKind::Show, "");
+
+    let panel = panel!(app_handle);
+    crate::presentation::adapt_to_app_behind(&panel);
+    panel.show();
+
+    let activation_delay = get_state!(app_handle, activation_delay);
+    if activation_delay.is_zero() {
+        focus_webview(&app_handle);
+        return;
+    }
+
+    // Keep key events away from the webview until the modifiers used to trigger the panel are
+    // released, otherwise they bleed into the first typed character
+    panel.make_first_responder(None);
+
+    thread::spawn(move || {
+        let deadline = Instant::now() + crate::architecture::adjusted_timing(activation_delay);
+
+        while modifiers_pressed() && Instant::now() < deadline {
+            thread::sleep(MODIFIER_POLL_INTERVAL);
+        }
+
+        let handle = app_handle.clone();
+        app_handle
+            .run_on_main_thread(move || focus_webview(&handle))
+            .unwrap();
+    });
+}
+
+/// Sets the maximum time to wait for held modifiers to be released before focusing the webview
+/// when the panel is shown. `0` focuses it immediately.
+#[tauri::command]
+pub fn set_activation_delay(app_handle: AppHandle<Wry>, delay_ms: u64) {
+    set_state!(
+        app_handle,
+        activation_delay,
+        Duration::from_millis(delay_ms)
+    );
+}
+
+/// Routes key events to the webview itself rather than its container so that input methods talk
+/// to the WKWebView's text input client
+fn focus_webview(app_handle: &AppHandle<Wry>) {
+    panel!(app_handle).make_first_responder(Some(webview!(app_handle).as_id()));
+}
+
+fn modifiers_pressed() -> bool {
+    let flags: NSEventModifierFlags = unsafe { msg_send![class!(NSEvent), modifierFlags] };
+    flags.intersects(
+        NSEventModifierFlags::NSShiftKeyMask
+            | NSEventModifierFlags::NSControlKeyMask
+            | NSEventModifierFlags::NSAlternateKeyMask
+            | NSEventModifierFlags::NSCommandKeyMask,
+    )
 }
 
 #[tauri::command]
 pub fn hide_spotlight(app_handle: AppHandle<Wry>) {
+    trace::record(TraceKind::Hide, "");
     panel!(app_handle).order_out(None);
+    crate::focus::restore_focus(&app_handle);
 }
 
 /// Positions a given window at the center of the monitor with cursor
-fn position_window_at_the_center_of_the_monitor_with_cursor(window: &Window<Wry>) {
+pub(crate) fn position_window_at_the_center_of_the_monitor_with_cursor(window: &Window<Wry>) {
+    position_window_on_the_monitor_with_cursor(window, Placement::Center);
+}
+
+/// Positions a given window on the monitor with cursor
+pub(crate) fn position_window_on_the_monitor_with_cursor(
+    window: &Window<Wry>,
+    placement: Placement,
+) {
     if let Some(monitor) = get_monitor_with_cursor() {
         let display_size = monitor.size.to_logical::<f64>(monitor.scale_factor);
         let display_pos = monitor.position.to_logical::<f64>(monitor.scale_factor);
 
         let handle: id = window.ns_window().unwrap() as _;
         let win_frame: NSRect = unsafe { handle.frame() };
+        let y = match placement {
+            Placement::Center => {
+                (display_pos.y + (display_size.height / 2.0)) - (win_frame.size.height / 2.0)
+            }
+            Placement::Top => {
+                (display_pos.y + (display_size.height * 4.0 / 5.0)) - win_frame.size.height
+            }
+        };
         let rect = NSRect {
             origin: NSPoint {
                 x: (display_pos.x + (display_size.width / 2.0)) - (win_frame.size.width / 2.0),
-                y: (display_pos.y + (display_size.height / 2.0)) - (win_frame.size.height / 2.0),
+                y,
             },
             size: win_frame.size,
         };
+        trace::record(
+            TraceKind::Frame,
+            format!(
+                "({}, {}) {}x{} on {}",
+                rect.origin.x,
+                rect.origin.y,
+                rect.size.width,
+                rect.size.height,
+                monitor.name.as_deref().unwrap_or("unnamed monitor")
+            ),
+        );
         let _: () = unsafe { msg_send![handle, setFrame: rect display: YES] };
     }
 }
 
 struct Monitor {
-    #[allow(dead_code)]
     pub name: Option<String>,
     pub size: PhysicalSize<u32>,
     pub position: PhysicalPosition<i32>,
@@ -257,6 +730,7 @@ impl RawNSPanel {
 
         unsafe {
             cls.add_ivar::<BOOL>("_autoHide");
+            cls.add_ivar::<BOOL>("_restoreAutoHideOnKey");
 
             cls.add_method(
                 sel!(canBecomeKeyWindow),
@@ -272,6 +746,16 @@ impl RawNSPanel {
                 sel!(setAutoHide:),
                 Self::_set_auto_hide as extern "C" fn(&mut Object, Sel, BOOL),
             );
+
+            cls.add_method(
+                sel!(restoreAutoHideOnKey),
+                Self::_get_restore_auto_hide_on_key as extern "C" fn(&mut Object, Sel) -> BOOL,
+            );
+
+            cls.add_method(
+                sel!(setRestoreAutoHideOnKey:),
+                Self::_set_restore_auto_hide_on_key as extern "C" fn(&mut Object, Sel, BOOL),
+            );
         }
 
         cls.register()
@@ -285,6 +769,14 @@ impl RawNSPanel {
         unsafe { this.set_ivar("_autoHide", value) };
     }
 
+    extern "C" fn _get_restore_auto_hide_on_key(this: &mut Object, _: Sel) -> BOOL {
+        unsafe { *this.get_ivar("_restoreAutoHideOnKey") }
+    }
+
+    extern "C" fn _set_restore_auto_hide_on_key(this: &mut Object, _: Sel, value: BOOL) {
+        unsafe { this.set_ivar("_restoreAutoHideOnKey", value) };
+    }
+
     /// Returns YES to ensure that RawNSPanel can become a key window
     extern "C" fn can_become_key_window(_: &Object, _: Sel) -> BOOL {
         YES
@@ -293,18 +785,26 @@ impl RawNSPanel {
 unsafe impl Message for RawNSPanel {}
 
 impl RawNSPanel {
+    pub(crate) fn frame(&self) -> NSRect {
+        unsafe { msg_send![self, frame] }
+    }
+
     fn show(&self) {
         self.make_first_responder(Some(self.content_view()));
         self.order_front_regardless();
         self.make_key_window();
     }
 
-    fn is_visible(&self) -> bool {
+    pub(crate) fn window_number(&self) -> isize {
+        unsafe { msg_send![self, windowNumber] }
+    }
+
+    pub(crate) fn is_visible(&self) -> bool {
         let flag: BOOL = unsafe { msg_send![self, isVisible] };
         flag == YES
     }
 
-    fn make_key_window(&self) {
+    pub(crate) fn make_key_window(&self) {
         let _: () = unsafe { msg_send![self, makeKeyWindow] };
     }
 
@@ -316,7 +816,7 @@ impl RawNSPanel {
         let _: () = unsafe { msg_send![self, orderOut: sender.unwrap_or(nil)] };
     }
 
-    fn content_view(&self) -> id {
+    pub(crate) fn content_view(&self) -> id {
         unsafe { msg_send![self, contentView] }
     }
 
@@ -329,18 +829,45 @@ impl RawNSPanel {
     }
 
     fn set_level(&self, level: i32) {
+        trace::record(TraceKind::Level, level.to_string());
         let _: () = unsafe { msg_send![self, setLevel: level] };
     }
 
-    fn set_auto_hide(&self, value: bool) {
+    pub(crate) fn set_auto_hide(&self, value: bool) {
         let _: () = unsafe { msg_send![self, setAutoHide: value] };
     }
 
+    fn auto_hide(&self) -> bool {
+        let flag: BOOL = unsafe { msg_send![self, autoHide] };
+        flag == YES
+    }
+
+    /// Suspends auto hide until the panel becomes key again, or another app becomes active.
+    ///
+    /// Use this before presenting system UI (pickers, sheets, prompts) that takes key status away
+    /// from the panel, so it isn't hidden while the user interacts with it.
+    pub(crate) fn suspend_auto_hide_until_key(&self) {
+        if self.auto_hide() {
+            self.set_auto_hide(false);
+            let _: () = unsafe { msg_send![self, setRestoreAutoHideOnKey: YES] };
+        }
+    }
+
+    /// Turns auto hide back on if it was suspended with `suspend_auto_hide_until_key`
+    fn restore_auto_hide(&self) {
+        let restore_auto_hide: BOOL = unsafe { msg_send![self, restoreAutoHideOnKey] };
+
+        if restore_auto_hide == YES {
+            let _: () = unsafe { msg_send![self, setRestoreAutoHideOnKey: NO] };
+            self.set_auto_hide(true);
+        }
+    }
+
     fn set_style_mask(&self, style_mask: i32) {
         let _: () = unsafe { msg_send![self, setStyleMask: style_mask] };
     }
 
-    fn set_collection_behaviour(&self, behaviour: NSWindowCollectionBehavior) {
+    pub(crate) fn set_collection_behaviour(&self, behaviour: NSWindowCollectionBehavior) {
         let _: () = unsafe { msg_send![self, setCollectionBehavior: behaviour] };
     }
 
@@ -368,6 +895,17 @@ impl INSObject for RawNSPanel {
     }
 }
 
+/// Tauri's WKWebView hosted inside the panel
+pub struct RawWKWebView;
+
+unsafe impl Message for RawWKWebView {}
+
+impl RawWKWebView {
+    pub(crate) fn as_id(&self) -> id {
+        self as *const Self as id
+    }
+}
+
 #[allow(dead_code)]
 const DELEGATE_CLS_NAME: &str = "RawNSPanelDelegate";
 
@@ -415,7 +953,16 @@ impl RawNSPanelDelegate {
         unsafe { this.set_ivar("panel", panel) };
     }
 
-    extern "C" fn window_did_become_key(_: &Object, _: Sel, _: id) {}
+    /// Restore auto hide if it was suspended while the panel wasn't key
+    extern "C" fn window_did_become_key(this: &Object, _: Sel, _: id) {
+        let panel: id = unsafe { *this.get_ivar("panel") };
+        let restore_auto_hide: BOOL = unsafe { msg_send![panel, restoreAutoHideOnKey] };
+
+        if restore_auto_hide == YES {
+            let _: () = unsafe { msg_send![panel, setRestoreAutoHideOnKey: NO] };
+            let _: () = unsafe { msg_send![panel, setAutoHide: YES] };
+        }
+    }
 
     /// Hide panel when it's no longer the key window and auto hide is enabled
     extern "C" fn window_did_resign_key(this: &Object, _: Sel, _: id) {
@@ -455,14 +1002,16 @@ fn create_spotlight_panel(window: &Window<Wry>) -> ShareId<RawNSPanel> {
     panel.set_auto_hide(true);
 
     // Ensure that the panel can display over the top of fullscreen apps
-    panel.set_collection_behaviour(
-        NSWindowCollectionBehavior::NSWindowCollectionBehaviorTransient
-            | NSWindowCollectionBehavior::NSWindowCollectionBehaviorMoveToActiveSpace
-            | NSWindowCollectionBehavior::NSWindowCollectionBehaviorFullScreenAuxiliary,
-    );
+    panel.set_collection_behaviour(crate::presentation::collection_behaviour(false));
 
     // Ensures panel does not activate
-    panel.set_style_mask(NSWindowStyleMaskNonActivatingPanel);
+    let app_handle = window.app_handle();
+    if crate::feature_flags::is_enabled(
+        &app_handle,
+        crate::feature_flags::FeatureFlag::NonActivatingPanel,
+    ) {
+        panel.set_style_mask(NSWindowStyleMaskNonActivatingPanel);
+    }
 
     // Setup delegate for an NSPanel to listen for window resign key and hide the panel
     let delegate = RawNSPanelDelegate::new();
@@ -0,0 +1,375 @@
+use std::{
+    ffi::{c_char, c_void, CString},
+    mem,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use cocoa::{
+    base::{id, nil, YES},
+    foundation::NSString,
+};
+use objc::{class, msg_send, rc::autoreleasepool, sel, sel_impl};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{nsstring_to_string, spotlight::ns_error_to_result};
+
+extern "C" {
+    static mach_task_self_: u32;
+    static vm_page_size: usize;
+
+    fn mach_host_self() -> u32;
+    fn mach_port_deallocate(task: u32, name: u32) -> i32;
+    fn host_statistics(host: u32, flavor: i32, info: *mut i32, count: *mut u32) -> i32;
+    fn host_statistics64(host: u32, flavor: i32, info: *mut i32, count: *mut u32) -> i32;
+    fn sysctlbyname(
+        name: *const c_char,
+        old: *mut c_void,
+        old_len: *mut usize,
+        new: *mut c_void,
+        new_len: usize,
+    ) -> i32;
+}
+
+#[link(name = "Foundation", kind = "framework")]
+extern "C" {
+    static NSURLVolumeTotalCapacityKey: id;
+    static NSURLVolumeAvailableCapacityForImportantUsageKey: id;
+}
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOPSCopyPowerSourcesInfo() -> *const c_void;
+    fn IOPSCopyPowerSourcesList(info: *const c_void) -> *const c_void;
+    fn IOPSGetPowerSourceDescription(info: *const c_void, source: id) -> id;
+}
+
+/// KERN_SUCCESS
+const KERN_SUCCESS: i32 = 0;
+
+/// HOST_CPU_LOAD_INFO
+const HOST_CPU_LOAD_INFO: i32 = 3;
+/// HOST_VM_INFO64
+const HOST_VM_INFO64: i32 = 4;
+
+/// CPU_STATE_USER, CPU_STATE_SYSTEM, CPU_STATE_IDLE and CPU_STATE_NICE index `cpu_ticks`
+const CPU_STATE_IDLE: usize = 2;
+
+/// host_cpu_load_info
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct CpuLoadInfo {
+    cpu_ticks: [u32; 4],
+}
+
+/// vm_statistics64
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)]
+struct VmStatistics64 {
+    free_count: u32,
+    active_count: u32,
+    inactive_count: u32,
+    wire_count: u32,
+    zero_fill_count: u64,
+    reactivations: u64,
+    pageins: u64,
+    pageouts: u64,
+    faults: u64,
+    cow_faults: u64,
+    lookups: u64,
+    hits: u64,
+    purges: u64,
+    purgeable_count: u32,
+    speculative_count: u32,
+    decompressions: u64,
+    compressions: u64,
+    swapins: u64,
+    swapouts: u64,
+    compressor_page_count: u32,
+    throttled_count: u32,
+    external_page_count: u32,
+    internal_page_count: u32,
+    total_uncompressed_pages_in_compressor: u64,
+}
+
+/// struct timeval
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)]
+struct TimeVal {
+    tv_sec: i64,
+    tv_usec: i32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemSnapshot {
+    /// Percentage of CPU time spent busy, across all cores, since the previous snapshot
+    cpu_usage: f64,
+    cpu_count: u32,
+    memory: Memory,
+    disk: Disk,
+    /// Seconds since the system booted
+    uptime: u64,
+    /// `None` on Macs without a battery
+    battery: Option<Battery>,
+}
+
+/// Sizes in bytes
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Memory {
+    total: u64,
+    /// App, wired and compressed memory, as shown by Activity Monitor
+    used: u64,
+    compressed: u64,
+}
+
+/// Sizes in bytes, of the startup volume
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Disk {
+    total: u64,
+    /// Space available for important data, including purgeable space, like Finder shows
+    available: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Battery {
+    /// 0 to 100
+    level: f64,
+    charging: bool,
+    plugged_in: bool,
+    /// Minutes until empty or full, `None` while macOS is still estimating
+    time_remaining: Option<u32>,
+}
+
+/// CPU ticks of the previous snapshot, usage is measured between two snapshots
+#[derive(Default)]
+pub struct State(Mutex<Option<CpuLoadInfo>>);
+
+/// Returns CPU, memory, disk, uptime and battery figures without shelling out to `top` or
+/// `pmset`.
+///
+/// CPU usage is measured since the previous call, the first call returns the average since boot.
+#[tauri::command]
+pub fn get_system_snapshot(app_handle: AppHandle<Wry>) -> Result<SystemSnapshot, String> {
+    let cpu_usage = {
+        let state = app_handle.state::<State>();
+        let mut previous = state.0.lock().unwrap();
+
+        let current = cpu_load_info()?;
+        let usage = cpu_usage(&previous.unwrap_or_default(), &current);
+        *previous = Some(current);
+
+        usage
+    };
+
+    Ok(SystemSnapshot {
+        cpu_usage,
+        cpu_count: sysctl::<u32>("hw.ncpu")?,
+        memory: memory()?,
+        disk: disk()?,
+        uptime: uptime()?,
+        battery: battery(),
+    })
+}
+
+fn cpu_usage(previous: &CpuLoadInfo, current: &CpuLoadInfo) -> f64 {
+    let ticks: Vec<u64> = current
+        .cpu_ticks
+        .iter()
+        .zip(previous.cpu_ticks)
+        .map(|(current, previous)| current.wrapping_sub(previous) as u64)
+        .collect();
+
+    let total: u64 = ticks.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+
+    (total - ticks[CPU_STATE_IDLE]) as f64 / total as f64 * 100.0
+}
+
+fn cpu_load_info() -> Result<CpuLoadInfo, String> {
+    let mut info = CpuLoadInfo::default();
+    host_info(HOST_CPU_LOAD_INFO, &mut info, false)?;
+    Ok(info)
+}
+
+fn memory() -> Result<Memory, String> {
+    let mut statistics = VmStatistics64::default();
+    host_info(HOST_VM_INFO64, &mut statistics, true)?;
+
+    let page_size = unsafe { vm_page_size } as u64;
+    // Anonymous memory minus what's purgeable is what Activity Monitor calls app memory
+    let app =
+        (statistics.internal_page_count as u64).saturating_sub(statistics.purgeable_count as u64);
+    let wired = statistics.wire_count as u64;
+    let compressed = statistics.compressor_page_count as u64;
+
+    Ok(Memory {
+        total: sysctl::<u64>("hw.memsize")?,
+        used: (app + wired + compressed) * page_size,
+        compressed: compressed * page_size,
+    })
+}
+
+/// Fills `info` with `host_statistics` (or `host_statistics64`) for `flavor`
+fn host_info<T>(flavor: i32, info: &mut T, is_64: bool) -> Result<(), String> {
+    let mut count = (mem::size_of::<T>() / mem::size_of::<i32>()) as u32;
+
+    let result = unsafe {
+        let host = mach_host_self();
+        let info = info as *mut T as *mut i32;
+        let result = if is_64 {
+            host_statistics64(host, flavor, info, &mut count)
+        } else {
+            host_statistics(host, flavor, info, &mut count)
+        };
+        mach_port_deallocate(mach_task_self_, host);
+
+        result
+    };
+
+    if result != KERN_SUCCESS {
+        return Err(format!("Unable to read host statistics ({})", result));
+    }
+
+    Ok(())
+}
+
+fn disk() -> Result<Disk, String> {
+    autoreleasepool(|| unsafe {
+        let path = NSString::alloc(nil).init_str("/");
+        let path: id = msg_send![path, autorelease];
+        let url: id = msg_send![class!(NSURL), fileURLWithPath: path isDirectory: YES];
+
+        let keys: id = msg_send![class!(NSMutableArray), array];
+        let _: () = msg_send![keys, addObject: NSURLVolumeTotalCapacityKey];
+        let _: () = msg_send![keys, addObject: NSURLVolumeAvailableCapacityForImportantUsageKey];
+
+        let mut error: id = nil;
+        let values: id = msg_send![url, resourceValuesForKeys: keys error: &mut error];
+
+        if values == nil {
+            ns_error_to_result(error)?;
+        }
+
+        let total: id = msg_send![values, objectForKey: NSURLVolumeTotalCapacityKey];
+        let available: id = msg_send![
+            values,
+            objectForKey: NSURLVolumeAvailableCapacityForImportantUsageKey
+        ];
+
+        Ok(Disk {
+            total: msg_send![total, unsignedLongLongValue],
+            available: msg_send![available, unsignedLongLongValue],
+        })
+    })
+}
+
+/// Time since boot including sleep, like `uptime`
+fn uptime() -> Result<u64, String> {
+    let boot_time = sysctl::<TimeVal>("kern.boottime")?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|error| error.to_string())?;
+
+    Ok(now.as_secs().saturating_sub(boot_time.tv_sec as u64))
+}
+
+/// The internal battery's state, read from IOKit's power sources
+fn battery() -> Option<Battery> {
+    autoreleasepool(|| unsafe {
+        let info = IOPSCopyPowerSourcesInfo();
+        if info.is_null() {
+            return None;
+        }
+        let _: id = msg_send![info as id, autorelease];
+
+        let sources = IOPSCopyPowerSourcesList(info) as id;
+        if sources == nil {
+            return None;
+        }
+        let sources: id = msg_send![sources, autorelease];
+        let count: usize = msg_send![sources, count];
+
+        (0..count).find_map(|index| {
+            let source: id = msg_send![sources, objectAtIndex: index];
+            to_battery(IOPSGetPowerSourceDescription(info, source))
+        })
+    })
+}
+
+/// Reads a power source description, `None` unless it's the internal battery
+fn to_battery(description: id) -> Option<Battery> {
+    if description == nil {
+        return None;
+    }
+
+    let value = |key: &str| -> id {
+        unsafe {
+            let key = NSString::alloc(nil).init_str(key);
+            let key: id = msg_send![key, autorelease];
+            msg_send![description, objectForKey: key]
+        }
+    };
+    let number = |key: &str| -> Option<i64> {
+        let number = value(key);
+        (number != nil).then(|| unsafe { msg_send![number, longLongValue] })
+    };
+
+    let kind = value("Type");
+    if nsstring_to_string!(kind).as_deref() != Some("InternalBattery") {
+        return None;
+    }
+
+    let capacity = number("Current Capacity")? as f64;
+    let max_capacity = number("Max Capacity").unwrap_or(100).max(1) as f64;
+    let charging = number("Is Charging") == Some(1);
+    let power_source = value("Power Source State");
+    let plugged_in = nsstring_to_string!(power_source).as_deref() == Some("AC Power");
+
+    // -1 while macOS is still estimating
+    let time_remaining = number(if charging {
+        "Time to Full Charge"
+    } else {
+        "Time to Empty"
+    })
+    .filter(|minutes| *minutes >= 0)
+    .map(|minutes| minutes as u32);
+
+    Some(Battery {
+        level: capacity / max_capacity * 100.0,
+        charging,
+        plugged_in,
+        time_remaining,
+    })
+}
+
+/// Reads a fixed size sysctl value by name
+pub(crate) fn sysctl<T: Default>(name: &str) -> Result<T, String> {
+    let c_name = CString::new(name).map_err(|error| error.to_string())?;
+    let mut value = T::default();
+    let mut length = mem::size_of::<T>();
+
+    let result = unsafe {
+        sysctlbyname(
+            c_name.as_ptr(),
+            &mut value as *mut T as *mut c_void,
+            &mut length,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+
+    if result != 0 {
+        return Err(format!("Unable to read {}", name));
+    }
+
+    Ok(value)
+}
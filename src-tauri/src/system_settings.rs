@@ -0,0 +1,182 @@
+use cocoa::{
+    base::{id, nil, BOOL, NO, YES},
+    foundation::NSString,
+};
+use objc::{class, msg_send, rc::autoreleasepool, runtime::Class, sel, sel_impl};
+use serde::Serialize;
+
+use crate::{appearance, nsstring_to_string};
+
+/// Private framework behind Night Shift and True Tone, loaded lazily since it has no public API
+const CORE_BRIGHTNESS_PATH: &str = "/System/Library/PrivateFrameworks/CoreBrightness.framework";
+
+/// CoreBrightness' `Time`
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)]
+struct BlueLightTime {
+    hour: i32,
+    minute: i32,
+}
+
+/// CoreBrightness' `StatusData`
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)]
+struct BlueLightStatus {
+    active: BOOL,
+    enabled: BOOL,
+    sun_schedule_permitted: BOOL,
+    mode: i32,
+    schedule_from: BlueLightTime,
+    schedule_to: BlueLightTime,
+    disable_flags: u64,
+    available: BOOL,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemToggles {
+    dark_mode: bool,
+    /// `null` when the Mac doesn't support Night Shift
+    night_shift: Option<bool>,
+    /// `null` when no display supports True Tone
+    true_tone: Option<bool>,
+}
+
+/// Returns the state of the toggles settable with `set_system_dark_mode`, `set_night_shift` and
+/// `set_true_tone`
+#[tauri::command]
+pub fn get_system_toggles() -> SystemToggles {
+    autoreleasepool(|| SystemToggles {
+        dark_mode: appearance::current_theme() == appearance::Theme::Dark,
+        night_shift: night_shift_enabled(),
+        true_tone: true_tone_enabled(),
+    })
+}
+
+/// Switches the system between the light and dark appearance.
+///
+/// Goes through System Events, the first call asks the user to allow automating it.
+#[tauri::command]
+pub fn set_system_dark_mode(enabled: bool) -> Result<(), String> {
+    run_apple_script(&format!(
+        "tell application \"System Events\" to tell appearance preferences to set dark mode to {}",
+        enabled
+    ))
+}
+
+/// Turns Night Shift on or off until its next scheduled change
+#[tauri::command]
+pub fn set_night_shift(enabled: bool) -> Result<(), String> {
+    autoreleasepool(|| {
+        let client = night_shift_client().ok_or("Night Shift is not supported on this Mac")?;
+        let enabled: BOOL = if enabled { YES } else { NO };
+        let success: BOOL = unsafe { msg_send![client, setEnabled: enabled] };
+
+        if success == NO {
+            return Err("Unable to change Night Shift".into());
+        }
+
+        Ok(())
+    })
+}
+
+/// Turns True Tone on or off
+#[tauri::command]
+pub fn set_true_tone(enabled: bool) -> Result<(), String> {
+    autoreleasepool(|| {
+        let client = true_tone_client().ok_or("True Tone is not supported on this Mac")?;
+        let enabled: BOOL = if enabled { YES } else { NO };
+        let success: BOOL = unsafe { msg_send![client, setEnabled: enabled] };
+
+        if success == NO {
+            return Err("Unable to change True Tone".into());
+        }
+
+        Ok(())
+    })
+}
+
+fn run_apple_script(source: &str) -> Result<(), String> {
+    autoreleasepool(|| {
+        let error_message: id = unsafe {
+            let source = NSString::alloc(nil).init_str(source);
+            let source: id = msg_send![source, autorelease];
+
+            let script: id = msg_send![class!(NSAppleScript), alloc];
+            let script: id = msg_send![script, initWithSource: source];
+            let script: id = msg_send![script, autorelease];
+
+            let mut error: id = nil;
+            let _: id = msg_send![script, executeAndReturnError: &mut error];
+
+            if error == nil {
+                return Ok(());
+            }
+
+            let key = NSString::alloc(nil).init_str("NSAppleScriptErrorMessage");
+            let key: id = msg_send![key, autorelease];
+            msg_send![error, objectForKey: key]
+        };
+
+        Err(nsstring_to_string!(error_message).unwrap_or_else(|| "Unable to run script".into()))
+    })
+}
+
+fn night_shift_enabled() -> Option<bool> {
+    let client = night_shift_client()?;
+    let mut status = BlueLightStatus::default();
+    let success: BOOL = unsafe { msg_send![client, getBlueLightStatus: &mut status] };
+
+    (success == YES && status.available == YES).then_some(status.enabled == YES)
+}
+
+fn true_tone_enabled() -> Option<bool> {
+    let client = true_tone_client()?;
+    let enabled: BOOL = unsafe { msg_send![client, getEnabled] };
+
+    Some(enabled == YES)
+}
+
+/// An autoreleased `CBBlueLightClient`, `None` without Night Shift support
+fn night_shift_client() -> Option<id> {
+    let class = core_brightness_class("CBBlueLightClient")?;
+    let supported: BOOL = unsafe { msg_send![class, supportsBlueLightReduction] };
+    if supported == NO {
+        return None;
+    }
+
+    Some(unsafe { new_autoreleased(class) })
+}
+
+/// An autoreleased `CBAdaptationClient`, `None` without a True Tone display
+fn true_tone_client() -> Option<id> {
+    let class = core_brightness_class("CBAdaptationClient")?;
+    let client = unsafe { new_autoreleased(class) };
+    let supported: BOOL = unsafe { msg_send![client, getSupported] };
+
+    (supported == YES).then_some(client)
+}
+
+fn core_brightness_class(name: &str) -> Option<&'static Class> {
+    if Class::get(name).is_none() {
+        let loaded: BOOL = unsafe {
+            let path = NSString::alloc(nil).init_str(CORE_BRIGHTNESS_PATH);
+            let path: id = msg_send![path, autorelease];
+            let bundle: id = msg_send![class!(NSBundle), bundleWithPath: path];
+            msg_send![bundle, load]
+        };
+
+        if loaded == NO {
+            return None;
+        }
+    }
+
+    Class::get(name)
+}
+
+unsafe fn new_autoreleased(class: &Class) -> id {
+    let object: id = msg_send![class, new];
+    msg_send![object, autorelease]
+}
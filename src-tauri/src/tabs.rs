@@ -0,0 +1,353 @@
+use std::{collections::HashMap, sync::Mutex, time::Instant};
+
+use block::ConcreteBlock;
+use cocoa::{
+    base::{id, nil, NO, YES},
+    foundation::NSRect,
+};
+use objc::{class, msg_send, runtime::Object, sel, sel_impl};
+use objc_id::ShareId;
+use serde::Serialize;
+use tauri::{async_runtime, AppHandle, Manager, WindowBuilder, WindowUrl, Wry};
+
+use crate::{events, get_state, memory::MemoryPressureLevel, panel, webview};
+
+const MODE_EVICTED_EVENT: &str = "spotlight://mode-evicted";
+
+/// Label of the tab showing the panel's own webview, always present
+const MAIN_TAB: &str = "main";
+
+const CROSSFADE_DURATION: f64 = 0.15;
+
+/// NSViewWidthSizable | NSViewHeightSizable
+const VIEW_SIZABLE: u64 = (1 << 1) | (1 << 4);
+/// NSWindowAbove
+const WINDOW_ABOVE: i64 = 1;
+
+/// Most preloaded modes kept warm at once, the least recently used one goes past that
+const MAX_WARM_MODES: usize = 3;
+
+struct Tab {
+    view: ShareId<Object>,
+    /// Preloaded with `preload_mode`, so it can be evicted
+    warm: bool,
+    last_used: Instant,
+}
+
+struct Tabs {
+    /// Added tabs by label, stacked above the panel's own webview
+    views: HashMap<String, Tab>,
+    active: String,
+}
+
+impl Tabs {
+    /// Labels of the warm tabs that can be evicted, least recently used first
+    fn evictable(&self) -> Vec<String> {
+        let mut warm: Vec<(&String, &Tab)> = self
+            .views
+            .iter()
+            .filter(|(label, tab)| tab.warm && **label != self.active)
+            .collect();
+        warm.sort_by_key(|(_, tab)| tab.last_used);
+
+        warm.into_iter().map(|(label, _)| label.clone()).collect()
+    }
+}
+
+impl Default for Tabs {
+    fn default() -> Self {
+        Self {
+            views: HashMap::new(),
+            active: MAIN_TAB.to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct State(Mutex<Tabs>);
+
+#[derive(Clone, Serialize)]
+struct ModeEvictedPayload {
+    label: String,
+}
+
+/// Adds a tab loading `url`, e.g. `index.html#/notes`, hidden until `switch_tab` shows it.
+///
+/// Each tab is a window of its own with the given label, so it has its own page, IPC and events,
+/// but its webview is moved into the panel and the window itself is never shown. Keeping heavier
+/// modes in their own tab leaves the main one light.
+#[tauri::command]
+pub async fn add_tab(app_handle: AppHandle<Wry>, label: String, url: String) -> Result<(), String> {
+    if label == MAIN_TAB || app_handle.get_window(&label).is_some() {
+        return Err(format!("Tab \"{}\" already exists", label));
+    }
+
+    create_tab(app_handle, label, url, false).await
+}
+
+/// Loads a mode in a hidden tab ahead of time, so that `switch_tab` to `label` shows it
+/// instantly. Does nothing if it's already loaded.
+///
+/// At most three preloaded modes are kept. The least recently shown ones are evicted past that
+/// and under memory pressure, emitting `spotlight://mode-evicted`; preloading them again is up to
+/// the frontend.
+#[tauri::command]
+pub async fn preload_mode(
+    app_handle: AppHandle<Wry>,
+    label: String,
+    url: String,
+) -> Result<(), String> {
+    if label == MAIN_TAB
+        || app_handle
+            .state::<State>()
+            .0
+            .lock()
+            .unwrap()
+            .views
+            .contains_key(&label)
+    {
+        return Ok(());
+    }
+
+    create_tab(app_handle.clone(), label, url, true).await?;
+
+    let evictable = app_handle.state::<State>().0.lock().unwrap().evictable();
+    let excess = evictable.len().saturating_sub(MAX_WARM_MODES);
+
+    // Commands run off the main thread, the webviews must only be removed from it
+    let handle = app_handle.clone();
+    app_handle
+        .run_on_main_thread(move || evict(&handle, &evictable[..excess]))
+        .map_err(|e| e.to_string())
+}
+
+/// Evicts preloaded modes under memory pressure, the least recently used one on a warning and
+/// all of them but the one shown when critical
+pub(crate) fn evict_warm_tabs(app_handle: &AppHandle<Wry>, level: MemoryPressureLevel) {
+    let evictable = app_handle.state::<State>().0.lock().unwrap().evictable();
+
+    let count = match level {
+        MemoryPressureLevel::Normal => 0,
+        MemoryPressureLevel::Warning => 1,
+        MemoryPressureLevel::Critical => evictable.len(),
+    };
+
+    evict(app_handle, &evictable[..count.min(evictable.len())]);
+}
+
+/// Removes the given tabs, must be called on the main thread
+fn evict(app_handle: &AppHandle<Wry>, labels: &[String]) {
+    for label in labels {
+        if remove_tab(app_handle.clone(), label.clone()).is_ok() {
+            let _ = events::emit(
+                app_handle,
+                MODE_EVICTED_EVENT,
+                ModeEvictedPayload {
+                    label: label.clone(),
+                },
+            );
+        }
+    }
+}
+
+async fn create_tab(
+    app_handle: AppHandle<Wry>,
+    label: String,
+    url: String,
+    warm: bool,
+) -> Result<(), String> {
+    let window = WindowBuilder::new(&app_handle, &label, WindowUrl::App(url.into()))
+        .visible(false)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let (tx, mut rx) = async_runtime::channel(1);
+    let handle = app_handle.clone();
+
+    window
+        .with_webview(move |webview| {
+            let _ = tx.try_send(host_tab(&handle, label, webview.inner() as id, warm));
+        })
+        .map_err(|e| e.to_string())?;
+
+    rx.recv()
+        .await
+        .ok_or_else(|| "Adding the tab was interrupted".to_string())?
+}
+
+/// Shows a tab, crossfading from the current one, and focuses its webview. `main` is the panel's
+/// own webview.
+#[tauri::command]
+pub fn switch_tab(app_handle: AppHandle<Wry>, label: String) -> Result<(), String> {
+    let state = app_handle.state::<State>();
+    let mut tabs = state.0.lock().unwrap();
+
+    if tabs.active == label {
+        return Ok(());
+    }
+
+    let incoming = if label == MAIN_TAB {
+        None
+    } else {
+        let tab = tabs
+            .views
+            .get_mut(&label)
+            .ok_or_else(|| format!("Tab \"{}\" does not exist", label))?;
+        tab.last_used = Instant::now();
+        Some(tab.view.clone())
+    };
+    let outgoing = tabs.views.get(&tabs.active).map(|tab| tab.view.clone());
+
+    crossfade(&app_handle, outgoing, incoming.clone());
+
+    let panel = panel!(app_handle);
+    let responder = match &incoming {
+        Some(view) => &**view as *const Object as id,
+        None => webview!(app_handle).as_id(),
+    };
+    let _: () = unsafe { msg_send![&*panel, makeFirstResponder: responder] };
+
+    tabs.active = label;
+
+    Ok(())
+}
+
+/// Removes a tab added with `add_tab` and closes its window, switching back to `main` if it was
+/// shown
+#[tauri::command]
+pub fn remove_tab(app_handle: AppHandle<Wry>, label: String) -> Result<(), String> {
+    let tab = {
+        let state = app_handle.state::<State>();
+        let mut tabs = state.0.lock().unwrap();
+        let tab = tabs
+            .views
+            .remove(&label)
+            .ok_or_else(|| format!("Tab \"{}\" does not exist", label))?;
+
+        if tabs.active == label {
+            tabs.active = MAIN_TAB.to_string();
+        }
+
+        tab
+    };
+
+    let _: () = unsafe { msg_send![&*tab.view, removeFromSuperview] };
+
+    if let Some(window) = app_handle.get_window(&label) {
+        window.close().map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Removes every tab and closes their windows, for when the panel hosting them goes away
+pub(crate) fn remove_all_tabs(app_handle: &AppHandle<Wry>) {
+    let labels: Vec<String> = app_handle
+        .state::<State>()
+        .0
+        .lock()
+        .unwrap()
+        .views
+        .keys()
+        .cloned()
+        .collect();
+
+    for label in labels {
+        let _ = remove_tab(app_handle.clone(), label);
+    }
+}
+
+/// Moves a tab's webview out of its window into the panel, hidden. Runs on the main thread.
+fn host_tab(
+    app_handle: &AppHandle<Wry>,
+    label: String,
+    webview: id,
+    warm: bool,
+) -> Result<(), String> {
+    let panel = get_state!(app_handle, panel, clone).ok_or("The panel isn't initialized")?;
+    let view: ShareId<Object> = unsafe { ShareId::from_ptr(webview as *mut Object) };
+    let content_view = panel.content_view();
+
+    unsafe {
+        let bounds: NSRect = msg_send![content_view, bounds];
+        let _: () = msg_send![&*view, removeFromSuperview];
+        let _: () = msg_send![&*view, setFrame: bounds];
+        let _: () = msg_send![&*view, setAutoresizingMask: VIEW_SIZABLE];
+        let _: () = msg_send![&*view, setHidden: YES];
+        let _: () =
+            msg_send![content_view, addSubview: &*view positioned: WINDOW_ABOVE relativeTo: nil];
+    }
+
+    app_handle.state::<State>().0.lock().unwrap().views.insert(
+        label,
+        Tab {
+            view,
+            warm,
+            last_used: Instant::now(),
+        },
+    );
+
+    Ok(())
+}
+
+/// Fades the incoming tab in on top of the others and the outgoing one out. `None` is the
+/// panel's own webview, which stays underneath the tabs and is never faded.
+fn crossfade(
+    app_handle: &AppHandle<Wry>,
+    outgoing: Option<ShareId<Object>>,
+    incoming: Option<ShareId<Object>>,
+) {
+    let content_view = panel!(app_handle).content_view();
+
+    if let Some(view) = &incoming {
+        unsafe {
+            let _: () = msg_send![&**view, setAlphaValue: 0.0f64];
+            let _: () = msg_send![&**view, setHidden: NO];
+            // Moves it above the other tabs
+            let _: () = msg_send![
+                content_view,
+                addSubview: &**view
+                positioned: WINDOW_ABOVE
+                relativeTo: nil
+            ];
+        }
+    }
+
+    let faded_out = outgoing.clone();
+    let animations = ConcreteBlock::new(move |context: id| unsafe {
+        let _: () = msg_send![context, setDuration: CROSSFADE_DURATION];
+
+        if let Some(view) = &incoming {
+            let animator: id = msg_send![&**view, animator];
+            let _: () = msg_send![animator, setAlphaValue: 1.0f64];
+        }
+
+        if let Some(view) = &outgoing {
+            let animator: id = msg_send![&**view, animator];
+            let _: () = msg_send![animator, setAlphaValue: 0.0f64];
+        }
+    })
+    .copy();
+
+    let completion = ConcreteBlock::new(move || {
+        if let Some(view) = &faded_out {
+            unsafe {
+                // Unless it was switched back to in the meantime
+                let alpha: f64 = msg_send![&**view, alphaValue];
+                if alpha == 0.0 {
+                    let _: () = msg_send![&**view, setHidden: YES];
+                    let _: () = msg_send![&**view, setAlphaValue: 1.0f64];
+                }
+            }
+        }
+    })
+    .copy();
+
+    unsafe {
+        let _: () = msg_send![
+            class!(NSAnimationContext),
+            runAnimationGroup: &*animations
+            completionHandler: &*completion
+        ];
+    }
+}
@@ -0,0 +1,165 @@
+use std::{ffi::CString, mem, sync::Mutex};
+
+use cocoa::{
+    base::{id, nil, BOOL, YES},
+    foundation::{NSPoint, NSRange, NSRect, NSSize, NSString},
+};
+use objc::{
+    class, msg_send,
+    runtime::{self, Class, Imp, Sel, NO},
+    sel, sel_impl,
+};
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{diagnostics, panel, webview};
+
+/// Rect of the focused input reported by the frontend, relative to the top-left corner of the
+/// webview
+static TEXT_INPUT_RECT: Mutex<Option<NSRect>> = Mutex::new(None);
+
+/// Rect of the focused input in CSS pixels, relative to the top-left corner of the webview
+#[derive(Deserialize)]
+pub struct TextInputRect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+/// Tells the native side where the focused text input is, so IME candidate windows can be placed
+/// next to it. Pass `null` when the input loses focus.
+#[tauri::command]
+pub fn set_text_input_rect(rect: Option<TextInputRect>) {
+    *TEXT_INPUT_RECT.lock().unwrap() = rect.map(|rect| {
+        NSRect::new(
+            NSPoint::new(rect.x, rect.y),
+            NSSize::new(rect.width, rect.height),
+        )
+    });
+}
+
+type FirstRectForCharacterRange = extern "C" fn(id, Sel, NSRange, *mut NSRange) -> NSRect;
+
+/// Overrides `firstRectForCharacterRange:actualRange:` on the webview's class.
+///
+/// WKWebView answers this from its own content process, which in a borderless non-activating
+/// panel often yields an empty rect, so input methods place their candidate window at the corner
+/// of the screen. When the frontend has reported a rect we answer with it instead.
+pub fn install_text_input_client(app_handle: &AppHandle<Wry>, webview: id) {
+    let cls: *const Class = unsafe { msg_send![webview, class] };
+    let sel = sel!(firstRectForCharacterRange:actualRange:);
+    let types =
+        CString::new("{CGRect={CGPoint=dd}{CGSize=dd}}@:{_NSRange=QQ}^{_NSRange=QQ}").unwrap();
+
+    unsafe {
+        let imp: Imp = mem::transmute(first_rect_for_character_range as FirstRectForCharacterRange);
+
+        // Only possible on wry's WKWebView subclass, WKWebView itself is left alone. Every
+        // webview shares that class, it's already there for the ones created after the first.
+        if runtime::class_addMethod(cls as *mut Class, sel, imp, types.as_ptr()) == NO
+            && !has_implementation(cls, sel, imp)
+        {
+            diagnostics::warn(
+                app_handle,
+                "textInputClientUnavailable",
+                "Unable to install the text input client on the webview",
+            );
+        }
+    }
+}
+
+/// Whether `imp` is what a class runs for `sel`
+pub(crate) fn has_implementation(cls: *const Class, sel: Sel, imp: Imp) -> bool {
+    unsafe {
+        let method = runtime::class_getInstanceMethod(cls, sel);
+        !method.is_null() && runtime::method_getImplementation(method) as usize == imp as usize
+    }
+}
+
+extern "C" fn first_rect_for_character_range(
+    this: id,
+    sel: Sel,
+    range: NSRange,
+    actual_range: *mut NSRange,
+) -> NSRect {
+    let rect = *TEXT_INPUT_RECT.lock().unwrap();
+
+    match rect {
+        Some(rect) => unsafe {
+            if !actual_range.is_null() {
+                *actual_range = range;
+            }
+
+            let bounds: NSRect = msg_send![this, bounds];
+            let flipped: BOOL = msg_send![this, isFlipped];
+            let y = if flipped == YES {
+                rect.origin.y
+            } else {
+                bounds.size.height - rect.origin.y - rect.size.height
+            };
+
+            let rect = NSRect::new(NSPoint::new(rect.origin.x, y), rect.size);
+            let window: id = msg_send![this, window];
+            let rect: NSRect = msg_send![this, convertRect: rect toView: nil];
+            msg_send![window, convertRectToScreen: rect]
+        },
+        None => unsafe {
+            let method = runtime::class_getInstanceMethod(class!(WKWebView), sel);
+            let imp: FirstRectForCharacterRange =
+                mem::transmute(runtime::method_getImplementation(method));
+            imp(this, sel, range, actual_range)
+        },
+    }
+}
+
+/// Enables or disables the press-and-hold accent picker for this app.
+///
+/// Writes `ApplePressAndHoldEnabled` to the app's user defaults, which AppKit consults when a key
+/// is held down in a text input client, so the change applies to subsequent key presses.
+#[tauri::command]
+pub fn set_press_and_hold_enabled(enabled: bool) {
+    unsafe {
+        let defaults: id = msg_send![class!(NSUserDefaults), standardUserDefaults];
+        let key = NSString::alloc(nil).init_str("ApplePressAndHoldEnabled");
+        let key: id = msg_send![key, autorelease];
+        let _: () = msg_send![defaults, setBool: enabled as BOOL forKey: key];
+    }
+}
+
+/// Starts system dictation into the panel's focused input.
+///
+/// The global dictation shortcut targets the active app, which a non-activating panel never is.
+/// This makes the panel key with the webview as first responder and sends `startDictation:` down
+/// the responder chain, the same way the Edit > Start Dictation menu item would.
+#[tauri::command]
+pub fn start_system_dictation(app_handle: AppHandle<Wry>) {
+    let panel = panel!(app_handle);
+    let webview = webview!(app_handle);
+
+    unsafe {
+        let _: () = msg_send![&*panel, makeKeyWindow];
+        let _: () = msg_send![&*panel, makeFirstResponder: webview.as_id()];
+
+        let app: id = msg_send![class!(NSApplication), sharedApplication];
+        let _: BOOL = msg_send![app, sendAction: sel!(startDictation:) to: nil from: nil];
+    }
+}
+
+/// Opens the system emoji & symbols picker anchored to the panel's focused input.
+///
+/// Auto hide is suspended while the picker is open and restored once the panel is key again.
+#[tauri::command]
+pub fn show_character_palette(app_handle: AppHandle<Wry>) {
+    let panel = panel!(app_handle);
+    let webview = webview!(app_handle);
+
+    panel.suspend_auto_hide_until_key();
+
+    unsafe {
+        let _: () = msg_send![&*panel, makeFirstResponder: webview.as_id()];
+
+        let app: id = msg_send![class!(NSApplication), sharedApplication];
+        let _: () = msg_send![app, orderFrontCharacterPalette: nil];
+    }
+}
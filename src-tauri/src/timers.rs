@@ -0,0 +1,189 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{api::notification::Notification, AppHandle, Manager, Wry};
+
+use crate::{
+    events, get_state, hud,
+    locale::{t, Text},
+    presentation_mode, spotlight,
+};
+
+const TIMER_FIRED_EVENT: &str = "spotlight://timer-fired";
+
+const HUD_DURATION: Duration = Duration::from_secs(3);
+
+/// How a timer announces itself when it expires, on top of `spotlight://timer-fired`
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TimerAlert {
+    /// Posts a system notification
+    notification: bool,
+    /// Shows a native HUD with the timer's name
+    hud: bool,
+    /// Shows the panel, so the frontend can render the expired timer
+    show_panel: bool,
+}
+
+impl Default for TimerAlert {
+    fn default() -> Self {
+        Self {
+            notification: true,
+            hud: false,
+            show_panel: false,
+        }
+    }
+}
+
+struct Timer {
+    name: String,
+    duration: Duration,
+    ends_at: Instant,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerInfo {
+    id: u32,
+    name: String,
+    duration_ms: u128,
+    remaining_ms: u128,
+}
+
+#[derive(Clone, Serialize)]
+struct TimerFiredPayload {
+    id: u32,
+    name: String,
+}
+
+/// Timers live on the native side so they keep running across webview reloads
+#[derive(Default)]
+pub struct State {
+    next_id: AtomicU32,
+    timers: Mutex<HashMap<u32, Timer>>,
+}
+
+/// Starts a timer and returns its id. When it expires, `spotlight://timer-fired` is emitted and
+/// the user is alerted as described by `alert`. Fails if `duration_ms` is too long to be
+/// represented.
+#[tauri::command]
+pub fn start_timer(
+    app_handle: AppHandle<Wry>,
+    name: String,
+    duration_ms: u64,
+    alert: Option<TimerAlert>,
+) -> Result<u32, String> {
+    let duration = Duration::from_millis(duration_ms);
+    let ends_at = Instant::now()
+        .checked_add(duration)
+        .ok_or("Timer duration is too long")?;
+
+    let state = app_handle.state::<State>();
+    let id = state.next_id.fetch_add(1, Ordering::Relaxed);
+
+    state.timers.lock().unwrap().insert(
+        id,
+        Timer {
+            name,
+            duration,
+            ends_at,
+        },
+    );
+
+    let alert = alert.unwrap_or_default();
+
+    thread::spawn(move || {
+        thread::sleep(duration);
+
+        // Gone if it was cancelled in the meantime
+        let timer = app_handle
+            .state::<State>()
+            .timers
+            .lock()
+            .unwrap()
+            .remove(&id);
+        if let Some(timer) = timer {
+            fire(&app_handle, id, timer, alert);
+        }
+    });
+
+    Ok(id)
+}
+
+/// Lists running timers, soonest first
+#[tauri::command]
+pub fn list_timers(app_handle: AppHandle<Wry>) -> Vec<TimerInfo> {
+    let state = app_handle.state::<State>();
+    let timers = state.timers.lock().unwrap();
+    let now = Instant::now();
+
+    let mut timers = timers
+        .iter()
+        .map(|(id, timer)| TimerInfo {
+            id: *id,
+            name: timer.name.clone(),
+            duration_ms: timer.duration.as_millis(),
+            remaining_ms: timer.ends_at.saturating_duration_since(now).as_millis(),
+        })
+        .collect::<Vec<_>>();
+
+    timers.sort_by_key(|timer| timer.remaining_ms);
+    timers
+}
+
+#[tauri::command]
+pub fn cancel_timer(app_handle: AppHandle<Wry>, id: u32) -> Result<(), String> {
+    app_handle
+        .state::<State>()
+        .timers
+        .lock()
+        .unwrap()
+        .remove(&id)
+        .map(drop)
+        .ok_or_else(|| format!("No running timer with id {}", id))
+}
+
+fn fire(app_handle: &AppHandle<Wry>, id: u32, timer: Timer, alert: TimerAlert) {
+    // The finished event still goes out, the frontend decides what to do with it
+    let quiet = presentation_mode::is_active(app_handle);
+
+    if alert.notification && !quiet {
+        let _ = Notification::new(&app_handle.config().tauri.bundle.identifier)
+            .title(t(Text::TimerFinished))
+            .body(&timer.name)
+            .show();
+    }
+
+    if (alert.hud || alert.show_panel) && !quiet {
+        let handle = app_handle.clone();
+        let name = timer.name.clone();
+
+        let _ = app_handle.run_on_main_thread(move || {
+            if alert.hud {
+                hud::show_hud(&name, HUD_DURATION);
+            }
+
+            // The panel can be missing, e.g. while its window is recreated
+            if alert.show_panel && get_state!(handle, panel, is_some) {
+                spotlight::show_spotlight(handle);
+            }
+        });
+    }
+
+    let _ = events::emit(
+        app_handle,
+        TIMER_FIRED_EVENT,
+        TimerFiredPayload {
+            id,
+            name: timer.name,
+        },
+    );
+}
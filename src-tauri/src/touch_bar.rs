@@ -0,0 +1,145 @@
+use std::{ffi::c_void, sync::Mutex};
+
+use cocoa::{
+    base::{id, nil},
+    foundation::NSString,
+};
+use objc::{
+    class,
+    declare::ClassDecl,
+    msg_send,
+    runtime::{self, Class, Object, Sel},
+    sel, sel_impl, Message,
+};
+use objc_foundation::INSObject;
+use objc_id::Id;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{events, nsstring_to_string, panel};
+
+const TOUCH_BAR_EVENT: &str = "spotlight://touch-bar";
+
+#[derive(Deserialize)]
+pub struct TouchBarItem {
+    id: String,
+    label: String,
+}
+
+#[derive(Clone, Serialize)]
+struct TouchBarPayload {
+    id: String,
+}
+
+#[derive(Default)]
+pub struct State(Mutex<Option<Id<RawTouchBarTarget>>>);
+
+/// Replaces the panel's Touch Bar with buttons for the given items.
+///
+/// Tapping a button emits `spotlight://touch-bar` with the item's id. Pass `[]` to remove the
+/// Touch Bar. Does nothing visible on Macs without a Touch Bar.
+#[tauri::command]
+pub fn set_touch_bar_items(app_handle: AppHandle<Wry>, items: Vec<TouchBarItem>) {
+    let panel = panel!(app_handle);
+
+    if items.is_empty() {
+        let _: () = unsafe { msg_send![&*panel, setTouchBar: nil] };
+        return;
+    }
+
+    let state = app_handle.state::<State>();
+    let mut target = state.0.lock().unwrap();
+    let target: &Id<RawTouchBarTarget> =
+        target.get_or_insert_with(|| RawTouchBarTarget::create(app_handle.clone()));
+
+    unsafe {
+        let touch_bar: id = msg_send![class!(NSTouchBar), new];
+        let identifiers: id = msg_send![class!(NSMutableArray), array];
+        let template_items: id = msg_send![class!(NSMutableSet), set];
+
+        for item in items {
+            let identifier = NSString::alloc(nil).init_str(&item.id);
+            let identifier: id = msg_send![identifier, autorelease];
+            let label = NSString::alloc(nil).init_str(&item.label);
+            let label: id = msg_send![label, autorelease];
+
+            let button: id = msg_send![
+                class!(NSButton),
+                buttonWithTitle: label
+                target: &**target
+                action: sel!(tap:)
+            ];
+            let _: () = msg_send![button, setIdentifier: identifier];
+
+            let touch_bar_item: id = msg_send![class!(NSCustomTouchBarItem), alloc];
+            let touch_bar_item: id = msg_send![touch_bar_item, initWithIdentifier: identifier];
+            let _: () = msg_send![touch_bar_item, setView: button];
+
+            let _: () = msg_send![identifiers, addObject: identifier];
+            let _: () = msg_send![template_items, addObject: touch_bar_item];
+            let _: () = msg_send![touch_bar_item, release];
+        }
+
+        let _: () = msg_send![touch_bar, setDefaultItemIdentifiers: identifiers];
+        let _: () = msg_send![touch_bar, setTemplateItems: template_items];
+        let _: () = msg_send![&*panel, setTouchBar: touch_bar];
+        let _: () = msg_send![touch_bar, release];
+    }
+}
+
+const TARGET_CLS_NAME: &str = "RawTouchBarTarget";
+
+/// Target of the Touch Bar buttons, forwards taps to the frontend
+pub struct RawTouchBarTarget;
+
+impl RawTouchBarTarget {
+    fn get_class() -> &'static Class {
+        Class::get(TARGET_CLS_NAME).unwrap_or_else(Self::define_class)
+    }
+
+    fn define_class() -> &'static Class {
+        let mut cls = ClassDecl::new(TARGET_CLS_NAME, class!(NSObject))
+            .unwrap_or_else(|| panic!("Unable to register {} class", TARGET_CLS_NAME));
+
+        unsafe {
+            cls.add_ivar::<*mut c_void>("_appHandle");
+
+            cls.add_method(sel!(tap:), Self::tap as extern "C" fn(&Object, Sel, id));
+        }
+
+        cls.register()
+    }
+
+    fn create(app_handle: AppHandle<Wry>) -> Id<Self> {
+        let target = Self::new();
+        let app_handle = Box::into_raw(Box::new(app_handle)) as *mut c_void;
+
+        // The target is kept in the app state and never deallocated
+        unsafe {
+            let this = &mut *(&*target as *const Self as *mut Object);
+            this.set_ivar("_appHandle", app_handle);
+        }
+
+        target
+    }
+
+    extern "C" fn tap(this: &Object, _: Sel, sender: id) {
+        let app_handle =
+            unsafe { &*(*this.get_ivar::<*mut c_void>("_appHandle") as *const AppHandle<Wry>) };
+        let identifier: id = unsafe { msg_send![sender, identifier] };
+
+        let payload = TouchBarPayload {
+            id: nsstring_to_string!(identifier).unwrap_or_default(),
+        };
+
+        let _ = events::emit(app_handle, TOUCH_BAR_EVENT, payload);
+    }
+}
+
+unsafe impl Message for RawTouchBarTarget {}
+
+impl INSObject for RawTouchBarTarget {
+    fn class() -> &'static runtime::Class {
+        Self::get_class()
+    }
+}
@@ -0,0 +1,62 @@
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+/// Entries kept, the oldest ones are dropped past that
+const MAX_TRACE_ENTRIES: usize = 256;
+
+/// Kinds of native operations worth knowing about when the panel misbehaves
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TraceKind {
+    /// The panel's window level changed
+    Level,
+    /// The panel was moved or resized
+    Frame,
+    Show,
+    Hide,
+    /// Focus was handed to another app
+    FocusRestore,
+    /// The toggle shortcut fired, or was registered, released or lost
+    Shortcut,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceEntry {
+    /// Milliseconds since the Unix epoch
+    timestamp: u64,
+    kind: TraceKind,
+    detail: String,
+}
+
+static TRACE: Mutex<VecDeque<TraceEntry>> = Mutex::new(VecDeque::new());
+
+/// Records a native operation in the trace returned by `get_native_trace`
+pub(crate) fn record(kind: TraceKind, detail: impl Into<String>) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_millis() as u64);
+
+    let mut trace = TRACE.lock().unwrap();
+    if trace.len() == MAX_TRACE_ENTRIES {
+        trace.pop_front();
+    }
+
+    trace.push_back(TraceEntry {
+        timestamp,
+        kind,
+        detail: detail.into(),
+    });
+}
+
+/// Returns the last native operations on the panel (level changes, frame sets, focus restores,
+/// shortcut events, ...), oldest first. Meant to be attached to bug reports.
+#[tauri::command]
+pub fn get_native_trace() -> Vec<TraceEntry> {
+    TRACE.lock().unwrap().iter().cloned().collect()
+}
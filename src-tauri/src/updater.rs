@@ -0,0 +1,123 @@
+use std::{sync::Mutex, thread, time::Duration};
+
+use serde::Serialize;
+use tauri::{async_runtime, updater::UpdateResponse, AppHandle, Manager, Wry};
+
+use crate::{diagnostics, drop_targets, events, handoff, spotlight};
+
+const UPDATE_AVAILABLE_EVENT: &str = "spotlight://update-available";
+
+/// Time between background checks, the first one happens shortly after launch
+const UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const FIRST_UPDATE_CHECK_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    version: String,
+    /// Release notes from the update manifest
+    notes: Option<String>,
+}
+
+impl From<&UpdateResponse<Wry>> for UpdateInfo {
+    fn from(update: &UpdateResponse<Wry>) -> Self {
+        Self {
+            version: update.latest_version().to_string(),
+            notes: update.body().cloned(),
+        }
+    }
+}
+
+/// The update found by the last check, if any
+#[derive(Default)]
+pub struct State(Mutex<Option<UpdateResponse<Wry>>>);
+
+/// Checks for an update now. Resolves to the available update, `null` if up to date.
+///
+/// Needs the updater enabled with an endpoint and public key under `tauri.updater` in
+/// tauri.conf.json. Its dialog must stay off, the app has no Dock icon to bring it forward.
+#[tauri::command]
+pub async fn check_for_update(app_handle: AppHandle<Wry>) -> Result<Option<UpdateInfo>, String> {
+    let update = app_handle
+        .updater()
+        .check()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !update.is_update_available() {
+        return Ok(None);
+    }
+
+    let info = UpdateInfo::from(&update);
+    let is_new = {
+        let state = app_handle.state::<State>();
+        let mut pending = state.0.lock().unwrap();
+        let is_new = pending.as_ref().map_or(true, |pending| {
+            pending.latest_version() != update.latest_version()
+        });
+        *pending = Some(update);
+        is_new
+    };
+
+    if is_new {
+        let _ = events::emit(&app_handle, UPDATE_AVAILABLE_EVENT, info.clone());
+
+        let handle = app_handle.clone();
+        let _ = app_handle
+            .run_on_main_thread(move || drop_targets::set_status_item_badge(&handle, true));
+    }
+
+    Ok(Some(info))
+}
+
+/// Downloads and installs the update found by the last check, then relaunches the app.
+///
+/// Settings are already on disk and the relaunched instance takes the shortcut over, see
+/// `handoff`; the frontend should `save_session` before calling this to come back where it was.
+/// Only the windows from tauri.conf.json come back, any other window the frontend opened has to
+/// be reopened by it from its saved session.
+#[tauri::command]
+pub async fn install_update(app_handle: AppHandle<Wry>) -> Result<(), String> {
+    let update = app_handle
+        .state::<State>()
+        .0
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No update available, call check_for_update first")?;
+
+    update
+        .download_and_install()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    spotlight::prepare_for_exit(&app_handle);
+    handoff::prepare_relaunch();
+    app_handle.restart();
+
+    Ok(())
+}
+
+/// Checks for updates in the background for the lifetime of the app, emitting
+/// `spotlight://update-available` and badging the status item when one is found
+pub fn watch_for_updates(app_handle: AppHandle<Wry>) {
+    if !app_handle.config().tauri.updater.active {
+        return;
+    }
+
+    thread::spawn(move || {
+        thread::sleep(FIRST_UPDATE_CHECK_DELAY);
+
+        loop {
+            if let Err(error) = async_runtime::block_on(check_for_update(app_handle.clone())) {
+                diagnostics::warn(
+                    &app_handle,
+                    "updateCheckFailed",
+                    format!("Update check failed: {}", error),
+                );
+            }
+
+            thread::sleep(UPDATE_CHECK_INTERVAL);
+        }
+    });
+}
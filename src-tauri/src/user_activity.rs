@@ -0,0 +1,172 @@
+use std::{ffi::CString, mem, sync::Mutex};
+
+use cocoa::{
+    base::{id, nil, BOOL, NO, YES},
+    foundation::NSString,
+};
+use objc::{
+    class, msg_send,
+    runtime::{self, Class, Imp, Object, Sel},
+    sel, sel_impl, Encode,
+};
+use objc_id::Id;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{
+    core_spotlight, diagnostics, events, get_state, json, nsstring_to_string, privacy, spotlight,
+    text_input,
+};
+
+const CONTINUE_ACTIVITY_EVENT: &str = "spotlight://continue-activity";
+
+/// Activity type declared under `NSUserActivityTypes` in Info.plist
+const ACTIVITY_TYPE: &str = "com.tauri.spotlight.panel";
+
+static APP_HANDLE: Mutex<Option<AppHandle<Wry>>> = Mutex::new(None);
+
+#[derive(Default)]
+pub struct State(Mutex<Option<Id<Object>>>);
+
+#[derive(Deserialize)]
+pub struct UserActivity {
+    title: String,
+    /// Anything describing the panel's current query/mode, handed back on continuation
+    state: Value,
+}
+
+#[derive(Clone, Serialize)]
+struct ContinueActivityPayload {
+    title: Option<String>,
+    state: Option<Value>,
+}
+
+/// Publishes the panel's current query/mode as the current `NSUserActivity`, making it available
+/// to Handoff on other devices. Pass `null` to stop publishing it.
+///
+/// When the activity is continued, the panel is shown and `spotlight://continue-activity` is
+/// emitted with the published state so the frontend can restore it. Nothing is published in
+/// privacy mode.
+#[tauri::command]
+pub fn set_user_activity(app_handle: AppHandle<Wry>, activity: Option<UserActivity>) {
+    let state = app_handle.state::<State>();
+    let mut current = state.0.lock().unwrap();
+
+    if let Some(previous) = current.take() {
+        let _: () = unsafe { msg_send![previous, invalidate] };
+    }
+
+    let activity = match activity {
+        Some(activity) if !privacy::is_enabled(&app_handle) => activity,
+        _ => return,
+    };
+
+    unsafe {
+        let activity_type = NSString::alloc(nil).init_str(ACTIVITY_TYPE);
+        let activity_type: id = msg_send![activity_type, autorelease];
+        let user_activity: id = msg_send![class!(NSUserActivity), alloc];
+        let user_activity: id = msg_send![user_activity, initWithActivityType: activity_type];
+
+        let title = NSString::alloc(nil).init_str(&activity.title);
+        let title: id = msg_send![title, autorelease];
+        let _: () = msg_send![user_activity, setTitle: title];
+
+        // `null` has no Foundation counterpart and can't go in a dictionary, it's left out
+        let state = json::to_ns_object(&activity.state);
+        if state != nil {
+            let key = NSString::alloc(nil).init_str("state");
+            let key: id = msg_send![key, autorelease];
+            let user_info: id = msg_send![
+                class!(NSDictionary),
+                dictionaryWithObject: state
+                forKey: key
+            ];
+            let _: () = msg_send![user_activity, setUserInfo: user_info];
+        }
+
+        let _: () = msg_send![user_activity, setEligibleForHandoff: YES];
+        let _: () = msg_send![user_activity, becomeCurrent];
+
+        *current = Some(Id::from_retained_ptr(user_activity));
+    }
+}
+
+type ContinueUserActivity = extern "C" fn(&Object, Sel, id, id, id) -> BOOL;
+
+/// Teaches Tauri's app delegate to continue our user activities, including those of items
+/// opened from the system Spotlight.
+///
+/// Must be called on the main thread once the app delegate is set, i.e. from `setup`.
+pub fn install_continuation_handler(app_handle: AppHandle<Wry>) {
+    *APP_HANDLE.lock().unwrap() = Some(app_handle.clone());
+
+    unsafe {
+        let app: id = msg_send![class!(NSApplication), sharedApplication];
+        let delegate: id = msg_send![app, delegate];
+        let cls: *const Class = msg_send![delegate, class];
+
+        let types = CString::new(format!("{}@:@@@", BOOL::encode().as_str())).unwrap();
+        let sel = sel!(application:continueUserActivity:restorationHandler:);
+        let imp: Imp = mem::transmute(continue_user_activity as ContinueUserActivity);
+
+        // Fails if the delegate already continues activities itself, ours would never run
+        if runtime::class_addMethod(cls as *mut Class, sel, imp, types.as_ptr()) == NO
+            && !text_input::has_implementation(cls, sel, imp)
+        {
+            diagnostics::warn(
+                &app_handle,
+                "userActivityUnavailable",
+                "Unable to install the user activity continuation handler",
+            );
+        }
+    }
+}
+
+extern "C" fn continue_user_activity(
+    _: &Object,
+    _: Sel,
+    _application: id,
+    user_activity: id,
+    _restoration_handler: id,
+) -> BOOL {
+    let app_handle = match APP_HANDLE.lock().unwrap().clone() {
+        Some(app_handle) => app_handle,
+        None => return NO,
+    };
+
+    // Only show the panel for our own activities and Spotlight results
+    let activity_type: id = unsafe { msg_send![user_activity, activityType] };
+    let is_ours = nsstring_to_string!(activity_type).as_deref() == Some(ACTIVITY_TYPE);
+    if !is_ours && !core_spotlight::is_spotlight_item(user_activity) {
+        return NO;
+    }
+
+    // The panel may not be initialized yet when the app is launched to continue an activity
+    if get_state!(app_handle, panel, is_some) {
+        spotlight::show_spotlight(app_handle.clone());
+    }
+
+    if core_spotlight::continue_user_activity(&app_handle, user_activity) {
+        return YES;
+    }
+
+    let title: id = unsafe { msg_send![user_activity, title] };
+    let user_info: id = unsafe { msg_send![user_activity, userInfo] };
+    let state: id = if user_info == nil {
+        nil
+    } else {
+        let key = unsafe { NSString::alloc(nil).init_str("state") };
+        let key: id = unsafe { msg_send![key, autorelease] };
+        unsafe { msg_send![user_info, objectForKey: key] }
+    };
+
+    let payload = ContinueActivityPayload {
+        title: nsstring_to_string!(title),
+        state: json::from_ns_object(state),
+    };
+
+    let _ = events::emit(&app_handle, CONTINUE_ACTIVITY_EVENT, payload);
+
+    YES
+}
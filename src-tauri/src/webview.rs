@@ -0,0 +1,270 @@
+use std::{ffi::CString, mem, sync::Mutex};
+
+use block::ConcreteBlock;
+use cocoa::{
+    base::{id, nil, BOOL, YES},
+    foundation::NSString,
+};
+use objc::{
+    class, msg_send,
+    runtime::{self, Class, Imp, Sel, NO},
+    sel, sel_impl,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Window, Wry};
+
+use crate::{diagnostics, events, get_state, settings, text_input, webview};
+
+const WEBVIEW_CRASHED_EVENT: &str = "spotlight://webview-crashed";
+
+const MIN_ZOOM: f64 = 0.5;
+const MAX_ZOOM: f64 = 3.0;
+
+/// Handle used by the content process termination handler, which has no other way to reach the app
+static CRASH_RECOVERY_APP_HANDLE: Mutex<Option<AppHandle<Wry>>> = Mutex::new(None);
+
+/// Text checking features of the panel's webview; `None` leaves a feature untouched
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextChecking {
+    spell_checking: Option<bool>,
+    smart_quotes: Option<bool>,
+    text_replacement: Option<bool>,
+}
+
+#[derive(Clone, Copy)]
+enum TextCheckingFeature {
+    SpellChecking,
+    SmartQuotes,
+    TextReplacement,
+}
+
+/// Enables or disables spell checking, smart quotes and text replacement on the panel's webview
+#[tauri::command]
+pub fn set_text_checking(app_handle: AppHandle<Wry>, options: TextChecking) {
+    let webview = webview!(app_handle).as_id();
+
+    for (feature, enabled) in [
+        (TextCheckingFeature::SpellChecking, options.spell_checking),
+        (TextCheckingFeature::SmartQuotes, options.smart_quotes),
+        (
+            TextCheckingFeature::TextReplacement,
+            options.text_replacement,
+        ),
+    ] {
+        if let Some(enabled) = enabled {
+            if is_enabled(webview, feature) != enabled {
+                toggle(webview, feature);
+            }
+        }
+    }
+}
+
+/// Returns the current text checking features of the panel's webview
+#[tauri::command]
+pub fn get_text_checking(app_handle: AppHandle<Wry>) -> TextChecking {
+    let webview = webview!(app_handle).as_id();
+
+    TextChecking {
+        spell_checking: Some(is_enabled(webview, TextCheckingFeature::SpellChecking)),
+        smart_quotes: Some(is_enabled(webview, TextCheckingFeature::SmartQuotes)),
+        text_replacement: Some(is_enabled(webview, TextCheckingFeature::TextReplacement)),
+    }
+}
+
+fn is_enabled(webview: id, feature: TextCheckingFeature) -> bool {
+    let flag: BOOL = unsafe {
+        match feature {
+            TextCheckingFeature::SpellChecking => {
+                msg_send![webview, isContinuousSpellCheckingEnabled]
+            }
+            TextCheckingFeature::SmartQuotes => {
+                msg_send![webview, isAutomaticQuoteSubstitutionEnabled]
+            }
+            TextCheckingFeature::TextReplacement => {
+                msg_send![webview, isAutomaticTextReplacementEnabled]
+            }
+        }
+    };
+
+    flag == YES
+}
+
+/// WKWebView only exposes these through the toggle actions behind the Edit > Spelling and
+/// Substitutions menus
+fn toggle(webview: id, feature: TextCheckingFeature) {
+    let _: () = unsafe {
+        match feature {
+            TextCheckingFeature::SpellChecking => {
+                msg_send![webview, toggleContinuousSpellChecking: nil]
+            }
+            TextCheckingFeature::SmartQuotes => {
+                msg_send![webview, toggleAutomaticQuoteSubstitution: nil]
+            }
+            TextCheckingFeature::TextReplacement => {
+                msg_send![webview, toggleAutomaticTextReplacement: nil]
+            }
+        }
+    };
+}
+
+/// Sets the page zoom of the calling window's webview, `1.0` being 100%, and remembers it for
+/// that window across launches
+#[tauri::command]
+pub fn set_webview_zoom(
+    app_handle: AppHandle<Wry>,
+    window: Window<Wry>,
+    factor: f64,
+) -> Result<(), String> {
+    if !(MIN_ZOOM..=MAX_ZOOM).contains(&factor) {
+        return Err(format!(
+            "Zoom must be between {} and {}",
+            MIN_ZOOM, MAX_ZOOM
+        ));
+    }
+
+    let mut settings = settings::settings(&app_handle);
+    if factor == 1.0 {
+        settings.webview_zoom.remove(window.label());
+    } else {
+        settings
+            .webview_zoom
+            .insert(window.label().to_string(), factor);
+    }
+    settings::set_settings(app_handle, settings)?;
+
+    set_page_zoom(&window, factor)
+}
+
+/// Returns the page zoom remembered for the calling window
+#[tauri::command]
+pub fn get_webview_zoom(app_handle: AppHandle<Wry>, window: Window<Wry>) -> f64 {
+    zoom_for(&app_handle, &window)
+}
+
+/// Applies the zoom remembered for a window, called whenever one of the app's pages loads
+pub fn restore_webview_zoom(window: &Window<Wry>) {
+    let factor = zoom_for(&window.app_handle(), window);
+
+    if factor != 1.0 {
+        let _ = set_page_zoom(window, factor);
+    }
+}
+
+fn zoom_for(app_handle: &AppHandle<Wry>, window: &Window<Wry>) -> f64 {
+    settings::settings(app_handle)
+        .webview_zoom
+        .get(window.label())
+        .copied()
+        .unwrap_or(1.0)
+}
+
+fn set_page_zoom(window: &Window<Wry>, factor: f64) -> Result<(), String> {
+    window
+        .with_webview(move |webview| {
+            let _: () = unsafe { msg_send![webview.inner(), setPageZoom: factor] };
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Clears the panel webview's cache and storage, then reloads it.
+///
+/// Removes every website data type (HTTP cache, cookies, local storage, IndexedDB, service
+/// workers, ...) so a long running agent can recover from stale state without a restart.
+#[tauri::command]
+pub fn reset_webview_data(app_handle: AppHandle<Wry>) {
+    let webview = webview!(app_handle);
+
+    unsafe {
+        let configuration: id = msg_send![webview.as_id(), configuration];
+        let data_store: id = msg_send![configuration, websiteDataStore];
+        let data_types: id = msg_send![class!(WKWebsiteDataStore), allWebsiteDataTypes];
+        let since: id = msg_send![class!(NSDate), distantPast];
+
+        let completion_handler = ConcreteBlock::new(move || {
+            let _: id = msg_send![webview.as_id(), reload];
+        })
+        .copy();
+
+        let _: () = msg_send![
+            data_store,
+            removeDataOfTypes: data_types
+            modifiedSince: since
+            completionHandler: &*completion_handler
+        ];
+    }
+}
+
+/// Drops the webview's in-memory cache, keeping storage and the on-disk cache intact
+pub fn purge_memory_cache(app_handle: &AppHandle<Wry>) {
+    let webview = match get_state!(app_handle, webview, clone) {
+        Some(webview) => webview,
+        None => return,
+    };
+
+    unsafe {
+        let configuration: id = msg_send![webview.as_id(), configuration];
+        let data_store: id = msg_send![configuration, websiteDataStore];
+        let memory_cache = NSString::alloc(nil).init_str("WebKitMemoryCache");
+        let data_types: id = msg_send![class!(NSSet), setWithObject: memory_cache];
+        let since: id = msg_send![class!(NSDate), distantPast];
+        let completion_handler = ConcreteBlock::new(|| {}).copy();
+
+        let _: () = msg_send![
+            data_store,
+            removeDataOfTypes: data_types
+            modifiedSince: since
+            completionHandler: &*completion_handler
+        ];
+    }
+}
+
+type WebContentProcessDidTerminate = extern "C" fn(id, Sel, id);
+
+/// Reloads the webview when its content process terminates (crash, jetsam, ...), which would
+/// otherwise leave a blank panel behind a working shortcut.
+///
+/// Implemented as `webViewWebContentProcessDidTerminate:` on wry's navigation delegate class.
+/// The frontend initializes again once reloaded; `spotlight://webview-crashed` is emitted so other
+/// windows can tell.
+pub(crate) fn install_crash_recovery(app_handle: AppHandle<Wry>, webview: id) {
+    *CRASH_RECOVERY_APP_HANDLE.lock().unwrap() = Some(app_handle.clone());
+
+    let delegate: id = unsafe { msg_send![webview, navigationDelegate] };
+    if delegate == nil {
+        diagnostics::warn(
+            &app_handle,
+            "crashRecoveryUnavailable",
+            "Unable to install crash recovery, the webview has no navigation delegate",
+        );
+        return;
+    }
+
+    let cls: *const Class = unsafe { msg_send![delegate, class] };
+    let sel = sel!(webViewWebContentProcessDidTerminate:);
+    let types = CString::new("v@:@").unwrap();
+
+    unsafe {
+        let imp: Imp =
+            mem::transmute(web_content_process_did_terminate as WebContentProcessDidTerminate);
+
+        // Already there when the panel's webview is recreated, the delegate class is shared
+        if runtime::class_addMethod(cls as *mut Class, sel, imp, types.as_ptr()) == NO
+            && !text_input::has_implementation(cls, sel, imp)
+        {
+            diagnostics::warn(
+                &app_handle,
+                "crashRecoveryUnavailable",
+                "Unable to install crash recovery on the webview's navigation delegate",
+            );
+        }
+    }
+}
+
+extern "C" fn web_content_process_did_terminate(_: id, _: Sel, webview: id) {
+    if let Some(app_handle) = CRASH_RECOVERY_APP_HANDLE.lock().unwrap().as_ref() {
+        let _ = events::emit(app_handle, WEBVIEW_CRASHED_EVENT, ());
+    }
+
+    let _: id = unsafe { msg_send![webview, reload] };
+}
@@ -0,0 +1,312 @@
+use std::ffi::c_void;
+
+use cocoa::{
+    base::{id, nil, BOOL, YES},
+    foundation::{NSPoint, NSRect, NSSize, NSString},
+};
+use objc::{class, msg_send, rc::autoreleasepool, sel, sel_impl};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Wry};
+
+use crate::{accessibility, focus, nsstring_to_string, screen::ScreenRect};
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGWindowListCopyWindowInfo(option: u32, relative_to_window: u32) -> *const c_void;
+    fn CGRectMakeWithDictionaryRepresentation(dict: id, rect: *mut NSRect) -> bool;
+}
+
+/// kCGWindowListOptionAll, includes windows on other Spaces and minimized ones
+const WINDOW_LIST_ALL: u32 = 0;
+/// kCGWindowListOptionOnScreenOnly | kCGWindowListExcludeDesktopElements
+const WINDOW_LIST_ON_SCREEN: u32 = (1 << 0) | (1 << 4);
+
+/// Windows smaller than this are helper windows rather than something worth switching to
+const MIN_WINDOW_SIZE: f64 = 50.0;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppWindow {
+    /// CGWindowID, stable for the lifetime of the window
+    id: Option<u32>,
+    /// Needs accessibility access, or screen recording access for windows on other Spaces
+    title: Option<String>,
+    minimized: bool,
+    /// Whether the window is on the current Space. Windows on other Spaces are only listed by
+    /// the window server, not the app's accessibility tree.
+    on_current_space: bool,
+    /// `null` for windows on other Spaces that don't report their bounds
+    bounds: Option<ScreenRect>,
+}
+
+/// Where `snap_window_behind` puts the window, within the visible area of its screen
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SnapPosition {
+    LeftHalf,
+    RightHalf,
+    Maximize,
+    /// Keeps the window's size, shrunk to fit if needed
+    Center,
+}
+
+impl SnapPosition {
+    fn frame(self, window: NSRect, screen: NSRect) -> NSRect {
+        let NSRect { origin, size } = screen;
+        let half_width = (size.width / 2.0).floor();
+
+        match self {
+            SnapPosition::LeftHalf => NSRect::new(origin, NSSize::new(half_width, size.height)),
+            SnapPosition::RightHalf => NSRect::new(
+                NSPoint::new(origin.x + size.width - half_width, origin.y),
+                NSSize::new(half_width, size.height),
+            ),
+            SnapPosition::Maximize => screen,
+            SnapPosition::Center => {
+                let width = window.size.width.min(size.width);
+                let height = window.size.height.min(size.height);
+
+                NSRect::new(
+                    NSPoint::new(
+                        origin.x + ((size.width - width) / 2.0).floor(),
+                        origin.y + ((size.height - height) / 2.0).floor(),
+                    ),
+                    NSSize::new(width, height),
+                )
+            }
+        }
+    }
+}
+
+/// A window from the window server's list
+pub(crate) struct WindowInfo {
+    id: u32,
+    title: Option<String>,
+    on_screen: bool,
+    /// In global display coordinates, origin at the top left of the main display
+    pub(crate) bounds: Option<NSRect>,
+}
+
+/// Lists the windows of an app, front to back, combining the window server's list with the
+/// app's accessibility tree.
+///
+/// Without accessibility access only windows known to the window server are listed, with the
+/// titles it exposes.
+#[tauri::command]
+pub fn list_windows_for_app(pid: i32) -> Vec<AppWindow> {
+    autoreleasepool(|| {
+        let mut infos = window_infos(pid, false);
+
+        let app = accessibility::application_element(pid);
+        let ax_windows = accessibility::attribute(app, "AXWindows");
+        let count: usize = if ax_windows == nil {
+            0
+        } else {
+            unsafe { msg_send![ax_windows, count] }
+        };
+
+        let mut windows: Vec<AppWindow> = (0..count)
+            .map(|index| {
+                let window: id = unsafe { msg_send![ax_windows, objectAtIndex: index] };
+                let id = accessibility::window_id(window);
+                let info = id.and_then(|id| {
+                    let position = infos.iter().position(|info| info.id == id)?;
+                    Some(infos.remove(position))
+                });
+
+                let title = accessibility::attribute(window, "AXTitle");
+                let minimized = accessibility::attribute(window, "AXMinimized");
+                let minimized = minimized != nil && {
+                    let minimized: BOOL = unsafe { msg_send![minimized, boolValue] };
+                    minimized == YES
+                };
+
+                let bounds = match (accessibility::position(window), accessibility::size(window)) {
+                    (Some(position), Some(size)) => Some(NSRect::new(position, size)),
+                    _ => info.as_ref().and_then(|info| info.bounds),
+                };
+
+                AppWindow {
+                    id,
+                    title: nsstring_to_string!(title)
+                        .or_else(|| info.as_ref().and_then(|info| info.title.clone())),
+                    minimized,
+                    on_current_space: true,
+                    bounds: bounds.map(ScreenRect::from),
+                }
+            })
+            .collect();
+
+        // What's left is on other Spaces, or everything when accessibility access is missing
+        windows.extend(
+            infos
+                .into_iter()
+                .filter(|info| {
+                    info.bounds.map_or(true, |bounds| {
+                        bounds.size.width >= MIN_WINDOW_SIZE
+                            && bounds.size.height >= MIN_WINDOW_SIZE
+                    })
+                })
+                .map(|info| AppWindow {
+                    id: Some(info.id),
+                    title: info.title,
+                    minimized: false,
+                    on_current_space: info.on_screen,
+                    bounds: info.bounds.map(ScreenRect::from),
+                }),
+        );
+
+        windows
+    })
+}
+
+/// Moves and resizes the focused window of the app behind the panel, or of the frontmost app once
+/// the panel is hidden. Needs accessibility access.
+#[tauri::command]
+pub fn snap_window_behind(
+    app_handle: AppHandle<Wry>,
+    position: SnapPosition,
+) -> Result<(), String> {
+    let pid = focus::focus_target_pid(&app_handle)
+        .or_else(frontmost_app_pid)
+        .ok_or("No window to snap")?;
+
+    autoreleasepool(|| {
+        let app = accessibility::application_element(pid);
+        let window = accessibility::attribute(app, "AXFocusedWindow");
+        if window == nil {
+            return Err("No window to snap, accessibility access may be missing".into());
+        }
+
+        let (origin, size) = match (accessibility::position(window), accessibility::size(window)) {
+            (Some(origin), Some(size)) => (origin, size),
+            _ => return Err("Unable to read the window's frame".into()),
+        };
+        let frame = NSRect::new(origin, size);
+        let target = position.frame(frame, visible_screen_frame(frame));
+
+        // Resizing first keeps the window from being pushed back when it grows past the screen
+        // edge, the second resize applies sizes the old position didn't allow
+        accessibility::set_size(window, target.size)?;
+        accessibility::set_position(window, target.origin)?;
+        accessibility::set_size(window, target.size)
+    })
+}
+
+fn frontmost_app_pid() -> Option<i32> {
+    let app: id = unsafe {
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        msg_send![workspace, frontmostApplication]
+    };
+
+    if app == nil {
+        return None;
+    }
+
+    let pid: i32 = unsafe { msg_send![app, processIdentifier] };
+    (pid != std::process::id() as i32).then_some(pid)
+}
+
+/// Visible frame, without the menu bar and Dock, of the screen containing most of a window. Both
+/// in global coordinates with the origin at the top left.
+fn visible_screen_frame(window: NSRect) -> NSRect {
+    let center = NSPoint::new(
+        window.origin.x + window.size.width / 2.0,
+        window.origin.y + window.size.height / 2.0,
+    );
+
+    let frames: Vec<(NSRect, NSRect)> = unsafe {
+        let screens: id = msg_send![class!(NSScreen), screens];
+        let count: usize = msg_send![screens, count];
+
+        (0..count)
+            .map(|index| {
+                let screen: id = msg_send![screens, objectAtIndex: index];
+                let frame: NSRect = msg_send![screen, frame];
+                let visible_frame: NSRect = msg_send![screen, visibleFrame];
+                (
+                    ScreenRect::from_frame(frame).into(),
+                    ScreenRect::from_frame(visible_frame).into(),
+                )
+            })
+            .collect()
+    };
+
+    frames
+        .iter()
+        .find(|(frame, _)| {
+            center.x >= frame.origin.x
+                && center.x < frame.origin.x + frame.size.width
+                && center.y >= frame.origin.y
+                && center.y < frame.origin.y + frame.size.height
+        })
+        .or_else(|| frames.first())
+        .map(|(_, visible_frame)| *visible_frame)
+        .unwrap_or(window)
+}
+
+/// Normal windows of a process known to the window server, front to back
+/// Normal windows of a process from the window server's list, front to back. `on_screen_only`
+/// leaves out windows on other Spaces and minimized ones.
+pub(crate) fn window_infos(pid: i32, on_screen_only: bool) -> Vec<WindowInfo> {
+    let option = if on_screen_only {
+        WINDOW_LIST_ON_SCREEN
+    } else {
+        WINDOW_LIST_ALL
+    };
+
+    let windows = unsafe { CGWindowListCopyWindowInfo(option, 0) } as id;
+    if windows == nil {
+        return Vec::new();
+    }
+
+    unsafe {
+        let windows: id = msg_send![windows, autorelease];
+        let count: usize = msg_send![windows, count];
+
+        (0..count)
+            .filter_map(|index| {
+                let window: id = msg_send![windows, objectAtIndex: index];
+
+                let owner_pid: i32 = msg_send![value(window, "kCGWindowOwnerPID"), intValue];
+                let layer: i32 = msg_send![value(window, "kCGWindowLayer"), intValue];
+                if owner_pid != pid || layer != 0 {
+                    return None;
+                }
+
+                let id: u32 = msg_send![value(window, "kCGWindowNumber"), unsignedIntValue];
+                let on_screen: BOOL = msg_send![value(window, "kCGWindowIsOnscreen"), boolValue];
+
+                let mut bounds = NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(0.0, 0.0));
+                let has_bounds = CGRectMakeWithDictionaryRepresentation(
+                    value(window, "kCGWindowBounds"),
+                    &mut bounds,
+                );
+
+                Some((
+                    id,
+                    value(window, "kCGWindowName"),
+                    on_screen,
+                    has_bounds,
+                    bounds,
+                ))
+            })
+            .collect::<Vec<_>>()
+    }
+    .into_iter()
+    .map(|(id, title, on_screen, has_bounds, bounds)| WindowInfo {
+        id,
+        title: nsstring_to_string!(title).filter(|title| !title.is_empty()),
+        on_screen: on_screen == YES,
+        bounds: has_bounds.then_some(bounds),
+    })
+    .collect()
+}
+
+fn value(dictionary: id, key: &str) -> id {
+    unsafe {
+        let key = NSString::alloc(nil).init_str(key);
+        let key: id = msg_send![key, autorelease];
+        msg_send![dictionary, objectForKey: key]
+    }
+}
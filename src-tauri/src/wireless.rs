@@ -0,0 +1,125 @@
+use cocoa::base::{id, nil, BOOL, NO, YES};
+use objc::{class, msg_send, rc::autoreleasepool, sel, sel_impl};
+use serde::Serialize;
+
+use crate::{nsstring_to_string, spotlight::ns_error_to_result};
+
+#[link(name = "CoreWLAN", kind = "framework")]
+extern "C" {}
+
+#[link(name = "IOBluetooth", kind = "framework")]
+extern "C" {
+    // Not in the public headers, but the only way to switch Bluetooth without System Settings
+    fn IOBluetoothPreferenceGetControllerPowerState() -> i32;
+    fn IOBluetoothPreferenceSetControllerPowerState(state: i32);
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WirelessStatus {
+    wifi: WifiStatus,
+    bluetooth: BluetoothStatus,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WifiStatus {
+    /// Whether the Mac has a Wi-Fi interface at all
+    available: bool,
+    powered: bool,
+    /// Network the Mac is connected to. Since macOS 14 this needs Location Services access,
+    /// `null` without it.
+    ssid: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BluetoothStatus {
+    /// Whether the Mac has a Bluetooth controller at all
+    available: bool,
+    powered: bool,
+}
+
+/// Returns whether Wi-Fi and Bluetooth are on, and the Wi-Fi network's name
+#[tauri::command]
+pub fn get_wireless_status() -> WirelessStatus {
+    WirelessStatus {
+        wifi: wifi_status(),
+        bluetooth: bluetooth_status(),
+    }
+}
+
+/// Turns Wi-Fi on or off
+#[tauri::command]
+pub fn set_wifi_enabled(enabled: bool) -> Result<(), String> {
+    autoreleasepool(|| unsafe {
+        let interface = wifi_interface();
+        if interface == nil {
+            return Err("No Wi-Fi interface found".into());
+        }
+
+        let mut error: id = nil;
+        let power: BOOL = if enabled { YES } else { NO };
+        let success: BOOL = msg_send![interface, setPower: power error: &mut error];
+
+        if success == NO {
+            ns_error_to_result(error)?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Turns Bluetooth on or off
+#[tauri::command]
+pub fn set_bluetooth_enabled(enabled: bool) -> Result<(), String> {
+    if !bluetooth_available() {
+        return Err("No Bluetooth controller found".into());
+    }
+
+    unsafe { IOBluetoothPreferenceSetControllerPowerState(enabled as i32) };
+
+    Ok(())
+}
+
+fn wifi_status() -> WifiStatus {
+    autoreleasepool(|| {
+        let interface = unsafe { wifi_interface() };
+        if interface == nil {
+            return WifiStatus {
+                available: false,
+                powered: false,
+                ssid: None,
+            };
+        }
+
+        let (powered, ssid): (BOOL, id) =
+            unsafe { (msg_send![interface, powerOn], msg_send![interface, ssid]) };
+
+        WifiStatus {
+            available: true,
+            powered: powered == YES,
+            ssid: nsstring_to_string!(ssid),
+        }
+    })
+}
+
+/// The default Wi-Fi interface, `nil` on Macs without one
+unsafe fn wifi_interface() -> id {
+    let client: id = msg_send![class!(CWWiFiClient), sharedWiFiClient];
+    msg_send![client, interface]
+}
+
+fn bluetooth_status() -> BluetoothStatus {
+    let available = bluetooth_available();
+
+    BluetoothStatus {
+        available,
+        powered: available && unsafe { IOBluetoothPreferenceGetControllerPowerState() } != 0,
+    }
+}
+
+fn bluetooth_available() -> bool {
+    let controller: id = unsafe { msg_send![class!(IOBluetoothHostController), defaultController] };
+    controller != nil
+}
@@ -1,11 +1,13 @@
 import { useEffect } from "react";
 import { invoke } from "@tauri-apps/api/tauri";
 import useEscape from "./hooks/useEscape";
+import useTextInputRect from "./hooks/useTextInputRect";
 
 import "./App.css";
 
 function App() {
   useEscape();
+  useTextInputRect();
   useEffect(() => {
     invoke("init_spotlight_window");
   }, []);
@@ -0,0 +1,72 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/tauri";
+import { listen } from "@tauri-apps/api/event";
+
+import "./App.css";
+
+type Permissions = {
+  accessibility: boolean;
+  screenRecording: boolean;
+};
+
+type Permission = keyof Permissions;
+
+const steps: { permission: Permission; title: string; description: string }[] =
+  [
+    {
+      permission: "accessibility",
+      title: "Accessibility",
+      description: "Lets the app read and focus the window behind the panel.",
+    },
+    {
+      permission: "screenRecording",
+      title: "Screen Recording",
+      description: "Lets the app recognize text and pick colors on screen.",
+    },
+  ];
+
+function Onboarding() {
+  const [permissions, setPermissions] = useState<Permissions>();
+
+  useEffect(() => {
+    invoke<Permissions>("get_permissions").then(setPermissions);
+
+    const unlisten = listen<Permissions>(
+      "spotlight://permissions-changed",
+      (event) => setPermissions(event.payload)
+    );
+
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  if (!permissions) return null;
+
+  const allGranted = steps.every(({ permission }) => permissions[permission]);
+
+  return (
+    <div className="container onboarding">
+      <h2>Welcome</h2>
+      <p>Grant the following permissions to get the most out of the app.</p>
+      {steps.map(({ permission, title, description }) => (
+        <section key={permission}>
+          <h4>
+            {title} {permissions[permission] ? "✓" : ""}
+          </h4>
+          <p>{description}</p>
+          {!permissions[permission] && (
+            <button onClick={() => invoke("request_permission", { permission })}>
+              Open System Settings
+            </button>
+          )}
+        </section>
+      ))}
+      <button onClick={() => invoke("finish_onboarding")}>
+        {allGranted ? "Done" : "Skip for now"}
+      </button>
+    </div>
+  );
+}
+
+export default Onboarding;
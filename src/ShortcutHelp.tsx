@@ -0,0 +1,66 @@
+import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+
+import "./App.css";
+
+type ShortcutHelpItem = {
+  keys: string;
+  description: string;
+};
+
+type ShortcutHelpSection = {
+  title: string;
+  items: ShortcutHelpItem[];
+};
+
+type ShortcutHelpPayload = {
+  toggleShortcut: string;
+  sections: ShortcutHelpSection[];
+};
+
+function ShortcutHelp() {
+  const [help, setHelp] = useState<ShortcutHelpPayload>();
+
+  useEffect(() => {
+    const unlisten = listen<ShortcutHelpPayload>(
+      "spotlight://shortcut-help",
+      (event) => setHelp(event.payload)
+    );
+
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  if (!help) return null;
+
+  return (
+    <div className="shortcut-help">
+      <dl>
+        <div>
+          <dt>
+            <kbd>{help.toggleShortcut}</kbd>
+          </dt>
+          <dd>Toggle the spotlight window</dd>
+        </div>
+      </dl>
+      {help.sections.map((section) => (
+        <section key={section.title}>
+          <h4>{section.title}</h4>
+          <dl>
+            {section.items.map((item) => (
+              <div key={item.keys}>
+                <dt>
+                  <kbd>{item.keys}</kbd>
+                </dt>
+                <dd>{item.description}</dd>
+              </div>
+            ))}
+          </dl>
+        </section>
+      ))}
+    </div>
+  );
+}
+
+export default ShortcutHelp;
@@ -0,0 +1,26 @@
+import { invoke } from "@tauri-apps/api/tauri";
+import { useEffect } from "react";
+
+const useTextInputRect = () => {
+  const handleFocusIn = (event: FocusEvent) => {
+    if (!(event.target instanceof HTMLInputElement)) return;
+
+    const { x, y, width, height } = event.target.getBoundingClientRect();
+    invoke("set_text_input_rect", { rect: { x, y, width, height } });
+  };
+
+  const handleFocusOut = () => {
+    invoke("set_text_input_rect", { rect: null });
+  };
+
+  useEffect(() => {
+    window.addEventListener("focusin", handleFocusIn);
+    window.addEventListener("focusout", handleFocusOut);
+    return () => {
+      window.removeEventListener("focusin", handleFocusIn);
+      window.removeEventListener("focusout", handleFocusOut);
+    };
+  }, []);
+};
+
+export default useTextInputRect;
@@ -1,10 +1,19 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
+import { appWindow } from "@tauri-apps/api/window";
 import App from "./App";
+import Onboarding from "./Onboarding";
+import ShortcutHelp from "./ShortcutHelp";
 import "./style.css";
 
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
   <React.StrictMode>
-    <App />
+    {appWindow.label === "help" ? (
+      <ShortcutHelp />
+    ) : appWindow.label === "onboarding" ? (
+      <Onboarding />
+    ) : (
+      <App />
+    )}
   </React.StrictMode>
 );